@@ -0,0 +1,8 @@
+-- Add migration script here
+ALTER TABLE session ADD COLUMN refresh_token_hash TEXT;
+ALTER TABLE session ADD COLUMN refresh_expires_at TIMESTAMPTZ;
+ALTER TABLE session ADD COLUMN user_agent TEXT;
+ALTER TABLE session ADD COLUMN ip_address TEXT;
+ALTER TABLE session ADD COLUMN last_seen_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX session_refresh_token_hash_idx ON session (refresh_token_hash);
@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE api_keys(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    label TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    scope TEXT NOT NULL CHECK (scope IN ('public', 'user', 'admin')),
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE login_attempt(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    email TEXT NOT NULL,
+    ip_address TEXT,
+    outcome TEXT NOT NULL CHECK (outcome IN ('success', 'failure', 'locked_out')),
+    attempted_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX login_attempt_email_idx ON login_attempt (email, attempted_at);
+CREATE INDEX login_attempt_ip_address_idx ON login_attempt (ip_address, attempted_at);
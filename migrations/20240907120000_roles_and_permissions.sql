@@ -0,0 +1,38 @@
+-- Add migration script here
+CREATE TABLE roles(
+    name TEXT NOT NULL,
+    PRIMARY KEY (name),
+    description TEXT NOT NULL
+);
+
+CREATE TABLE role_permissions(
+    role TEXT NOT NULL REFERENCES roles (name) ON UPDATE CASCADE ON DELETE CASCADE,
+    permission TEXT NOT NULL,
+    PRIMARY KEY (role, permission)
+);
+
+CREATE TABLE user_roles(
+    user_id uuid NOT NULL REFERENCES users (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    role TEXT NOT NULL REFERENCES roles (name) ON UPDATE CASCADE ON DELETE CASCADE,
+    PRIMARY KEY (user_id, role)
+);
+
+INSERT INTO roles (name, description) VALUES
+    ('super_admin', 'Can do everything, including managing users and API keys.'),
+    ('data_steward', 'Can add, edit and delete fish samples.'),
+    ('content_editor', 'Can edit recipes and fish type descriptions.');
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('super_admin', 'manage_fish'),
+    ('super_admin', 'manage_fish_types'),
+    ('super_admin', 'manage_recipes'),
+    ('super_admin', 'manage_users'),
+    ('super_admin', 'manage_api_keys'),
+    ('super_admin', 'view_analytics'),
+    ('data_steward', 'manage_fish'),
+    ('content_editor', 'manage_fish_types'),
+    ('content_editor', 'manage_recipes');
+
+-- users.is_admin is replaced by roles, existing admins keep full access.
+INSERT INTO user_roles (user_id, role)
+SELECT id, 'super_admin' FROM users WHERE is_admin;
@@ -0,0 +1,29 @@
+-- Add migration script here
+CREATE TABLE audit_log(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    -- Not a foreign key, the log has to outlive the users in it.
+    actor_id uuid NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('create', 'update', 'delete')),
+    entity TEXT NOT NULL,
+    entity_id uuid NOT NULL,
+    before JSONB,
+    after JSONB,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX audit_log_entity_idx ON audit_log (entity, entity_id, created_at);
+CREATE INDEX audit_log_actor_idx ON audit_log (actor_id, created_at);
+
+-- The log is append only.
+CREATE FUNCTION reject_audit_log_changes() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION reject_audit_log_changes();
+
+INSERT INTO role_permissions (role, permission) VALUES ('super_admin', 'view_audit_log');
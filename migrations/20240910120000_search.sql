@@ -0,0 +1,32 @@
+-- Add migration script here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- The search vectors are built by functions so the same expression can be used
+-- in the indexes and the queries. Names aren't stemmed, most of the Anishinaabe
+-- names would be mangled by the english dictionary.
+CREATE FUNCTION fish_type_search_vector(name TEXT, anishinaabe_name TEXT, about TEXT)
+RETURNS tsvector AS $$
+    SELECT
+        setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(anishinaabe_name, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(about, '')), 'C')
+$$ LANGUAGE SQL IMMUTABLE;
+
+-- array_to_string is only marked stable, but joining text arrays doesn't depend
+-- on any settings.
+CREATE FUNCTION recipe_search_vector(name TEXT, ingredients TEXT[], steps TEXT[])
+RETURNS tsvector AS $$
+    SELECT
+        setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(array_to_string(ingredients, ' '), '')), 'B') ||
+        setweight(to_tsvector('english', coalesce(array_to_string(steps, ' '), '')), 'D')
+$$ LANGUAGE SQL IMMUTABLE;
+
+CREATE INDEX fish_type_search_idx ON fish_type
+    USING GIN (fish_type_search_vector(name, anishinaabe_name, about));
+CREATE INDEX recipe_search_idx ON recipe
+    USING GIN (recipe_search_vector(name, ingredients, steps));
+CREATE INDEX fish_type_name_trgm_idx ON fish_type USING GIN (name gin_trgm_ops);
+CREATE INDEX fish_type_anishinaabe_name_trgm_idx ON fish_type
+    USING GIN (anishinaabe_name gin_trgm_ops);
+CREATE INDEX recipe_name_trgm_idx ON recipe USING GIN (name gin_trgm_ops);
@@ -0,0 +1,17 @@
+-- Add migration script here
+CREATE TABLE meal_log(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    user_id uuid NOT NULL REFERENCES users (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    fish_type_id uuid NOT NULL REFERENCES fish_type (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    -- Set when the user knows which sample they ate, otherwise the averages of
+    -- the fish type (in the lake, if given) are used.
+    fish_id uuid REFERENCES fish (id) ON UPDATE CASCADE ON DELETE SET NULL,
+    lake TEXT,
+    eaten_on DATE NOT NULL,
+    -- In ounces, like users.portion_size.
+    portion_size SMALLINT NOT NULL CHECK (portion_size > 0),
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX meal_log_user_idx ON meal_log (user_id, eaten_on);
@@ -0,0 +1,39 @@
+-- Add migration script here
+CREATE TABLE lake(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    name TEXT NOT NULL UNIQUE,
+    anishinaabe_name TEXT,
+    region TEXT,
+    description TEXT,
+    -- Centroid of the lake, in WGS 84 degrees.
+    latitude DOUBLE PRECISION CHECK (latitude BETWEEN -90 AND 90),
+    longitude DOUBLE PRECISION CHECK (longitude BETWEEN -180 AND 180),
+    image_url TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((latitude IS NULL) = (longitude IS NULL))
+);
+
+INSERT INTO lake (id, name, anishinaabe_name, region, description, latitude, longitude) VALUES
+    (gen_random_uuid(), 'Store', NULL, NULL, 'Fish bought at a store.', NULL, NULL),
+    (gen_random_uuid(), 'Superior', 'Gichigami', 'Great Lakes', NULL, 47.7, -87.5),
+    (gen_random_uuid(), 'Huron', 'Naadowewi-gichigami', 'Great Lakes', NULL, 44.8, -82.4),
+    (gen_random_uuid(), 'Michigan', 'Ininwewi-gichigami', 'Great Lakes', NULL, 44.0, -87.0),
+    (gen_random_uuid(), 'Inland', NULL, 'Inland lakes', 'Inland lakes in the Great Lakes basin.', NULL, NULL);
+
+-- Keep any lake already used by a sample or meal so the foreign keys can be added.
+INSERT INTO lake (id, name)
+SELECT gen_random_uuid(), name
+FROM (SELECT lake FROM fish UNION SELECT lake FROM meal_log WHERE lake IS NOT NULL) AS used (name)
+ON CONFLICT (name) DO NOTHING;
+
+-- Renaming a lake renames it everywhere, a lake with samples can't be deleted.
+ALTER TABLE fish
+    ADD CONSTRAINT fish_lake_fkey FOREIGN KEY (lake) REFERENCES lake (name) ON UPDATE CASCADE;
+ALTER TABLE meal_log
+    ADD CONSTRAINT meal_log_lake_fkey FOREIGN KEY (lake) REFERENCES lake (name)
+        ON UPDATE CASCADE ON DELETE SET NULL;
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('super_admin', 'manage_lakes'),
+    ('data_steward', 'manage_lakes');
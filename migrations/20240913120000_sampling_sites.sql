@@ -0,0 +1,23 @@
+-- Add migration script here
+CREATE TABLE sampling_site(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    name TEXT NOT NULL,
+    -- e.g. a bay or river mouth, more specific than the lake.
+    water_body TEXT NOT NULL,
+    lake TEXT REFERENCES lake (name) ON UPDATE CASCADE,
+    -- Who collected the samples, e.g. GLIFWC or EPA.
+    agency TEXT,
+    latitude DOUBLE PRECISION NOT NULL CHECK (latitude BETWEEN -90 AND 90),
+    longitude DOUBLE PRECISION NOT NULL CHECK (longitude BETWEEN -180 AND 180),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- The nearest sites lookup narrows the search down by latitude before working
+-- out the exact distances.
+CREATE INDEX sampling_site_latitude_idx ON sampling_site (latitude);
+
+ALTER TABLE fish
+ADD COLUMN sampling_site_id uuid REFERENCES sampling_site (id) ON UPDATE CASCADE ON DELETE SET NULL;
+
+CREATE INDEX fish_sampling_site_idx ON fish (sampling_site_id);
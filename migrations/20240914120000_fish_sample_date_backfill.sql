@@ -0,0 +1,8 @@
+-- Add migration script here
+-- fish.date_sampled was never written by the API, fish.sample_date is the one
+-- used from now on.
+UPDATE fish
+SET sample_date = date_sampled::DATE
+WHERE sample_date IS NULL AND date_sampled IS NOT NULL;
+
+CREATE INDEX fish_type_sample_date_idx ON fish (fish_type_id, sample_date);
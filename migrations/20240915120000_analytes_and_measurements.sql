@@ -0,0 +1,124 @@
+-- Add migration script here
+CREATE TABLE analyte(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    name TEXT NOT NULL UNIQUE,
+    display_name TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('contaminant', 'nutrient')),
+    unit TEXT NOT NULL,
+    -- In µg/kg/day, contaminants only.
+    reference_dose DOUBLE PRECISION CHECK (reference_dose > 0),
+    -- In the analyte's unit, the usual limit of the lab methods used.
+    detection_limit DOUBLE PRECISION CHECK (detection_limit > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+INSERT INTO analyte (id, name, display_name, kind, unit, reference_dose) VALUES
+    (gen_random_uuid(), 'mercury', 'Mercury', 'contaminant', 'ppm', 0.1),
+    (gen_random_uuid(), 'pcb', 'PCBs', 'contaminant', 'ppm', 0.02),
+    (gen_random_uuid(), 'omega_3', 'Omega-3', 'nutrient', 'g/100g', NULL),
+    (gen_random_uuid(), 'omega_3_ratio', 'Omega-3 ratio', 'nutrient', 'ratio', NULL),
+    (gen_random_uuid(), 'protein', 'Protein', 'nutrient', 'g/100g', NULL),
+    (gen_random_uuid(), 'pfas', 'PFAS', 'contaminant', 'ppb', 0.0001),
+    (gen_random_uuid(), 'selenium', 'Selenium', 'nutrient', 'ppm', 5),
+    (gen_random_uuid(), 'dioxins', 'Dioxins (TEQ)', 'contaminant', 'ppt', 0.0000007),
+    (gen_random_uuid(), 'lead', 'Lead', 'contaminant', 'ppm', NULL),
+    (gen_random_uuid(), 'vitamin_d', 'Vitamin D', 'nutrient', 'IU/100g', NULL);
+
+CREATE TABLE measurement(
+    fish_id uuid NOT NULL REFERENCES fish (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    analyte TEXT NOT NULL REFERENCES analyte (name) ON UPDATE CASCADE,
+    PRIMARY KEY (fish_id, analyte),
+    value DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX measurement_analyte_idx ON measurement (analyte);
+
+-- The legacy columns are REAL, going through NUMERIC keeps e.g. 0.1 as 0.1
+-- rather than 0.10000000149.
+INSERT INTO measurement (fish_id, analyte, value)
+SELECT fish.id, legacy.analyte, legacy.value
+FROM fish
+CROSS JOIN LATERAL (
+    VALUES
+        ('mercury', fish.mercury::NUMERIC::FLOAT8),
+        ('pcb', fish.pcb::NUMERIC::FLOAT8),
+        ('omega_3', fish.omega_3::NUMERIC::FLOAT8),
+        ('omega_3_ratio', fish.omega_3_ratio::NUMERIC::FLOAT8),
+        ('protein', fish.protein::NUMERIC::FLOAT8)
+) AS legacy (analyte, value)
+WHERE legacy.value IS NOT NULL;
+
+-- measurement is the source of truth. The five legacy columns on fish are kept
+-- for the existing endpoints, and kept in sync both ways by the triggers below.
+-- Each trigger ignores changes made by the other one.
+CREATE FUNCTION sync_legacy_measurements() RETURNS TRIGGER AS $$
+BEGIN
+    IF pg_trigger_depth() > 1 THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO measurement (fish_id, analyte, value)
+    SELECT NEW.id, legacy.analyte, legacy.value
+    FROM (
+        VALUES
+            ('mercury', NEW.mercury::NUMERIC::FLOAT8),
+            ('pcb', NEW.pcb::NUMERIC::FLOAT8),
+            ('omega_3', NEW.omega_3::NUMERIC::FLOAT8),
+            ('omega_3_ratio', NEW.omega_3_ratio::NUMERIC::FLOAT8),
+            ('protein', NEW.protein::NUMERIC::FLOAT8)
+    ) AS legacy (analyte, value)
+    WHERE legacy.value IS NOT NULL
+    ON CONFLICT (fish_id, analyte) DO UPDATE
+    SET value = EXCLUDED.value
+    WHERE measurement.value IS DISTINCT FROM EXCLUDED.value;
+
+    DELETE FROM measurement
+    USING (
+        VALUES
+            ('mercury', NEW.mercury::NUMERIC::FLOAT8),
+            ('pcb', NEW.pcb::NUMERIC::FLOAT8),
+            ('omega_3', NEW.omega_3::NUMERIC::FLOAT8),
+            ('omega_3_ratio', NEW.omega_3_ratio::NUMERIC::FLOAT8),
+            ('protein', NEW.protein::NUMERIC::FLOAT8)
+    ) AS legacy (analyte, value)
+    WHERE measurement.fish_id = NEW.id
+        AND measurement.analyte = legacy.analyte
+        AND legacy.value IS NULL;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER fish_legacy_measurements
+AFTER INSERT OR UPDATE OF mercury, pcb, omega_3, omega_3_ratio, protein ON fish
+FOR EACH ROW EXECUTE FUNCTION sync_legacy_measurements();
+
+CREATE FUNCTION sync_fish_legacy_columns() RETURNS TRIGGER AS $$
+DECLARE
+    changed_fish_id uuid := COALESCE(NEW.fish_id, OLD.fish_id);
+BEGIN
+    IF pg_trigger_depth() > 1
+        OR COALESCE(NEW.analyte, OLD.analyte)
+            NOT IN ('mercury', 'pcb', 'omega_3', 'omega_3_ratio', 'protein') THEN
+        RETURN NULL;
+    END IF;
+
+    UPDATE fish
+    SET
+        mercury = (SELECT value FROM measurement WHERE fish_id = fish.id AND analyte = 'mercury'),
+        pcb = (SELECT value FROM measurement WHERE fish_id = fish.id AND analyte = 'pcb'),
+        omega_3 = (SELECT value FROM measurement WHERE fish_id = fish.id AND analyte = 'omega_3'),
+        omega_3_ratio = (
+            SELECT value FROM measurement WHERE fish_id = fish.id AND analyte = 'omega_3_ratio'
+        ),
+        protein = (SELECT value FROM measurement WHERE fish_id = fish.id AND analyte = 'protein')
+    WHERE id = changed_fish_id;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER measurement_fish_legacy_columns
+AFTER INSERT OR UPDATE OR DELETE ON measurement
+FOR EACH ROW EXECUTE FUNCTION sync_fish_legacy_columns();
@@ -0,0 +1,58 @@
+-- Add migration script here
+-- Non-detects keep being stored as 0, the detection limit says what they were
+-- below of. Measurements without one fall back on the analyte's.
+ALTER TABLE measurement
+    ADD COLUMN detected BOOLEAN NOT NULL DEFAULT true,
+    ADD COLUMN detection_limit DOUBLE PRECISION CHECK (detection_limit > 0),
+    ADD CONSTRAINT measurement_non_detect_is_zero CHECK (detected OR value = 0);
+
+UPDATE measurement SET detected = false WHERE value = 0;
+
+CREATE OR REPLACE FUNCTION sync_legacy_measurements() RETURNS TRIGGER AS $$
+BEGIN
+    IF pg_trigger_depth() > 1 THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO measurement (fish_id, analyte, value, detected)
+    SELECT NEW.id, legacy.analyte, legacy.value, legacy.value <> 0
+    FROM (
+        VALUES
+            ('mercury', NEW.mercury::NUMERIC::FLOAT8),
+            ('pcb', NEW.pcb::NUMERIC::FLOAT8),
+            ('omega_3', NEW.omega_3::NUMERIC::FLOAT8),
+            ('omega_3_ratio', NEW.omega_3_ratio::NUMERIC::FLOAT8),
+            ('protein', NEW.protein::NUMERIC::FLOAT8)
+    ) AS legacy (analyte, value)
+    WHERE legacy.value IS NOT NULL
+    ON CONFLICT (fish_id, analyte) DO UPDATE
+    SET value = EXCLUDED.value, detected = EXCLUDED.detected
+    WHERE measurement.value IS DISTINCT FROM EXCLUDED.value;
+
+    DELETE FROM measurement
+    USING (
+        VALUES
+            ('mercury', NEW.mercury::NUMERIC::FLOAT8),
+            ('pcb', NEW.pcb::NUMERIC::FLOAT8),
+            ('omega_3', NEW.omega_3::NUMERIC::FLOAT8),
+            ('omega_3_ratio', NEW.omega_3_ratio::NUMERIC::FLOAT8),
+            ('protein', NEW.protein::NUMERIC::FLOAT8)
+    ) AS legacy (analyte, value)
+    WHERE measurement.fish_id = NEW.id
+        AND measurement.analyte = legacy.analyte
+        AND legacy.value IS NULL;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- The value averages use for a measurement: non-detects become `factor` times
+-- the detection limit, or stay 0 without one.
+CREATE FUNCTION substituted_value(
+    detected BOOLEAN,
+    value DOUBLE PRECISION,
+    detection_limit DOUBLE PRECISION,
+    factor DOUBLE PRECISION
+) RETURNS DOUBLE PRECISION AS $$
+    SELECT CASE WHEN detected THEN value ELSE COALESCE(detection_limit * factor, value) END;
+$$ LANGUAGE SQL IMMUTABLE;
@@ -0,0 +1,20 @@
+-- Add migration script here
+CREATE TABLE data_source(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    -- Who published the data, e.g. GLIFWC or EPA.
+    agency TEXT NOT NULL,
+    study TEXT,
+    url TEXT,
+    -- How the samples were analysed, e.g. EPA Method 1631.
+    lab_method TEXT,
+    collection_year INT CHECK (collection_year BETWEEN 1900 AND 2100),
+    license TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Sources can't be deleted while samples cite them.
+ALTER TABLE fish
+ADD COLUMN data_source_id uuid REFERENCES data_source (id) ON UPDATE CASCADE ON DELETE RESTRICT;
+
+CREATE INDEX fish_data_source_idx ON fish (data_source_id);
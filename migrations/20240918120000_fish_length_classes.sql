@@ -0,0 +1,37 @@
+-- Add migration script here
+ALTER TABLE fish
+    ADD COLUMN length_cm DOUBLE PRECISION CHECK (length_cm > 0),
+    ADD COLUMN weight_g DOUBLE PRECISION CHECK (weight_g > 0);
+
+-- Length ranges advice is given for, e.g. walleye under 15 inches. Fish types
+-- share class names, so `under_38cm` can mean the same across fish types.
+CREATE TABLE length_class(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    fish_type_id uuid NOT NULL REFERENCES fish_type (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    min_length_cm DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK (min_length_cm >= 0),
+    -- Exclusive, NULL for no upper bound.
+    max_length_cm DOUBLE PRECISION CHECK (max_length_cm > min_length_cm),
+    UNIQUE (fish_type_id, name)
+);
+
+-- Whether a fish of the type and length is in the named length class. Fish
+-- without a length aren't in any.
+CREATE FUNCTION in_length_class(
+    fish_type_id uuid,
+    length_cm DOUBLE PRECISION,
+    class_name TEXT
+) RETURNS BOOLEAN AS $$
+    SELECT EXISTS (
+        SELECT 1
+        FROM length_class
+        WHERE length_class.fish_type_id = in_length_class.fish_type_id
+            AND length_class.name = class_name
+            AND in_length_class.length_cm >= length_class.min_length_cm
+            AND (
+                length_class.max_length_cm IS NULL
+                OR in_length_class.length_cm < length_class.max_length_cm
+            )
+    );
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,4 @@
+-- Add migration script here
+-- Only contaminants have a reference dose, and the advisories treat selenium as
+-- one: above its reference dose it's harmful, whatever its benefits below.
+UPDATE analyte SET kind = 'contaminant' WHERE name = 'selenium';
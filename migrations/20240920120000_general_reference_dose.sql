@@ -0,0 +1,9 @@
+-- Add migration script here
+-- reference_dose is the protective dose, for children and anyone who is or
+-- plans to become pregnant. general_reference_dose is the one for everyone
+-- else when it's less strict, NULL when the same dose applies to everyone.
+ALTER TABLE analyte
+    ADD COLUMN general_reference_dose DOUBLE PRECISION CHECK (general_reference_dose > 0);
+
+-- Methylmercury: US EPA RfD for the sensitive population, ATSDR MRL otherwise.
+UPDATE analyte SET general_reference_dose = 0.3 WHERE name = 'mercury';
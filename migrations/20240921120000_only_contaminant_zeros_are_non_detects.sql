@@ -0,0 +1,53 @@
+-- Add migration script here
+-- A zero is only a non-detect for contaminants, or when a detection limit
+-- says what it was below of. Zeros of nutrients are real results.
+UPDATE measurement
+SET detected = true
+FROM analyte
+WHERE analyte.name = measurement.analyte
+    AND analyte.kind <> 'contaminant'
+    AND NOT measurement.detected
+    AND measurement.detection_limit IS NULL;
+
+CREATE OR REPLACE FUNCTION sync_legacy_measurements() RETURNS TRIGGER AS $$
+BEGIN
+    IF pg_trigger_depth() > 1 THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO measurement (fish_id, analyte, value, detected)
+    SELECT
+        NEW.id,
+        legacy.analyte,
+        legacy.value,
+        legacy.value <> 0 OR analyte.kind <> 'contaminant'
+    FROM (
+        VALUES
+            ('mercury', NEW.mercury::NUMERIC::FLOAT8),
+            ('pcb', NEW.pcb::NUMERIC::FLOAT8),
+            ('omega_3', NEW.omega_3::NUMERIC::FLOAT8),
+            ('omega_3_ratio', NEW.omega_3_ratio::NUMERIC::FLOAT8),
+            ('protein', NEW.protein::NUMERIC::FLOAT8)
+    ) AS legacy (analyte, value)
+    JOIN analyte ON analyte.name = legacy.analyte
+    WHERE legacy.value IS NOT NULL
+    ON CONFLICT (fish_id, analyte) DO UPDATE
+    SET value = EXCLUDED.value, detected = EXCLUDED.detected
+    WHERE measurement.value IS DISTINCT FROM EXCLUDED.value;
+
+    DELETE FROM measurement
+    USING (
+        VALUES
+            ('mercury', NEW.mercury::NUMERIC::FLOAT8),
+            ('pcb', NEW.pcb::NUMERIC::FLOAT8),
+            ('omega_3', NEW.omega_3::NUMERIC::FLOAT8),
+            ('omega_3_ratio', NEW.omega_3_ratio::NUMERIC::FLOAT8),
+            ('protein', NEW.protein::NUMERIC::FLOAT8)
+    ) AS legacy (analyte, value)
+    WHERE measurement.fish_id = NEW.id
+        AND measurement.analyte = legacy.analyte
+        AND legacy.value IS NULL;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,19 @@
+-- Add migration script here
+-- Every measurement with the value averages use, non-detects substituted as
+-- set by `factor` (see substituted_value). Averages join this rather than
+-- measurement, so every endpoint counts non-detects the same way.
+CREATE FUNCTION substituted_measurement(factor DOUBLE PRECISION)
+RETURNS TABLE (fish_id uuid, analyte TEXT, value DOUBLE PRECISION, detected BOOLEAN) AS $$
+    SELECT
+        measurement.fish_id,
+        measurement.analyte,
+        substituted_value(
+            measurement.detected,
+            measurement.value,
+            COALESCE(measurement.detection_limit, analyte.detection_limit),
+            factor
+        ),
+        measurement.detected
+    FROM measurement
+    JOIN analyte ON analyte.name = measurement.analyte;
+$$ LANGUAGE SQL STABLE;
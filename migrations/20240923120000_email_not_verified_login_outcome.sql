@@ -0,0 +1,6 @@
+-- Add migration script here
+-- Logins with the right password that the unverified user policy rejects.
+-- They neither count as failures nor reset them.
+ALTER TABLE login_attempt DROP CONSTRAINT login_attempt_outcome_check;
+ALTER TABLE login_attempt ADD CONSTRAINT login_attempt_outcome_check
+    CHECK (outcome IN ('success', 'failure', 'locked_out', 'email_not_verified'));
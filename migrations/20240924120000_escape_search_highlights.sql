@@ -0,0 +1,12 @@
+-- Add migration script here
+-- Search highlights are HTML with <mark> tags, so the text they are cut from
+-- is escaped first and only the tags ts_headline adds are markup.
+CREATE FUNCTION html_escape(text TEXT) RETURNS TEXT AS $$
+    SELECT replace(replace(replace(replace(replace(
+        text,
+        '&', '&amp;'),
+        '<', '&lt;'),
+        '>', '&gt;'),
+        '"', '&quot;'),
+        '''', '&#39;');
+$$ LANGUAGE SQL IMMUTABLE;
@@ -0,0 +1,290 @@
+//! Fish consumption advice.
+//!
+//! Combines the contaminant levels of a fish (sample or average) with a
+//! user's profile to produce a recommended number of meals per month,
+//! following the approach used by the Great Lakes state fish advisories:
+//!
+//! ```text
+//! allowable intake (µg/month) = reference dose (µg/kg/day) * body weight (kg) * days per month
+//! intake per meal (µg)        = concentration (µg/g, i.e. ppm) * portion size (g)
+//! meals per month             = allowable intake / intake per meal
+//! ```
+//!
+//! The raw meals per month is then rounded down into one of the advisory
+//! categories (16, 12, 8, 4, 2 or 1 meals a month, 6 meals a year, or do
+//! not eat). The most restrictive contaminant determines the advice. The
+//! reference doses come from the analyte catalog, see `ReferenceDoses`. A fish
+//! missing its mercury or PCB level gets no advice, rather than the most
+//! generous category.
+
+use crate::configuration::ScoringSettings;
+use uuid::Uuid;
+
+/// Body weight used when a user hasn't filled in their profile, in pounds.
+pub const DEFAULT_WEIGHT_LBS: i16 = 180;
+/// Portion size used when a user hasn't filled in their profile, in ounces.
+pub const DEFAULT_PORTION_OZ: i16 = 8;
+/// Users younger than this are treated as part of the sensitive population.
+pub const SENSITIVE_AGE: i16 = 15;
+
+const KG_PER_LB: f64 = 0.453_592_37;
+const GRAMS_PER_OZ: f64 = 28.349_523_125;
+const DAYS_PER_MONTH: f64 = 365.25 / 12.0;
+const WEEKS_PER_MONTH: f64 = 52.0 / 12.0;
+
+/// The profile fields stored on `users` that affect the advice.
+#[derive(serde::Serialize, Default, Debug)]
+pub struct UserProfile {
+    pub weight: Option<i16>,
+    pub age: Option<i16>,
+    pub plan_to_get_pregnant: Option<bool>,
+    pub portion_size: Option<i16>,
+}
+
+impl UserProfile {
+    /// Children and anyone planning to become pregnant get the more protective
+    /// reference doses.
+    pub fn is_sensitive(&self) -> bool {
+        let is_child = self.age.map(|age| age < SENSITIVE_AGE).unwrap_or(false);
+
+        is_child || self.plan_to_get_pregnant.unwrap_or(false)
+    }
+
+    pub fn weight_kg(&self) -> f64 {
+        f64::from(positive_or(self.weight, DEFAULT_WEIGHT_LBS)) * KG_PER_LB
+    }
+
+    pub fn portion_g(&self) -> f64 {
+        portion_oz_to_g(positive_or(self.portion_size, DEFAULT_PORTION_OZ))
+    }
+
+    /// The portion size on the profile, or the default, in ounces.
+    pub fn portion_oz(&self) -> i16 {
+        positive_or(self.portion_size, DEFAULT_PORTION_OZ)
+    }
+}
+
+pub fn portion_oz_to_g(portion_oz: i16) -> f64 {
+    f64::from(portion_oz) * GRAMS_PER_OZ
+}
+
+/// How much of a contaminant the user can eat over `days` days, in µg.
+pub fn allowable_intake(reference_dose: f64, weight_kg: f64, days: f64) -> f64 {
+    reference_dose * weight_kg * days
+}
+
+fn positive_or(value: Option<i16>, default: i16) -> i16 {
+    value.filter(|v| *v > 0).unwrap_or(default)
+}
+
+/// How much omega-3 a fish gives per unit of mercury, higher is better. None
+/// when either isn't known. Mercury below the scoring floor counts as the
+/// floor, like in `benefit_risk_score`.
+pub fn benefit_risk_ratio(
+    scoring: &ScoringSettings,
+    omega_3: Option<f64>,
+    mercury: Option<f64>,
+) -> Option<f64> {
+    match (omega_3, mercury) {
+        (Some(omega_3), Some(mercury)) => Some(omega_3 / mercury.max(scoring.mercury_floor)),
+        _ => None,
+    }
+}
+
+/// Ranks fish by their benefit and risk: omega-3 per unit of mercury, minus a
+/// penalty for PCBs. Higher is better. None when the omega-3 level isn't
+/// known, a missing mercury or PCB level counts as none detected.
+pub fn benefit_risk_score(
+    scoring: &ScoringSettings,
+    omega_3: Option<f64>,
+    mercury: Option<f64>,
+    pcb: Option<f64>,
+) -> Option<f64> {
+    let mercury = mercury.unwrap_or(0.0).max(scoring.mercury_floor);
+    let pcb = pcb.unwrap_or(0.0);
+
+    omega_3.map(|omega_3| omega_3 / mercury - scoring.pcb_penalty * pcb)
+}
+
+/// Contaminant concentrations of the fish being eaten, in ppm (µg/g).
+#[derive(Debug)]
+pub struct ContaminantLevels {
+    pub fish_id: Uuid,
+    pub name: String,
+    pub mercury: Option<f64>,
+    pub pcb: Option<f64>,
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum MealCategory {
+    DoNotEat,
+    SixPerYear,
+    OnePerMonth,
+    TwoPerMonth,
+    FourPerMonth,
+    EightPerMonth,
+    TwelvePerMonth,
+    SixteenPerMonth,
+}
+
+impl MealCategory {
+    const ALL: [MealCategory; 8] = [
+        MealCategory::SixteenPerMonth,
+        MealCategory::TwelvePerMonth,
+        MealCategory::EightPerMonth,
+        MealCategory::FourPerMonth,
+        MealCategory::TwoPerMonth,
+        MealCategory::OnePerMonth,
+        MealCategory::SixPerYear,
+        MealCategory::DoNotEat,
+    ];
+
+    pub fn meals_per_month(&self) -> f64 {
+        match self {
+            MealCategory::SixteenPerMonth => 16.0,
+            MealCategory::TwelvePerMonth => 12.0,
+            MealCategory::EightPerMonth => 8.0,
+            MealCategory::FourPerMonth => 4.0,
+            MealCategory::TwoPerMonth => 2.0,
+            MealCategory::OnePerMonth => 1.0,
+            MealCategory::SixPerYear => 0.5,
+            MealCategory::DoNotEat => 0.0,
+        }
+    }
+
+    /// The most generous category that doesn't exceed the calculated meals.
+    /// Anything above 16 meals a month is capped at 16.
+    pub fn from_meals_per_month(meals: f64) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.meals_per_month() <= meals)
+            .unwrap_or(MealCategory::DoNotEat)
+    }
+}
+
+/// The reference doses that apply to a profile, in µg/kg/day. The catalog's
+/// `reference_dose` for sensitive profiles, its `general_reference_dose`, when
+/// there's one, for everyone else.
+#[derive(serde::Serialize, Debug)]
+pub struct ReferenceDoses {
+    pub mercury: f64,
+    pub pcb: f64,
+}
+
+/// The math behind the advice for a single contaminant.
+#[derive(serde::Serialize, Debug)]
+pub struct ContaminantAdvice {
+    /// ppm (µg/g)
+    pub concentration: Option<f64>,
+    /// µg/kg/day
+    pub reference_dose: f64,
+    /// µg/month
+    pub allowable_intake: f64,
+    /// µg
+    pub intake_per_meal: Option<f64>,
+    /// None when the contaminant wasn't detected or measured.
+    pub meals_per_month: Option<f64>,
+    /// None when the contaminant wasn't measured.
+    pub category: Option<MealCategory>,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct ProfileUsed {
+    pub weight_kg: f64,
+    pub portion_g: f64,
+    pub sensitive: bool,
+    /// True when neither a weight nor a portion size was on the profile.
+    pub is_default: bool,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct Advice {
+    pub fish_id: Uuid,
+    pub name: String,
+    /// None when either contaminant wasn't measured.
+    pub category: Option<MealCategory>,
+    pub meals_per_month: Option<f64>,
+    pub meals_per_week: Option<f64>,
+    pub limiting_contaminant: Option<&'static str>,
+    pub reference_doses: ReferenceDoses,
+    pub mercury: ContaminantAdvice,
+    pub pcb: ContaminantAdvice,
+    pub profile: ProfileUsed,
+}
+
+fn contaminant_advice(
+    concentration: Option<f64>,
+    reference_dose: f64,
+    weight_kg: f64,
+    portion_g: f64,
+) -> ContaminantAdvice {
+    let allowable_intake = allowable_intake(reference_dose, weight_kg, DAYS_PER_MONTH);
+    let intake_per_meal = concentration.map(|c| c * portion_g);
+    let meals_per_month = intake_per_meal
+        .filter(|intake| *intake > 0.0)
+        .map(|intake| allowable_intake / intake);
+    // Measured but not detected, there's nothing to limit the meals.
+    let category = concentration.map(|_| {
+        meals_per_month
+            .map(MealCategory::from_meals_per_month)
+            .unwrap_or(MealCategory::SixteenPerMonth)
+    });
+
+    ContaminantAdvice {
+        concentration,
+        reference_dose,
+        allowable_intake,
+        intake_per_meal,
+        meals_per_month,
+        category,
+    }
+}
+
+/// Calculates the recommended meals for the profile and fish, with the
+/// reference doses that apply to the profile.
+pub fn advise(
+    profile: &UserProfile,
+    reference_doses: ReferenceDoses,
+    levels: ContaminantLevels,
+) -> Advice {
+    let weight_kg = profile.weight_kg();
+    let portion_g = profile.portion_g();
+
+    let mercury = contaminant_advice(
+        levels.mercury,
+        reference_doses.mercury,
+        weight_kg,
+        portion_g,
+    );
+    let pcb = contaminant_advice(levels.pcb, reference_doses.pcb, weight_kg, portion_g);
+
+    let category = mercury.category.zip(pcb.category).map(|(m, p)| m.min(p));
+    let limiting_contaminant = match (mercury.meals_per_month, pcb.meals_per_month) {
+        _ if category.is_none() => None,
+        (None, None) => None,
+        (Some(_), None) => Some("mercury"),
+        (None, Some(_)) => Some("pcb"),
+        (Some(mercury_meals), Some(pcb_meals)) if pcb_meals < mercury_meals => Some("pcb"),
+        (Some(_), Some(_)) => Some("mercury"),
+    };
+    let meals_per_month = category.map(|category| category.meals_per_month());
+
+    Advice {
+        fish_id: levels.fish_id,
+        name: levels.name,
+        category,
+        meals_per_month,
+        meals_per_week: meals_per_month.map(|meals| meals / WEEKS_PER_MONTH),
+        limiting_contaminant,
+        reference_doses,
+        mercury,
+        pcb,
+        profile: ProfileUsed {
+            weight_kg,
+            portion_g,
+            sensitive: profile.is_sensitive(),
+            is_default: profile.weight.is_none() && profile.portion_size.is_none(),
+        },
+    }
+}
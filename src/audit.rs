@@ -0,0 +1,246 @@
+//! Append only log of every change made through the admin routes.
+//!
+//! Each entry records who made the change and a JSON snapshot of the row
+//! before and after it. Entries are written in the same transaction as the
+//! change itself, so a change can't happen without its entry and vice versa.
+
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+        }
+    }
+
+    fn from_snapshots(
+        before: &Option<serde_json::Value>,
+        after: &Option<serde_json::Value>,
+    ) -> Self {
+        match (before, after) {
+            (None, _) => AuditAction::Create,
+            (Some(_), Some(_)) => AuditAction::Update,
+            (Some(_), None) => AuditAction::Delete,
+        }
+    }
+}
+
+/// The kinds of rows the admin routes change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditEntity {
+    Fish,
+    FishType,
+    Recipe,
+    User,
+    ApiKey,
+    Lake,
+    SamplingSite,
+    Analyte,
+    DataSource,
+}
+
+impl AuditEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEntity::Fish => "fish",
+            AuditEntity::FishType => "fish_type",
+            AuditEntity::Recipe => "recipe",
+            AuditEntity::User => "user",
+            AuditEntity::ApiKey => "api_key",
+            AuditEntity::Lake => "lake",
+            AuditEntity::SamplingSite => "sampling_site",
+            AuditEntity::Analyte => "analyte",
+            AuditEntity::DataSource => "data_source",
+        }
+    }
+}
+
+/// Takes a JSON snapshot of a row, `None` if it doesn't exist. Fish types
+/// include their linked recipes and length classes, fish their measurements
+/// and users their roles. Secrets, i.e. password and key hashes, are left out.
+#[tracing::instrument(name = "Taking an audit snapshot", skip(transaction))]
+pub async fn audit_snapshot(
+    transaction: &mut Transaction<'_, Postgres>,
+    entity: AuditEntity,
+    entity_id: Uuid,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    let snapshot = match entity {
+        AuditEntity::Fish => {
+            sqlx::query_scalar!(
+                r#"
+                SELECT to_jsonb(fish) || jsonb_build_object(
+                    'measurements',
+                    COALESCE(
+                        (
+                            SELECT jsonb_object_agg(analyte, value)
+                            FROM measurement
+                            WHERE fish_id = fish.id
+                        ),
+                        '{}'
+                    )
+                ) as "snapshot!"
+                FROM fish
+                WHERE id = $1;
+                "#,
+                entity_id
+            )
+            .fetch_optional(&mut **transaction)
+            .await
+        }
+        AuditEntity::FishType => {
+            sqlx::query_scalar!(
+                r#"
+                SELECT to_jsonb(fish_type) || jsonb_build_object(
+                    'recipes',
+                    ARRAY(
+                        SELECT recipe_id FROM fishtype_recipe
+                        WHERE fishtype_id = fish_type.id
+                        ORDER BY recipe_id
+                    ),
+                    'length_classes',
+                    ARRAY(
+                        SELECT jsonb_build_object(
+                            'name', name,
+                            'min_length_cm', min_length_cm,
+                            'max_length_cm', max_length_cm
+                        )
+                        FROM length_class
+                        WHERE fish_type_id = fish_type.id
+                        ORDER BY min_length_cm, name
+                    )
+                ) as "snapshot!"
+                FROM fish_type
+                WHERE id = $1;
+                "#,
+                entity_id
+            )
+            .fetch_optional(&mut **transaction)
+            .await
+        }
+        AuditEntity::Recipe => {
+            sqlx::query_scalar!(
+                r#"SELECT to_jsonb(recipe) as "snapshot!" FROM recipe WHERE id = $1;"#,
+                entity_id
+            )
+            .fetch_optional(&mut **transaction)
+            .await
+        }
+        AuditEntity::User => {
+            sqlx::query_scalar!(
+                r#"
+                SELECT jsonb_build_object(
+                    'id', id,
+                    'email', email,
+                    'roles', ARRAY(
+                        SELECT role FROM user_roles WHERE user_id = users.id ORDER BY role
+                    ),
+                    'disabled_at', disabled_at
+                ) as "snapshot!"
+                FROM users
+                WHERE id = $1;
+                "#,
+                entity_id
+            )
+            .fetch_optional(&mut **transaction)
+            .await
+        }
+        AuditEntity::ApiKey => {
+            sqlx::query_scalar!(
+                r#"
+                SELECT to_jsonb(api_keys) - 'key_hash' as "snapshot!"
+                FROM api_keys
+                WHERE id = $1;
+                "#,
+                entity_id
+            )
+            .fetch_optional(&mut **transaction)
+            .await
+        }
+        AuditEntity::Lake => {
+            sqlx::query_scalar!(
+                r#"SELECT to_jsonb(lake) as "snapshot!" FROM lake WHERE id = $1;"#,
+                entity_id
+            )
+            .fetch_optional(&mut **transaction)
+            .await
+        }
+        AuditEntity::SamplingSite => sqlx::query_scalar!(
+            r#"SELECT to_jsonb(sampling_site) as "snapshot!" FROM sampling_site WHERE id = $1;"#,
+            entity_id
+        )
+        .fetch_optional(&mut **transaction)
+        .await,
+        AuditEntity::Analyte => {
+            sqlx::query_scalar!(
+                r#"SELECT to_jsonb(analyte) as "snapshot!" FROM analyte WHERE id = $1;"#,
+                entity_id
+            )
+            .fetch_optional(&mut **transaction)
+            .await
+        }
+        AuditEntity::DataSource => {
+            sqlx::query_scalar!(
+                r#"SELECT to_jsonb(data_source) as "snapshot!" FROM data_source WHERE id = $1;"#,
+                entity_id
+            )
+            .fetch_optional(&mut **transaction)
+            .await
+        }
+    };
+
+    snapshot.map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+/// Saves an entry for a change. The action is worked out from the snapshots:
+/// no `before` is a create, no `after` a delete.
+#[tracing::instrument(name = "Saving an audit log entry", skip(transaction, before, after))]
+pub async fn record_audit(
+    transaction: &mut Transaction<'_, Postgres>,
+    actor_id: Uuid,
+    entity: AuditEntity,
+    entity_id: Uuid,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+) -> Result<(), sqlx::Error> {
+    if before.is_none() && after.is_none() {
+        tracing::info!("The row doesn't exist, nothing to audit.");
+        return Ok(());
+    }
+    let action = AuditAction::from_snapshots(&before, &after);
+    sqlx::query!(
+        r#"
+        INSERT INTO audit_log (id, actor_id, action, entity, entity_id, before, after, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8);
+        "#,
+        Uuid::new_v4(),
+        actor_id,
+        action.as_str(),
+        entity.as_str(),
+        entity_id,
+        before,
+        after,
+        Utc::now()
+    )
+    .execute(&mut **transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
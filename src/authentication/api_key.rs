@@ -0,0 +1,111 @@
+use super::{generate_token, hash_token, LAST_USED_INTERVAL_MINUTES};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// What an API key can be used for. Each scope can do everything the ones
+/// before it can.
+#[derive(
+    serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiScope {
+    /// Read-only access to the public fish and recipe data, without sessions.
+    Public,
+    /// Everything a logged in user of the app can do.
+    User,
+    /// The admin routes, still requiring an admin user's session.
+    Admin,
+}
+
+impl ApiScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::Public => "public",
+            ApiScope::User => "user",
+            ApiScope::Admin => "admin",
+        }
+    }
+}
+
+impl TryFrom<String> for ApiScope {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "public" => Ok(Self::Public),
+            "user" => Ok(Self::User),
+            "admin" => Ok(Self::Admin),
+            other => Err(format!("{other} is not a valid API key scope.")),
+        }
+    }
+}
+
+/// The API key a request was made with. `id` is `None` for the keys set in
+/// the configuration.
+#[derive(Clone, Copy, Debug)]
+pub struct ApiKey {
+    pub id: Option<Uuid>,
+    pub scope: ApiScope,
+}
+
+/// A new, random API key. Only its hash is stored, so it can't be shown again.
+pub fn generate_api_key() -> String {
+    format!("fe_{}", generate_token())
+}
+
+/// Finds the key if it hasn't expired or been revoked and records that it was
+/// just used, unless that was recorded less than `LAST_USED_INTERVAL_MINUTES`
+/// ago. Keys are looked up by their hash, so the key itself is never compared.
+#[tracing::instrument(name = "Validating an API key", skip(db_pool, key))]
+pub async fn find_api_key(db_pool: &PgPool, key: &str) -> Result<Option<ApiKey>, anyhow::Error> {
+    let now = Utc::now();
+    let row = sqlx::query!(
+        r#"
+        SELECT id, scope, last_used_at
+        FROM api_keys
+        WHERE key_hash = $1
+            AND revoked_at IS NULL
+            AND (expires_at IS NULL OR expires_at > $2);
+        "#,
+        hash_token(key),
+        now
+    )
+    .fetch_optional(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let stale_before = now - Duration::minutes(LAST_USED_INTERVAL_MINUTES);
+    if row
+        .last_used_at
+        .is_none_or(|last_used_at| last_used_at < stale_before)
+    {
+        sqlx::query!(
+            r#"
+            UPDATE api_keys
+            SET last_used_at = $2
+            WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < $3);
+            "#,
+            row.id,
+            now,
+            stale_before
+        )
+        .execute(db_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to execute the query: {:?}", e);
+            e
+        })?;
+    }
+
+    Ok(Some(ApiKey {
+        id: Some(row.id),
+        scope: ApiScope::try_from(row.scope).map_err(anyhow::Error::msg)?,
+    }))
+}
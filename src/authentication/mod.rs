@@ -1,3 +1,15 @@
+mod api_key;
 mod password;
+mod permission;
+mod session;
+mod throttle;
+mod token;
+mod verification;
 
+pub use api_key::*;
 pub use password::*;
+pub use permission::*;
+pub use session::*;
+pub use throttle::*;
+pub use token::*;
+pub use verification::*;
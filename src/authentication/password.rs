@@ -28,6 +28,8 @@ impl Deref for UserId {
 pub enum AuthError {
     #[error("Invalid credentials.")]
     InvalidCredentials(#[source] anyhow::Error),
+    #[error("The account has been disabled.")]
+    AccountDisabled,
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -37,14 +39,25 @@ pub struct Credentials {
     pub password: Secret<String>,
 }
 
+struct StoredCredentials {
+    user_id: Uuid,
+    password_hash: Secret<String>,
+    is_admin: bool,
+    is_disabled: bool,
+}
+
 #[tracing::instrument(name = "Get stored credentials", skip(email, pool))]
 async fn get_stored_credentials(
     email: &str,
     pool: &PgPool,
-) -> Result<Option<(uuid::Uuid, Secret<String>, bool)>, anyhow::Error> {
+) -> Result<Option<StoredCredentials>, anyhow::Error> {
     let row = sqlx::query!(
         r#"
-        SELECT id, password_hash, is_admin
+        SELECT
+            id,
+            password_hash,
+            EXISTS(SELECT 1 FROM user_roles WHERE user_id = users.id) as "is_admin!",
+            disabled_at IS NOT NULL as "is_disabled!"
         FROM users
         WHERE email = $1
         "#,
@@ -53,12 +66,11 @@ async fn get_stored_credentials(
     .fetch_optional(pool)
     .await
     .context("Failed to performed a query to retrieve stored credentials.")?
-    .map(|row| {
-        (
-            row.id,
-            Secret::new(row.password_hash),
-            row.is_admin.unwrap_or(false),
-        )
+    .map(|row| StoredCredentials {
+        user_id: row.id,
+        password_hash: Secret::new(row.password_hash),
+        is_admin: row.is_admin,
+        is_disabled: row.is_disabled,
     });
 
     Ok(row)
@@ -77,13 +89,13 @@ pub async fn validate_credentials(
             .to_string(),
     );
     let mut is_admin = false;
+    let mut is_disabled = false;
 
-    if let Some((stored_user_id, stored_password_hash, stored_is_admin)) =
-        get_stored_credentials(&credentials.email, pool).await?
-    {
-        user_id = Some(stored_user_id);
-        expected_password_hash = stored_password_hash;
-        is_admin = stored_is_admin;
+    if let Some(stored) = get_stored_credentials(&credentials.email, pool).await? {
+        user_id = Some(stored.user_id);
+        expected_password_hash = stored.password_hash;
+        is_admin = stored.is_admin;
+        is_disabled = stored.is_disabled;
     }
 
     spawn_blocking_with_tracing(move || {
@@ -96,6 +108,12 @@ pub async fn validate_credentials(
         .ok_or_else(|| anyhow::anyhow!("Unknown username."))
         .map_err(AuthError::InvalidCredentials)?;
 
+    // Only checked once the password is known to be right, so a disabled
+    // account can't be found by guessing emails.
+    if is_disabled {
+        return Err(AuthError::AccountDisabled);
+    }
+
     Ok((user_id, is_admin))
 }
 
@@ -0,0 +1,86 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// What a role allows its users to do in the admin routes. Which roles have
+/// which permissions is stored in `role_permissions`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    /// Add, edit and delete fish samples.
+    ManageFish,
+    /// Add, edit and delete the lakes fish samples are from.
+    ManageLakes,
+    /// Add and edit fish types, including their descriptions.
+    ManageFishTypes,
+    /// Add, edit and delete recipes.
+    ManageRecipes,
+    /// Manage users, their roles and login attempts.
+    ManageUsers,
+    /// Create and revoke API keys.
+    ManageApiKeys,
+    ViewAnalytics,
+    ViewAuditLog,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ManageFish => "manage_fish",
+            Permission::ManageLakes => "manage_lakes",
+            Permission::ManageFishTypes => "manage_fish_types",
+            Permission::ManageRecipes => "manage_recipes",
+            Permission::ManageUsers => "manage_users",
+            Permission::ManageApiKeys => "manage_api_keys",
+            Permission::ViewAnalytics => "view_analytics",
+            Permission::ViewAuditLog => "view_audit_log",
+        }
+    }
+}
+
+/// Whether the user has any role, i.e. can use the admin routes at all.
+#[tracing::instrument(name = "Checking if a user has a role", skip(db_pool))]
+pub async fn user_has_any_role(db_pool: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM user_roles WHERE user_id = $1
+        ) as "has_role!";
+        "#,
+        user_id
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.has_role)
+}
+
+#[tracing::instrument(name = "Checking a user's permission", skip(db_pool))]
+pub async fn user_has_permission(
+    db_pool: &PgPool,
+    user_id: Uuid,
+    permission: Permission,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM user_roles
+            JOIN role_permissions ON role_permissions.role = user_roles.role
+            WHERE user_roles.user_id = $1 AND role_permissions.permission = $2
+        ) as "allowed!";
+        "#,
+        user_id,
+        permission.as_str()
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.allowed)
+}
@@ -0,0 +1,496 @@
+use super::{generate_token, hash_token, ApiKey, ApiScope};
+use crate::configuration::SessionSettings;
+use crate::startup::TrustedProxies;
+use actix_web::cookie::Cookie;
+use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
+use actix_web::{web, FromRequest, HttpMessage, HttpRequest, ResponseError};
+use anyhow::Context;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, Secret};
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::future::Future;
+use std::net::IpAddr;
+use std::pin::Pin;
+use uuid::Uuid;
+
+/// The cookie login sets the session token in.
+pub const SESSION_COOKIE: &str = "session";
+/// Clients that don't keep cookies can send the session token in this header.
+pub const SESSION_HEADER: &str = "X-Session-Token";
+const LEGACY_USER_ID_COOKIE: &str = "user_id";
+/// How stale a session's `last_seen_at`, or an API key's `last_used_at`, can
+/// get before it's updated, so every request doesn't write to the database.
+pub(crate) const LAST_USED_INTERVAL_MINUTES: i64 = 5;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SessionError {
+    #[error("No session token was included with the request.")]
+    Missing,
+    #[error("The session token is invalid.")]
+    InvalidToken,
+    #[error("The session has expired.")]
+    Expired,
+    #[error("The session has been revoked.")]
+    Revoked,
+    #[error("The account doesn't exist or has been disabled.")]
+    Disabled,
+    #[error("The user_id cookie is not a valid uuid.")]
+    InvalidUserIdCookie(#[source] uuid::Error),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl ResponseError for SessionError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SessionError::InvalidUserIdCookie(_) => StatusCode::BAD_REQUEST,
+            SessionError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+/// A newly issued or refreshed session. The token is
+/// `{session_id}.{user_id}.{expires_at}` followed by the hex encoded
+/// HMAC-SHA256 tag of those three parts. The refresh token is an opaque random
+/// value; only its hash is stored, and it's replaced every time it's used.
+pub struct SessionToken {
+    pub token: String,
+    pub session_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub refresh_token: String,
+    pub refresh_expires_at: DateTime<Utc>,
+}
+
+/// Where a session was started from, shown when listing a user's sessions.
+#[derive(Default, Debug)]
+pub struct SessionDevice {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl SessionDevice {
+    pub fn from_request(req: &HttpRequest) -> Self {
+        Self {
+            user_agent: req
+                .headers()
+                .get("User-Agent")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned),
+            ip_address: client_ip(req).map(|ip| ip.to_string()),
+        }
+    }
+}
+
+/// The address of the client making the request. That's the peer address,
+/// unless the peer is a trusted proxy: then it's the last `X-Forwarded-For`
+/// address that wasn't added by a trusted proxy, since clients can send the
+/// header with anything in it.
+pub fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+    let peer = req.peer_addr()?.ip();
+    let trusted_proxies = req.app_data::<web::Data<TrustedProxies>>();
+    let is_trusted = |ip: &IpAddr| {
+        trusted_proxies.is_some_and(|proxies| proxies.0.iter().any(|net| net.contains(ip)))
+    };
+
+    let mut client = peer;
+    if is_trusted(&peer) {
+        let forwarded = req
+            .headers()
+            .get_all("X-Forwarded-For")
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect::<Vec<_>>();
+        for ip in forwarded.into_iter().rev() {
+            let Ok(ip) = ip.trim().parse() else {
+                break;
+            };
+            client = ip;
+            if !is_trusted(&ip) {
+                break;
+            }
+        }
+    }
+
+    Some(client)
+}
+
+struct Claims {
+    session_id: Uuid,
+    user_id: Uuid,
+    expires_at: DateTime<Utc>,
+}
+
+/// The user making the request. The `session_id` is `None` when the user was
+/// identified by the deprecated `user_id` cookie.
+#[derive(Copy, Clone, Debug)]
+pub struct SessionUser {
+    pub user_id: Uuid,
+    pub session_id: Option<Uuid>,
+}
+
+impl FromRequest for SessionUser {
+    type Error = SessionError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move { authenticate(&req).await?.ok_or(SessionError::Missing) })
+    }
+}
+
+/// Same as `SessionUser` for routes that can be used without logging in. A
+/// session that is included still has to be valid.
+#[derive(Copy, Clone, Debug)]
+pub struct OptionalSessionUser(pub Option<SessionUser>);
+
+impl OptionalSessionUser {
+    pub fn user_id(&self) -> Option<Uuid> {
+        self.0.map(|user| user.user_id)
+    }
+}
+
+impl FromRequest for OptionalSessionUser {
+    type Error = SessionError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move { Ok(OptionalSessionUser(authenticate(&req).await?)) })
+    }
+}
+
+async fn authenticate(req: &HttpRequest) -> Result<Option<SessionUser>, SessionError> {
+    // Public API keys are for anonymous clients.
+    let is_public_key = req
+        .extensions()
+        .get::<ApiKey>()
+        .is_some_and(|api_key| api_key.scope == ApiScope::Public);
+    if is_public_key {
+        return Ok(None);
+    }
+
+    let settings = req
+        .app_data::<web::Data<SessionSettings>>()
+        .context("Unable to find the session settings.")?;
+    let db_pool = req
+        .app_data::<web::Data<PgPool>>()
+        .context("Unable to find attached pool.")?;
+
+    if let Some(token) = session_token(req) {
+        let claims = parse_token(&token, &settings.hmac_secret)?;
+        if claims.expires_at <= Utc::now() {
+            return Err(SessionError::Expired);
+        }
+        validate_session(db_pool, claims.session_id, claims.user_id).await?;
+
+        return Ok(Some(SessionUser {
+            user_id: claims.user_id,
+            session_id: Some(claims.session_id),
+        }));
+    }
+
+    if settings.allow_user_id_cookie {
+        if let Some(cookie) = req.cookie(LEGACY_USER_ID_COOKIE) {
+            tracing::warn!("A request was authenticated with the deprecated user_id cookie.");
+            let user_id =
+                Uuid::parse_str(cookie.value()).map_err(SessionError::InvalidUserIdCookie)?;
+            validate_legacy_user(db_pool, user_id).await?;
+
+            return Ok(Some(SessionUser {
+                user_id,
+                session_id: None,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+fn session_token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(SESSION_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned)
+        .or_else(|| {
+            req.cookie(SESSION_COOKIE)
+                .map(|cookie| cookie.value().to_owned())
+        })
+}
+
+fn sign(payload: &str, secret: &Secret<String>) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.expose_secret().as_bytes())
+        .expect("HMAC can take a key of any size.");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// Whole-second expiry, since that's all a token carries.
+fn expiry(from: DateTime<Utc>, ttl: Duration) -> Result<DateTime<Utc>, anyhow::Error> {
+    DateTime::from_timestamp((from + ttl).timestamp(), 0).context("Session expiry is out of range.")
+}
+
+fn sign_token(
+    session_id: Uuid,
+    user_id: Uuid,
+    expires_at: DateTime<Utc>,
+    secret: &Secret<String>,
+) -> String {
+    let payload = format!("{}.{}.{}", session_id, user_id, expires_at.timestamp());
+    let tag = hex::encode(sign(&payload, secret).finalize().into_bytes());
+
+    format!("{payload}.{tag}")
+}
+
+fn parse_token(token: &str, secret: &Secret<String>) -> Result<Claims, SessionError> {
+    let (payload, tag) = token.rsplit_once('.').ok_or(SessionError::InvalidToken)?;
+    let tag = hex::decode(tag).map_err(|_| SessionError::InvalidToken)?;
+    sign(payload, secret)
+        .verify_slice(&tag)
+        .map_err(|_| SessionError::InvalidToken)?;
+
+    let mut parts = payload.split('.');
+    let (Some(session_id), Some(user_id), Some(expires_at), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(SessionError::InvalidToken);
+    };
+
+    Ok(Claims {
+        session_id: Uuid::parse_str(session_id).map_err(|_| SessionError::InvalidToken)?,
+        user_id: Uuid::parse_str(user_id).map_err(|_| SessionError::InvalidToken)?,
+        expires_at: expires_at
+            .parse()
+            .ok()
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+            .ok_or(SessionError::InvalidToken)?,
+    })
+}
+
+/// Checks the session hasn't been revoked and records that it was just used,
+/// unless that was recorded less than `LAST_USED_INTERVAL_MINUTES` ago.
+#[tracing::instrument(name = "Validating a session", skip(db_pool))]
+async fn validate_session(
+    db_pool: &PgPool,
+    session_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), SessionError> {
+    let session = sqlx::query!(
+        r#"
+        SELECT expires_at, revoked_at, last_seen_at
+        FROM session
+        WHERE id = $1 AND user_id = $2;
+        "#,
+        session_id,
+        user_id
+    )
+    .fetch_optional(db_pool)
+    .await
+    .context("Failed to perform a query to retrieve the session.")?
+    .ok_or(SessionError::Revoked)?;
+
+    let now = Utc::now();
+    if session.revoked_at.is_some() {
+        return Err(SessionError::Revoked);
+    }
+    if session.expires_at <= now {
+        return Err(SessionError::Expired);
+    }
+
+    let stale_before = now - Duration::minutes(LAST_USED_INTERVAL_MINUTES);
+    if session
+        .last_seen_at
+        .is_none_or(|last_seen_at| last_seen_at < stale_before)
+    {
+        sqlx::query!(
+            r#"
+            UPDATE session
+            SET last_seen_at = $2
+            WHERE id = $1 AND (last_seen_at IS NULL OR last_seen_at < $3);
+            "#,
+            session_id,
+            now,
+            stale_before
+        )
+        .execute(db_pool)
+        .await
+        .context("Failed to perform a query to update the session.")?;
+    }
+
+    Ok(())
+}
+
+/// Checks the user of a `user_id` cookie exists and hasn't been disabled.
+/// Sessions are revoked when a user is disabled, the cookie has nothing to
+/// revoke.
+#[tracing::instrument(name = "Validating a user_id cookie", skip(db_pool))]
+async fn validate_legacy_user(db_pool: &PgPool, user_id: Uuid) -> Result<(), SessionError> {
+    let user = sqlx::query!(
+        r#"SELECT disabled_at IS NOT NULL as "is_disabled!" FROM users WHERE id = $1;"#,
+        user_id
+    )
+    .fetch_optional(db_pool)
+    .await
+    .context("Failed to perform a query to retrieve the user.")?;
+
+    match user {
+        Some(user) if !user.is_disabled => Ok(()),
+        _ => Err(SessionError::Disabled),
+    }
+}
+
+/// Starts a new session for the user and returns its signed token.
+#[tracing::instrument(name = "Creating a session", skip(db_pool, settings))]
+pub async fn create_session(
+    db_pool: &PgPool,
+    user_id: Uuid,
+    device: &SessionDevice,
+    settings: &SessionSettings,
+) -> Result<SessionToken, anyhow::Error> {
+    let session_id = Uuid::new_v4();
+    let created_at = Utc::now();
+    let expires_at = expiry(created_at, Duration::minutes(settings.ttl_minutes))?;
+    let refresh_expires_at = expiry(created_at, Duration::days(settings.refresh_ttl_days))?;
+    let refresh_token = generate_token();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO session (
+            id, user_id, created_at, expires_at, refresh_token_hash, refresh_expires_at,
+            user_agent, ip_address, last_seen_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $3);
+        "#,
+        session_id,
+        user_id,
+        created_at,
+        expires_at,
+        hash_token(&refresh_token),
+        refresh_expires_at,
+        device.user_agent,
+        device.ip_address
+    )
+    .execute(db_pool)
+    .await
+    .context("Failed to save the session.")?;
+
+    Ok(SessionToken {
+        token: sign_token(session_id, user_id, expires_at, &settings.hmac_secret),
+        session_id,
+        expires_at,
+        refresh_token,
+        refresh_expires_at,
+    })
+}
+
+/// Extends the session the refresh token belongs to, returning a new session
+/// token and a new refresh token. A refresh token can only be used once.
+#[tracing::instrument(name = "Refreshing a session", skip(db_pool, refresh_token, settings))]
+pub async fn refresh_session(
+    db_pool: &PgPool,
+    refresh_token: &str,
+    settings: &SessionSettings,
+) -> Result<SessionToken, SessionError> {
+    let now = Utc::now();
+    let expires_at = expiry(now, Duration::minutes(settings.ttl_minutes))?;
+    let refresh_expires_at = expiry(now, Duration::days(settings.refresh_ttl_days))?;
+    let new_refresh_token = generate_token();
+
+    let session = sqlx::query!(
+        r#"
+        UPDATE session
+        SET
+            refresh_token_hash = $2,
+            expires_at = $3,
+            refresh_expires_at = $4,
+            last_seen_at = $5
+        WHERE refresh_token_hash = $1
+            AND revoked_at IS NULL
+            AND refresh_expires_at > $5
+        RETURNING id, user_id;
+        "#,
+        hash_token(refresh_token),
+        hash_token(&new_refresh_token),
+        expires_at,
+        refresh_expires_at,
+        now
+    )
+    .fetch_optional(db_pool)
+    .await
+    .context("Failed to refresh the session.")?
+    .ok_or(SessionError::InvalidToken)?;
+
+    Ok(SessionToken {
+        token: sign_token(
+            session.id,
+            session.user_id,
+            expires_at,
+            &settings.hmac_secret,
+        ),
+        session_id: session.id,
+        expires_at,
+        refresh_token: new_refresh_token,
+        refresh_expires_at,
+    })
+}
+
+/// The cookie the session token is kept in by browsers.
+pub fn session_cookie(token: &str) -> Cookie<'static> {
+    Cookie::build(SESSION_COOKIE, token.to_owned())
+        .path("/")
+        .http_only(true)
+        .secure(true)
+        .finish()
+}
+
+/// Revokes a single session, i.e. logs it out.
+#[tracing::instrument(name = "Revoking a session", skip(db_pool))]
+pub async fn revoke_session(
+    db_pool: &PgPool,
+    session_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE session
+        SET revoked_at = $3
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL;
+        "#,
+        session_id,
+        user_id,
+        Utc::now()
+    )
+    .execute(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
+
+/// Revokes every active session of a user, e.g. after their password changes.
+#[tracing::instrument(name = "Revoking a user's sessions", skip(db_pool))]
+pub async fn revoke_user_sessions(db_pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE session
+        SET revoked_at = $2
+        WHERE user_id = $1 AND revoked_at IS NULL;
+        "#,
+        user_id,
+        Utc::now()
+    )
+    .execute(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use crate::configuration::LoginThrottleSettings;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug)]
+pub enum LoginOutcome {
+    Success,
+    Failure,
+    /// The attempt was rejected without checking the password.
+    LockedOut,
+    /// The password was right, but the unverified user policy forbids the
+    /// login.
+    EmailNotVerified,
+}
+
+impl LoginOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginOutcome::Success => "success",
+            LoginOutcome::Failure => "failure",
+            LoginOutcome::LockedOut => "locked_out",
+            LoginOutcome::EmailNotVerified => "email_not_verified",
+        }
+    }
+}
+
+struct RecentFailures {
+    count: i64,
+    last_failure: Option<DateTime<Utc>>,
+}
+
+/// How long to wait after `failures` consecutive failed attempts. The first
+/// `free_attempts` failures don't lock anything, after that the lockout
+/// doubles with every failure up to `max_lockout_seconds`.
+fn lockout(failures: i64, free_attempts: i64, settings: &LoginThrottleSettings) -> Duration {
+    if failures < free_attempts {
+        return Duration::zero();
+    }
+    let doublings = u32::try_from(failures - free_attempts)
+        .unwrap_or(u32::MAX)
+        .min(30);
+    let seconds = settings
+        .base_lockout_seconds
+        .saturating_mul(1 << doublings)
+        .min(settings.max_lockout_seconds);
+
+    Duration::seconds(seconds)
+}
+
+/// Returns how long the caller has to wait before trying to log in again, if
+/// either the email or the IP address is locked out.
+#[tracing::instrument(name = "Checking for a login lockout", skip(db_pool, settings))]
+pub async fn login_lockout(
+    db_pool: &PgPool,
+    email: &str,
+    ip_address: Option<&str>,
+    settings: &LoginThrottleSettings,
+) -> Result<Option<Duration>, sqlx::Error> {
+    let now = Utc::now();
+    let since = now - Duration::minutes(settings.window_minutes);
+
+    let by_email = recent_email_failures(db_pool, email, since).await?;
+    let mut wait = by_email
+        .last_failure
+        .map(|last| last + lockout(by_email.count, settings.free_attempts, settings) - now);
+
+    if let Some(ip_address) = ip_address {
+        let by_ip = recent_ip_failures(db_pool, ip_address, since).await?;
+        let ip_wait = by_ip
+            .last_failure
+            .map(|last| last + lockout(by_ip.count, settings.ip_free_attempts, settings) - now);
+        wait = wait.max(ip_wait);
+    }
+
+    Ok(wait.filter(|wait| *wait > Duration::zero()))
+}
+
+/// Failed attempts for the email since its last successful login.
+async fn recent_email_failures(
+    db_pool: &PgPool,
+    email: &str,
+    since: DateTime<Utc>,
+) -> Result<RecentFailures, sqlx::Error> {
+    sqlx::query_as!(
+        RecentFailures,
+        r#"
+        SELECT
+            COUNT(*) as "count!",
+            MAX(attempted_at) as last_failure
+        FROM login_attempt
+        WHERE email = $1
+            AND outcome = 'failure'
+            AND attempted_at > GREATEST($2, (
+                SELECT MAX(attempted_at)
+                FROM login_attempt
+                WHERE email = $1 AND outcome = 'success'
+            ));
+        "#,
+        email,
+        since
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+/// Failed attempts from the IP address, for any email. A success for one
+/// account doesn't reset these, so one address can't cycle through accounts.
+async fn recent_ip_failures(
+    db_pool: &PgPool,
+    ip_address: &str,
+    since: DateTime<Utc>,
+) -> Result<RecentFailures, sqlx::Error> {
+    sqlx::query_as!(
+        RecentFailures,
+        r#"
+        SELECT
+            COUNT(*) as "count!",
+            MAX(attempted_at) as last_failure
+        FROM login_attempt
+        WHERE ip_address = $1
+            AND outcome = 'failure'
+            AND attempted_at > $2;
+        "#,
+        ip_address,
+        since
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+#[tracing::instrument(name = "Recording a login attempt", skip(db_pool))]
+pub async fn record_login_attempt(
+    db_pool: &PgPool,
+    email: &str,
+    ip_address: Option<&str>,
+    outcome: LoginOutcome,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO login_attempt (id, email, ip_address, outcome, attempted_at)
+        VALUES ($1, $2, $3, $4, $5);
+        "#,
+        Uuid::new_v4(),
+        email,
+        ip_address,
+        outcome.as_str(),
+        Utc::now()
+    )
+    .execute(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
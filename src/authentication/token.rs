@@ -0,0 +1,13 @@
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+/// A random, URL-safe token for refresh tokens and one-time links.
+pub fn generate_token() -> String {
+    hex::encode(rand::thread_rng().gen::<[u8; 32]>())
+}
+
+/// Tokens are only stored hashed, so a leaked table can't be used to log in.
+/// They're random enough that a fast hash is fine.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
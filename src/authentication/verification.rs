@@ -0,0 +1,22 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[tracing::instrument(name = "Checking if a user's email is verified", skip(db_pool))]
+pub async fn is_email_verified(db_pool: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT email_verified_at
+        FROM users
+        WHERE id = $1;
+        "#,
+        user_id
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.email_verified_at.is_some())
+}
@@ -1,14 +1,21 @@
+use crate::email_client::{EmailClient, FileEmailClient, SmtpEmailClient};
+use ipnet::IpNet;
 use secrecy::{ExposeSecret, Secret};
 use serde_aux::field_attributes::deserialize_number_from_string;
 use sqlx::postgres::PgConnectOptions;
 use sqlx::postgres::PgSslMode;
 use sqlx::ConnectOptions;
+use std::sync::Arc;
 
 #[derive(serde::Deserialize)]
 pub struct Settings {
     pub database: DataBaseSettings,
     pub application: ApplicationSettings,
     pub s3: S3Settings,
+    pub session: SessionSettings,
+    pub email_client: EmailClientSettings,
+    pub login_throttle: LoginThrottleSettings,
+    pub scoring: ScoringSettings,
 }
 
 #[derive(serde::Deserialize)]
@@ -18,6 +25,20 @@ pub struct ApplicationSettings {
     pub host: String,
     pub api_key: String,
     pub public_key: String,
+    /// Used to build the links in emails.
+    pub base_url: String,
+    pub unverified_users: UnverifiedUserPolicy,
+    /// Proxies in front of the app, trusted to tell the client's address in
+    /// `X-Forwarded-For`. Requests from anywhere else are identified by their
+    /// peer address.
+    pub trusted_proxies: Vec<IpNet>,
+}
+
+/// What users who haven't verified their email yet are allowed to do.
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct UnverifiedUserPolicy {
+    pub can_login: bool,
+    pub can_favorite: bool,
 }
 
 #[derive(serde::Deserialize)]
@@ -39,6 +60,85 @@ pub struct S3Settings {
     pub secret_access_key: String,
 }
 
+#[derive(serde::Deserialize, Clone)]
+pub struct SessionSettings {
+    pub hmac_secret: Secret<String>,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub ttl_minutes: i64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub refresh_ttl_days: i64,
+    /// Deprecated: still accept the plaintext `user_id` cookie while clients
+    /// migrate to session tokens.
+    pub allow_user_id_cookie: bool,
+}
+
+/// Failed logins are counted per email and per IP address over the last
+/// `window_minutes`. Past the free attempts, each failure doubles the lockout,
+/// starting at `base_lockout_seconds`.
+#[derive(serde::Deserialize, Clone)]
+pub struct LoginThrottleSettings {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub free_attempts: i64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub ip_free_attempts: i64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub base_lockout_seconds: i64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_lockout_seconds: i64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub window_minutes: i64,
+}
+
+/// Weights of the benefit-risk score used to rank fish, see
+/// `advisory::benefit_risk_score`.
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct ScoringSettings {
+    /// Mercury levels below this, in ppm, are scored as this, so fish without
+    /// detectable mercury don't get an infinite score.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub mercury_floor: f64,
+    /// Subtracted from the score per ppm of PCB.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub pcb_penalty: f64,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailBackend {
+    Smtp,
+    File,
+}
+
+#[derive(serde::Deserialize)]
+pub struct EmailClientSettings {
+    pub backend: EmailBackend,
+    pub sender_email: String,
+    pub smtp_host: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub smtp_port: u16,
+    pub smtp_username: String,
+    pub smtp_password: Secret<String>,
+    /// Where the file backend writes emails to.
+    pub outbox_dir: String,
+}
+
+impl EmailClientSettings {
+    pub fn client(&self) -> Result<Arc<dyn EmailClient>, anyhow::Error> {
+        let client: Arc<dyn EmailClient> = match self.backend {
+            EmailBackend::Smtp => Arc::new(SmtpEmailClient::new(
+                &self.sender_email,
+                &self.smtp_host,
+                self.smtp_port,
+                self.smtp_username.clone(),
+                self.smtp_password.clone(),
+            )?),
+            EmailBackend::File => Arc::new(FileEmailClient::new(&self.outbox_dir)),
+        };
+
+        Ok(client)
+    }
+}
+
 impl DataBaseSettings {
     pub fn without_db(&self) -> PgConnectOptions {
         let ssl_mode = if self.require_ssl {
@@ -74,8 +174,11 @@ pub fn get_configuration() -> Result<Settings, config::ConfigError> {
         .expect("Failed to parse APP_ENVIRONMENT.");
 
     let environment_filename = format!("{}.yaml", env.as_str());
-    let settings = config::Config::builder()
+    let base = config::Config::builder()
         .add_source(config::File::from(config_directory.join("base.yaml")))
+        .build()?;
+    let settings = config::Config::builder()
+        .add_source(base.clone())
         .add_source(config::File::from(
             config_directory.join(environment_filename),
         ))
@@ -86,7 +189,19 @@ pub fn get_configuration() -> Result<Settings, config::ConfigError> {
         )
         .build()?;
 
-    settings.try_deserialize::<Settings>()
+    let settings = settings.try_deserialize::<Settings>()?;
+    // The secret in base.yaml is public, anyone could sign a session token
+    // with it.
+    if !matches!(env, Environment::Local)
+        && settings.session.hmac_secret.expose_secret()
+            == &base.get_string("session.hmac_secret")?
+    {
+        return Err(config::ConfigError::Message(
+            "session.hmac_secret must be set, e.g. with APP_SESSION__HMAC_SECRET.".into(),
+        ));
+    }
+
+    Ok(settings)
 }
 
 pub enum Environment {
@@ -0,0 +1,127 @@
+use anyhow::Context;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use secrecy::{ExposeSecret, Secret};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Clone, Debug)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+}
+
+/// Sends the emails the API needs, e.g. password resets. Which implementation
+/// is used is picked in the `email_client` configuration.
+#[async_trait::async_trait]
+pub trait EmailClient: Send + Sync {
+    async fn send_email(&self, email: Email) -> Result<(), anyhow::Error>;
+}
+
+pub struct SmtpEmailClient {
+    sender: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpEmailClient {
+    pub fn new(
+        sender: &str,
+        host: &str,
+        port: u16,
+        username: String,
+        password: Secret<String>,
+    ) -> Result<Self, anyhow::Error> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .context("Failed to build the SMTP transport.")?
+            .port(port)
+            .credentials(Credentials::new(
+                username,
+                password.expose_secret().to_owned(),
+            ))
+            .build();
+
+        Ok(Self {
+            sender: sender.parse().context("Invalid sender email.")?,
+            transport,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailClient for SmtpEmailClient {
+    #[tracing::instrument(name = "Sending an email over SMTP", skip(self, email), fields(to = %email.to))]
+    async fn send_email(&self, email: Email) -> Result<(), anyhow::Error> {
+        let message = Message::builder()
+            .from(self.sender.clone())
+            .to(email.to.parse().context("Invalid recipient email.")?)
+            .subject(email.subject)
+            .body(email.text_body)
+            .context("Failed to build the email.")?;
+
+        self.transport
+            .send(message)
+            .await
+            .context("Failed to send the email.")?;
+
+        Ok(())
+    }
+}
+
+/// Writes every email to a file in `outbox_dir` instead of sending it, for
+/// local development.
+pub struct FileEmailClient {
+    outbox_dir: PathBuf,
+}
+
+impl FileEmailClient {
+    pub fn new(outbox_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            outbox_dir: outbox_dir.into(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailClient for FileEmailClient {
+    #[tracing::instrument(name = "Writing an email to the outbox", skip(self, email), fields(to = %email.to))]
+    async fn send_email(&self, email: Email) -> Result<(), anyhow::Error> {
+        tokio::fs::create_dir_all(&self.outbox_dir)
+            .await
+            .context("Failed to create the outbox directory.")?;
+
+        let path = self
+            .outbox_dir
+            .join(format!("{}.txt", uuid::Uuid::new_v4()));
+        let contents = format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            email.to, email.subject, email.text_body
+        );
+        tokio::fs::write(&path, contents)
+            .await
+            .context("Failed to write the email to the outbox.")?;
+
+        Ok(())
+    }
+}
+
+/// Keeps every email in memory so tests can read them back.
+#[derive(Default)]
+pub struct InMemoryEmailClient {
+    sent: Mutex<Vec<Email>>,
+}
+
+impl InMemoryEmailClient {
+    pub fn sent_emails(&self) -> Vec<Email> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailClient for InMemoryEmailClient {
+    async fn send_email(&self, email: Email) -> Result<(), anyhow::Error> {
+        self.sent.lock().unwrap().push(email);
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
+pub mod advisory;
+pub mod audit;
 pub mod authentication;
 pub mod configuration;
+pub mod email_client;
 pub mod middleware;
 pub mod routes;
 pub mod startup;
@@ -18,7 +18,11 @@ async fn main() -> std::io::Result<()> {
         "🚀🚀 starting up: http://{} 🚀🚀",
         listener.local_addr().unwrap()
     );
-    run(listener, connection_pool)?.await?;
+    let email_client = config
+        .email_client
+        .client()
+        .expect("Failed to build the email client.");
+    run(listener, connection_pool, email_client, &config)?.await?;
 
     Ok(())
 }
@@ -1,25 +1,71 @@
-use crate::configuration::get_configuration;
-use actix_web::{dev::ServiceRequest, Error};
+use crate::authentication::{find_api_key, ApiKey, ApiScope};
+use actix_web::http::Method;
+use actix_web::{dev::ServiceRequest, error, web, Error, HttpMessage};
 use actix_web_httpauth::extractors::bearer::{BearerAuth, Config};
 use actix_web_httpauth::extractors::AuthenticationError;
+use secrecy::{ExposeSecret, Secret};
+use sqlx::PgPool;
+use subtle::ConstantTimeEq;
 
+/// The keys from the `application` configuration. `admin` has full access and
+/// `public` is read-only, the same as the `admin` and `public` scoped keys in
+/// the `api_keys` table.
+pub struct ConfiguredApiKeys {
+    pub admin: Secret<String>,
+    pub public: Secret<String>,
+}
+
+/// Checks the bearer token is a valid API key and stores it on the request
+/// for the routes and middleware that depend on its scope. Keys with the
+/// `public` scope can only make GET requests.
 pub async fn api_auth(
     req: ServiceRequest,
     auth: BearerAuth,
 ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
-    match validate_token(auth.token()) {
-        Ok(_) => Ok(req),
-        Err(_) => Err((AuthenticationError::from(Config::default()).into(), req)),
+    let api_key = match validate_token(&req, auth.token()).await {
+        Ok(Some(api_key)) => api_key,
+        Ok(None) => return Err((AuthenticationError::from(Config::default()).into(), req)),
+        Err(e) => return Err((error::ErrorInternalServerError(e), req)),
+    };
+
+    if api_key.scope == ApiScope::Public && !matches!(*req.method(), Method::GET | Method::HEAD) {
+        let e = anyhow::anyhow!("The API key is read-only.");
+        return Err((error::ErrorForbidden(e), req));
     }
+
+    req.extensions_mut().insert(api_key);
+    Ok(req)
 }
 
-fn validate_token(token: &str) -> Result<bool, std::io::Error> {
-    let api_key = get_configuration().unwrap().application.api_key;
-    if token.eq(&api_key) {
-        return Ok(true);
+async fn validate_token(
+    req: &ServiceRequest,
+    token: &str,
+) -> Result<Option<ApiKey>, anyhow::Error> {
+    let configured = req
+        .app_data::<web::Data<ConfiguredApiKeys>>()
+        .ok_or_else(|| anyhow::anyhow!("Unable to find the configured API keys."))?;
+    if constant_time_eq(token, &configured.admin) {
+        return Ok(Some(ApiKey {
+            id: None,
+            scope: ApiScope::Admin,
+        }));
     }
-    Err(std::io::Error::new(
-        std::io::ErrorKind::Other,
-        "Authenication failed.",
-    ))
+    if constant_time_eq(token, &configured.public) {
+        return Ok(Some(ApiKey {
+            id: None,
+            scope: ApiScope::Public,
+        }));
+    }
+
+    let db_pool = req
+        .app_data::<web::Data<PgPool>>()
+        .ok_or_else(|| anyhow::anyhow!("Unable to find attached pool."))?;
+    find_api_key(db_pool, token).await
+}
+
+fn constant_time_eq(token: &str, key: &Secret<String>) -> bool {
+    token
+        .as_bytes()
+        .ct_eq(key.expose_secret().as_bytes())
+        .into()
 }
@@ -1,5 +1,7 @@
 mod auth;
 mod reject_non_admin_users;
+mod require_permission;
 
 pub use auth::*;
 pub use reject_non_admin_users::*;
+pub use require_permission::*;
@@ -0,0 +1,47 @@
+use actix_web::body::BoxBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::error;
+use actix_web::web;
+use actix_web::HttpMessage;
+use actix_web_lab::middleware::Next;
+use sqlx::PgPool;
+
+use crate::authentication::{user_has_permission, Permission, SessionUser};
+use crate::utils::e500;
+
+/// Rejects users whose roles don't grant the permission. Has to run inside
+/// `reject_non_admin_users`, which authenticates the user.
+///
+/// # Example
+///
+/// ```ignore
+/// web::scope("/fish")
+///     .wrap(from_fn(|req, next| require_permission(Permission::ManageFish, req, next)))
+/// ```
+pub async fn require_permission(
+    permission: Permission,
+    req: ServiceRequest,
+    next: Next<BoxBody>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let user = req
+        .extensions()
+        .get::<SessionUser>()
+        .copied()
+        .ok_or(e500("Unable to find the admin user."))?;
+
+    let db_pool = req
+        .app_data::<web::Data<PgPool>>()
+        .ok_or(e500("Unable to find attached pool."))?;
+
+    match user_has_permission(db_pool, user.user_id, permission).await {
+        Ok(true) => next.call(req).await,
+        Ok(false) => {
+            let e = anyhow::anyhow!(
+                "The user does not have the {} permission.",
+                permission.as_str()
+            );
+            Err(error::ErrorForbidden(e))
+        }
+        Err(e) => Err(e500(e)),
+    }
+}
@@ -0,0 +1,103 @@
+use super::{is_duplicate_name, NewAnalyteData};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{post, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Adds a contaminant or nutrient fish samples can be measured for.
+///
+/// # Example
+///
+/// `.../admin/analyte/`
+///
+///```json
+/// {
+///     "name": "cadmium",
+///     "display_name": "Cadmium",
+///     "kind": "contaminant",
+///     "unit": "ppm",
+///     "reference_dose": 1.0,
+///     "detection_limit": 0.005
+/// }
+///```
+#[tracing::instrument(name = "Creating a new analyte.", skip(data, db_pool, admin))]
+#[post("/")]
+pub async fn create_analyte(
+    data: web::Json<NewAnalyteData>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    if let Err(reason) = data.validate() {
+        return HttpResponse::BadRequest().body(reason);
+    }
+    let analyte_id = Uuid::new_v4();
+    match new_analyte_db(&db_pool, admin.user_id, analyte_id, &data).await {
+        Ok(_) => {
+            tracing::info!("New analyte has been added.");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) if is_duplicate_name(&e) => {
+            HttpResponse::BadRequest().body("An analyte with that name already exists.")
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Saving new analyte data to the database", skip(db_pool, data))]
+async fn new_analyte_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    analyte_id: Uuid,
+    data: &NewAnalyteData,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO analyte (
+            id,
+            name,
+            display_name,
+            kind,
+            unit,
+            reference_dose,
+            general_reference_dose,
+            detection_limit
+        )
+        VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8
+        );
+        "#,
+        analyte_id,
+        data.name,
+        data.data.display_name.trim(),
+        data.data.kind,
+        data.data.unit.trim(),
+        data.data.reference_dose,
+        data.data.general_reference_dose,
+        data.data.detection_limit
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::Analyte, analyte_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::Analyte,
+        analyte_id,
+        None,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
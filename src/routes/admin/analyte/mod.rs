@@ -0,0 +1,76 @@
+mod create;
+mod update;
+
+pub use create::create_analyte;
+pub use update::update_analyte;
+
+use uuid::Uuid;
+
+const ANALYTE_KINDS: [&str; 2] = ["contaminant", "nutrient"];
+
+#[derive(serde::Deserialize)]
+pub struct AnalyteUuid {
+    uuid: Uuid,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct AnalyteData {
+    display_name: String,
+    kind: String,
+    unit: String,
+    /// µg/kg/day, for children and anyone who is or plans to become pregnant.
+    reference_dose: Option<f64>,
+    /// µg/kg/day, for everyone else when it's less strict than
+    /// `reference_dose`.
+    general_reference_dose: Option<f64>,
+    detection_limit: Option<f64>,
+}
+
+/// The name is what measurements refer to, so it's only given on creation.
+#[derive(serde::Deserialize, Debug)]
+pub struct NewAnalyteData {
+    name: String,
+    #[serde(flatten)]
+    data: AnalyteData,
+}
+
+impl AnalyteData {
+    /// Rejects a blank display name or unit, an unknown kind, and reference
+    /// doses or a detection limit that aren't positive.
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.display_name.trim().is_empty() || self.unit.trim().is_empty() {
+            return Err("The analyte needs a display name and a unit.");
+        }
+        if !ANALYTE_KINDS.contains(&self.kind.as_str()) {
+            return Err("The kind must be contaminant or nutrient.");
+        }
+        let positive = |value: Option<f64>| value.is_none_or(|value| value > 0.0);
+        if !positive(self.reference_dose)
+            || !positive(self.general_reference_dose)
+            || !positive(self.detection_limit)
+        {
+            return Err("The reference doses and detection limit must be positive.");
+        }
+        Ok(())
+    }
+}
+
+impl NewAnalyteData {
+    fn validate(&self) -> Result<(), &'static str> {
+        let is_valid_name = !self.name.is_empty()
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !is_valid_name {
+            return Err("The name must be lowercase letters, digits and underscores.");
+        }
+        self.data.validate()
+    }
+}
+
+/// Whether a query failed because another analyte already has the name.
+fn is_duplicate_name(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .is_some_and(|e| e.is_unique_violation())
+}
@@ -0,0 +1,101 @@
+use super::{AnalyteData, AnalyteUuid};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{put, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Replaces an analyte's data, except for its name.
+///
+/// # Example
+///
+/// `.../admin/analyte/1fe5c906-d09d-11ed-afa1-0242ac120002`
+///
+///```json
+/// {
+///     "display_name": "Selenium",
+///     "kind": "nutrient",
+///     "unit": "ppm",
+///     "reference_dose": 5.0,
+///     "detection_limit": 0.01
+/// }
+///```
+#[tracing::instrument(name = "Updating analyte data", skip(uuid, data, db_pool, admin))]
+#[put("/{uuid}")]
+pub async fn update_analyte(
+    uuid: web::Path<AnalyteUuid>,
+    data: web::Json<AnalyteData>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    if let Err(reason) = data.validate() {
+        return HttpResponse::BadRequest().body(reason);
+    }
+    match update_analyte_db(&db_pool, admin.user_id, uuid.uuid, &data).await {
+        Ok(_) => {
+            tracing::info!("Analyte has been updated.");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            match e {
+                sqlx::Error::RowNotFound => HttpResponse::BadRequest().finish(),
+                _ => HttpResponse::InternalServerError().finish(),
+            }
+        }
+    }
+}
+
+#[tracing::instrument(name = "Saving analyte data to the database", skip(db_pool, data))]
+async fn update_analyte_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    analyte_uuid: Uuid,
+    data: &AnalyteData,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::Analyte, analyte_uuid).await?;
+    if before.is_none() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    sqlx::query!(
+        r#"
+        UPDATE analyte
+        SET
+            display_name = $1,
+            kind = $2,
+            unit = $3,
+            reference_dose = $4,
+            general_reference_dose = $5,
+            detection_limit = $6
+        WHERE id = $7;
+        "#,
+        data.display_name.trim(),
+        data.kind,
+        data.unit.trim(),
+        data.reference_dose,
+        data.general_reference_dose,
+        data.detection_limit,
+        analyte_uuid,
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::Analyte, analyte_uuid).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::Analyte,
+        analyte_uuid,
+        before,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
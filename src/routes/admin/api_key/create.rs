@@ -0,0 +1,109 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::{generate_api_key, hash_token, ApiScope, SessionUser};
+use actix_web::{post, web, HttpResponse};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct NewApiKey {
+    label: String,
+    scope: ApiScope,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(serde::Serialize)]
+pub struct CreatedApiKey {
+    id: Uuid,
+    label: String,
+    scope: ApiScope,
+    expires_at: Option<DateTime<Utc>>,
+    /// Only returned here, it can't be retrieved again.
+    key: String,
+}
+
+/// Creates a new API key for a client app. To rotate a key, create a new one,
+/// update the app and then revoke the old key.
+///
+/// # Example
+///
+///```json
+/// {
+///     "label": "iOS app",
+///     "scope": "user",
+///     "expires_at": "2025-09-01T00:00:00Z"
+/// }
+///```
+#[tracing::instrument(name = "Creating a new API key.", skip(data, db_pool, admin))]
+#[post("/")]
+pub async fn create_api_key(
+    data: web::Json<NewApiKey>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let data = data.into_inner();
+    let id = Uuid::new_v4();
+    let key = generate_api_key();
+    match new_api_key_db(&db_pool, admin.user_id, id, &key, &data).await {
+        Ok(_) => {
+            tracing::info!("New API key has been added.");
+            Ok(HttpResponse::Ok().json(CreatedApiKey {
+                id,
+                label: data.label,
+                scope: data.scope,
+                expires_at: data.expires_at,
+                key,
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[tracing::instrument(
+    name = "Saving a new API key to the database",
+    skip(db_pool, key, data)
+)]
+async fn new_api_key_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    id: Uuid,
+    key: &str,
+    data: &NewApiKey,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO api_keys (id, label, key_hash, scope, created_at, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6);
+        "#,
+        id,
+        data.label,
+        hash_token(key),
+        data.scope.as_str(),
+        Utc::now(),
+        data.expires_at
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::ApiKey, id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::ApiKey,
+        id,
+        None,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
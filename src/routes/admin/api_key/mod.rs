@@ -0,0 +1,7 @@
+mod create;
+mod read_all;
+mod revoke;
+
+pub use create::create_api_key;
+pub use read_all::read_all_api_keys;
+pub use revoke::revoke_api_key;
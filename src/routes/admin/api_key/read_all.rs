@@ -0,0 +1,57 @@
+use actix_web::{get, web, HttpResponse};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Serialize)]
+pub struct ApiKeyData {
+    id: Uuid,
+    label: String,
+    scope: String,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+#[tracing::instrument(name = "Retreving all API keys.", skip(db_pool))]
+#[get("/")]
+pub async fn read_all_api_keys(db_pool: web::Data<PgPool>) -> HttpResponse {
+    match get_api_key_data(&db_pool).await {
+        Ok(data) => {
+            tracing::info!("All API key data has been queried from the db.");
+            HttpResponse::Ok().json(data)
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Querying the database", skip(db_pool))]
+async fn get_api_key_data(db_pool: &PgPool) -> Result<Vec<ApiKeyData>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        ApiKeyData,
+        r#"
+        SELECT
+            id,
+            label,
+            scope,
+            created_at,
+            expires_at,
+            last_used_at,
+            revoked_at
+        FROM api_keys
+        ORDER BY created_at;
+        "#
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
@@ -0,0 +1,70 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{delete, web, HttpResponse};
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct ApiKeyUuid {
+    uuid: Uuid,
+}
+
+/// Revokes an API key, immediately rejecting every request made with it.
+#[tracing::instrument(name = "Revoking an API key", skip(uuid, db_pool, admin))]
+#[delete("/{uuid}")]
+pub async fn revoke_api_key(
+    uuid: web::Path<ApiKeyUuid>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    match revoke_api_key_db(&db_pool, admin.user_id, uuid.uuid).await {
+        Ok(_) => {
+            tracing::info!("API key has been revoked.");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Revoking an API key in the database", skip(db_pool))]
+async fn revoke_api_key_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    api_key_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::ApiKey, api_key_id).await?;
+    sqlx::query!(
+        r#"
+        UPDATE api_keys
+        SET revoked_at = $2
+        WHERE id = $1 AND revoked_at IS NULL;
+        "#,
+        api_key_id,
+        Utc::now()
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::ApiKey, api_key_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::ApiKey,
+        api_key_id,
+        before,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
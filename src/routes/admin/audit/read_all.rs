@@ -0,0 +1,109 @@
+use actix_web::{get, web, HttpResponse};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct AuditQuery {
+    entity: Option<String>,
+    entity_id: Option<Uuid>,
+    actor_id: Option<Uuid>,
+    limit: Option<i64>,
+}
+
+#[derive(serde::Serialize)]
+pub struct AuditEntry {
+    id: Uuid,
+    actor_id: Uuid,
+    /// None if the actor's account has since been deleted.
+    actor_email: Option<String>,
+    action: String,
+    entity: String,
+    entity_id: Uuid,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+    created_at: DateTime<Utc>,
+}
+
+/// Lists the most recent admin changes, optionally filtered by entity
+/// (`fish`, `fish_type`, `recipe`, `user`, `api_key`, `lake`, `sampling_site`,
+/// `analyte` or `data_source`), entity id or actor.
+/// Returns at most `limit` entries, 100 by default.
+///
+/// # Example
+///
+/// `.../admin/audit/?entity=fish&actor_id=1fe5c906-d09d-11ed-afa1-0242ac120002`
+///
+///```json
+/// [
+///     {
+///         "id": "9b2b3f0e-6a55-4d6b-b3d2-6f2c1f1c3c2a",
+///         "actor_id": "1fe5c906-d09d-11ed-afa1-0242ac120002",
+///         "actor_email": "admin@example.com",
+///         "action": "update",
+///         "entity": "fish",
+///         "entity_id": "3e1f6a0e-1c55-4b7a-9d0a-2b1f4f0b7e11",
+///         "before": { "mercury": 1.1, ... },
+///         "after": { "mercury": 2.1, ... },
+///         "created_at": "2024-09-09T12:00:00Z"
+///     }
+/// ]
+///```
+#[tracing::instrument(name = "Retreving the audit log.", skip(query, db_pool))]
+#[get("/")]
+pub async fn read_audit_log(
+    query: web::Query<AuditQuery>,
+    db_pool: web::Data<PgPool>,
+) -> HttpResponse {
+    match get_audit_log(&db_pool, query.into_inner()).await {
+        Ok(data) => {
+            tracing::info!("Audit log has been queried from the db.");
+            HttpResponse::Ok().json(data)
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Querying the database", skip(db_pool))]
+async fn get_audit_log(
+    db_pool: &PgPool,
+    query: AuditQuery,
+) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        AuditEntry,
+        r#"
+        SELECT
+            audit_log.id,
+            actor_id,
+            users.email as "actor_email?",
+            action,
+            entity,
+            entity_id,
+            before,
+            after,
+            audit_log.created_at
+        FROM audit_log
+        LEFT JOIN users ON users.id = audit_log.actor_id
+        WHERE ($1::TEXT IS NULL OR entity = $1)
+            AND ($2::UUID IS NULL OR entity_id = $2)
+            AND ($3::UUID IS NULL OR actor_id = $3)
+        ORDER BY audit_log.created_at DESC
+        LIMIT $4;
+        "#,
+        query.entity,
+        query.entity_id,
+        query.actor_id,
+        query.limit.unwrap_or(100).clamp(1, 1000)
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
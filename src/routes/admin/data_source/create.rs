@@ -0,0 +1,101 @@
+use super::DataSourceData;
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{post, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Adds a data source. Returns the new source's id so samples can cite it.
+///
+/// # Example
+///
+/// `.../admin/data_source/`
+///
+///```json
+/// {
+///     "agency": "GLIFWC",
+///     "study": "Mercury in Ceded Territory Walleye",
+///     "url": "https://glifwc.org/mercury",
+///     "lab_method": "EPA Method 7473",
+///     "collection_year": 2019,
+///     "license": "CC BY 4.0"
+/// }
+///```
+#[tracing::instrument(name = "Creating a new data source.", skip(data, db_pool, admin))]
+#[post("/")]
+pub async fn create_data_source(
+    data: web::Json<DataSourceData>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    if let Err(reason) = data.validate() {
+        return HttpResponse::BadRequest().body(reason);
+    }
+    let source_id = Uuid::new_v4();
+    match new_data_source_db(&db_pool, admin.user_id, source_id, &data).await {
+        Ok(_) => {
+            tracing::info!("New data source has been added.");
+            HttpResponse::Ok().json(serde_json::json!({ "id": source_id }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(
+    name = "Saving new data source data to the database",
+    skip(db_pool, data)
+)]
+async fn new_data_source_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    source_id: Uuid,
+    data: &DataSourceData,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO data_source (
+            id,
+            agency,
+            study,
+            url,
+            lab_method,
+            collection_year,
+            license
+        )
+        VALUES (
+            $1, $2, $3, $4, $5, $6, $7
+        );
+        "#,
+        source_id,
+        data.agency.trim(),
+        data.study,
+        data.url,
+        data.lab_method,
+        data.collection_year,
+        data.license
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::DataSource, source_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::DataSource,
+        source_id,
+        None,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
@@ -0,0 +1,69 @@
+use super::DataSourceUuid;
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{delete, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Deletes a data source. Sources still cited by fish samples can't be
+/// deleted.
+#[tracing::instrument(name = "Deleting data source data", skip(uuid, db_pool, admin))]
+#[delete("/{uuid}")]
+pub async fn delete_data_source(
+    uuid: web::Path<DataSourceUuid>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    match delete_data_source_db(&db_pool, admin.user_id, uuid.uuid).await {
+        Ok(_) => {
+            tracing::info!("Data source has been deleted.");
+            HttpResponse::Ok().finish()
+        }
+        Err(e)
+            if e.as_database_error()
+                .is_some_and(|e| e.is_foreign_key_violation()) =>
+        {
+            HttpResponse::BadRequest().body("The data source is still cited by fish samples.")
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Deleting data source data from the database", skip(db_pool))]
+async fn delete_data_source_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    source_uuid: Uuid,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::DataSource, source_uuid).await?;
+    sqlx::query!(
+        r#"
+        DELETE FROM data_source
+        WHERE id = $1;
+        "#,
+        source_uuid
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::DataSource,
+        source_uuid,
+        before,
+        None,
+    )
+    .await?;
+
+    transaction.commit().await
+}
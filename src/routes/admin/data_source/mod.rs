@@ -0,0 +1,46 @@
+mod create;
+mod delete;
+mod update;
+
+pub use create::create_data_source;
+pub use delete::delete_data_source;
+pub use update::update_data_source;
+
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct DataSourceUuid {
+    uuid: Uuid,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct DataSourceData {
+    agency: String,
+    study: Option<String>,
+    url: Option<String>,
+    lab_method: Option<String>,
+    collection_year: Option<i32>,
+    license: Option<String>,
+}
+
+impl DataSourceData {
+    /// Every source names its agency. A URL has to be something a browser can
+    /// follow, and a collection year has to be plausible.
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.agency.trim().is_empty() {
+            return Err("The data source needs an agency.");
+        }
+        if let Some(url) = &self.url {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err("The URL must start with http:// or https://.");
+            }
+        }
+        if self
+            .collection_year
+            .is_some_and(|year| !(1900..=2100).contains(&year))
+        {
+            return Err("The collection year is out of range.");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use super::{DataSourceData, DataSourceUuid};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{put, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[tracing::instrument(name = "Updating data source data", skip(uuid, data, db_pool, admin))]
+#[put("/{uuid}")]
+pub async fn update_data_source(
+    uuid: web::Path<DataSourceUuid>,
+    data: web::Json<DataSourceData>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    if let Err(reason) = data.validate() {
+        return HttpResponse::BadRequest().body(reason);
+    }
+    match update_data_source_db(&db_pool, admin.user_id, uuid.uuid, &data).await {
+        Ok(_) => {
+            tracing::info!("Data source has been updated.");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            match e {
+                sqlx::Error::RowNotFound => HttpResponse::BadRequest().finish(),
+                _ => HttpResponse::InternalServerError().finish(),
+            }
+        }
+    }
+}
+
+#[tracing::instrument(name = "Saving data source data to the database", skip(db_pool, data))]
+async fn update_data_source_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    source_uuid: Uuid,
+    data: &DataSourceData,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::DataSource, source_uuid).await?;
+    if before.is_none() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    sqlx::query!(
+        r#"
+        UPDATE data_source
+        SET
+            agency = $1,
+            study = $2,
+            url = $3,
+            lab_method = $4,
+            collection_year = $5,
+            license = $6
+        WHERE id = $7;
+        "#,
+        data.agency.trim(),
+        data.study,
+        data.url,
+        data.lab_method,
+        data.collection_year,
+        data.license,
+        source_uuid,
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::DataSource, source_uuid).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::DataSource,
+        source_uuid,
+        before,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
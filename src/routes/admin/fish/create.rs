@@ -1,6 +1,12 @@
+use super::{measurements_are_valid, save_measurements, size_is_valid, MeasurementData};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use crate::routes::lake_exists;
 use actix_web::{post, web, HttpResponse};
 use anyhow::Result;
+use chrono::NaiveDate;
 use sqlx::PgPool;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(serde::Deserialize)]
@@ -12,17 +18,55 @@ pub struct FishData {
     pub(crate) omega_3_ratio: f32,
     pub(crate) pcb: f32,
     pub(crate) protein: f32,
+    pub(crate) sampling_site_id: Option<Uuid>,
+    pub(crate) sample_date: Option<NaiveDate>,
+    pub(crate) data_source_id: Option<Uuid>,
+    pub(crate) length_cm: Option<f64>,
+    pub(crate) weight_g: Option<f64>,
+    /// Analyte name to `MeasurementData`, for anything beyond the fields above. A legacy
+    /// analyte given here wins over its field.
+    #[serde(default)]
+    pub(crate) measurements: HashMap<String, MeasurementData>,
 }
 
-#[tracing::instrument(name = "Creating a new fish.", skip(data, db_pool))]
+#[tracing::instrument(name = "Creating a new fish.", skip(data, db_pool, admin))]
 #[post("/")]
-pub async fn new_fish(data: web::Json<FishData>, db_pool: web::Data<PgPool>) -> HttpResponse {
+pub async fn new_fish(
+    data: web::Json<FishData>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    match lake_exists(&db_pool, &data.lake).await {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::BadRequest().body("Unknown lake."),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+    if !size_is_valid(data.length_cm, data.weight_g) {
+        return HttpResponse::BadRequest().body("Length and weight must be positive.");
+    }
+    match measurements_are_valid(&db_pool, &data.measurements).await {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::BadRequest().body("Unknown analyte or invalid level."),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
     let fish_id = Uuid::new_v4();
-    match new_fish_db(&db_pool, fish_id, data).await {
+    match new_fish_db(&db_pool, admin.user_id, fish_id, data).await {
         Ok(_) => {
             tracing::info!("New fish has been added.");
             HttpResponse::Ok().finish()
         }
+        Err(e)
+            if e.as_database_error()
+                .is_some_and(|e| e.is_foreign_key_violation()) =>
+        {
+            HttpResponse::BadRequest().body("Unknown fish type, sampling site or data source.")
+        }
         Err(e) => {
             tracing::error!("Failed to execute query: {:?}", e);
             HttpResponse::InternalServerError().finish()
@@ -33,9 +77,11 @@ pub async fn new_fish(data: web::Json<FishData>, db_pool: web::Data<PgPool>) ->
 #[tracing::instrument(name = "Saving new fish data to the database", skip(db_pool, data))]
 async fn new_fish_db(
     db_pool: &PgPool,
+    actor_id: Uuid,
     fish_id: Uuid,
     data: web::Json<FishData>,
 ) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
     sqlx::query!(
         r#"
         INSERT INTO fish (
@@ -46,10 +92,15 @@ async fn new_fish_db(
             omega_3,
             omega_3_ratio,
             pcb,
-            protein
+            protein,
+            sampling_site_id,
+            sample_date,
+            data_source_id,
+            length_cm,
+            weight_g
         )
         VALUES (
-            $1, $2, $3, $4, $5, $6, $7, $8
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13
         );
         "#,
         fish_id,
@@ -59,14 +110,31 @@ async fn new_fish_db(
         data.omega_3,
         data.omega_3_ratio,
         data.pcb,
-        data.protein
+        data.protein,
+        data.sampling_site_id,
+        data.sample_date,
+        data.data_source_id,
+        data.length_cm,
+        data.weight_g
     )
-    .execute(db_pool)
+    .execute(&mut *transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
+    save_measurements(&mut transaction, fish_id, &data.measurements).await?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::Fish, fish_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::Fish,
+        fish_id,
+        None,
+        after,
+    )
+    .await?;
 
-    Ok(())
+    transaction.commit().await
 }
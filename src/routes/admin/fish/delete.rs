@@ -1,3 +1,5 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
 use actix_web::{delete, web, HttpResponse};
 use anyhow::Result;
 use sqlx::PgPool;
@@ -8,10 +10,14 @@ pub struct FishUuid {
     uuid: Uuid,
 }
 
-#[tracing::instrument(name = "Deleting fish data", skip(uuid, db_pool))]
+#[tracing::instrument(name = "Deleting fish data", skip(uuid, db_pool, admin))]
 #[delete("/{uuid}")]
-pub async fn delete_fish(uuid: web::Path<FishUuid>, db_pool: web::Data<PgPool>) -> HttpResponse {
-    match delete_fish_db(&db_pool, uuid.uuid).await {
+pub async fn delete_fish(
+    uuid: web::Path<FishUuid>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    match delete_fish_db(&db_pool, admin.user_id, uuid.uuid).await {
         Ok(_) => {
             tracing::info!("Fish has been deleted.");
             HttpResponse::Ok().finish()
@@ -24,7 +30,13 @@ pub async fn delete_fish(uuid: web::Path<FishUuid>, db_pool: web::Data<PgPool>)
 }
 
 #[tracing::instrument(name = "Deleting fish data from the database", skip(db_pool))]
-async fn delete_fish_db(db_pool: &PgPool, fish_uuid: Uuid) -> Result<(), sqlx::Error> {
+async fn delete_fish_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    fish_uuid: Uuid,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::Fish, fish_uuid).await?;
     sqlx::query!(
         r#"
         DELETE FROM fish
@@ -32,12 +44,22 @@ async fn delete_fish_db(db_pool: &PgPool, fish_uuid: Uuid) -> Result<(), sqlx::E
         "#,
         fish_uuid
     )
-    .execute(db_pool)
+    .execute(&mut *transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
 
-    Ok(())
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::Fish,
+        fish_uuid,
+        before,
+        None,
+    )
+    .await?;
+
+    transaction.commit().await
 }
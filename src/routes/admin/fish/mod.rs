@@ -5,3 +5,120 @@ mod update;
 pub use create::new_fish;
 pub use delete::delete_fish;
 pub use update::update_fish;
+
+use crate::routes::analytes_exist;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A measurement as given to the admin routes, either just the detected level
+/// or the details, e.g. `{"detected": false, "detection_limit": 0.01}` for a
+/// non-detect.
+#[derive(serde::Deserialize, Debug)]
+#[serde(untagged)]
+pub enum MeasurementData {
+    Level(f64),
+    Detailed {
+        value: Option<f64>,
+        #[serde(default = "detected_by_default")]
+        detected: bool,
+        detection_limit: Option<f64>,
+    },
+}
+
+fn detected_by_default() -> bool {
+    true
+}
+
+impl MeasurementData {
+    /// The value, whether it was detected and the detection limit. `None` if
+    /// they don't add up: detected levels need a value, non-detects are 0.
+    fn parts(&self) -> Option<(f64, bool, Option<f64>)> {
+        let (value, detected, detection_limit) = match *self {
+            MeasurementData::Level(value) => (value, true, None),
+            MeasurementData::Detailed {
+                value,
+                detected: true,
+                detection_limit,
+            } => (value?, true, detection_limit),
+            MeasurementData::Detailed {
+                value,
+                detected: false,
+                detection_limit,
+            } if value.unwrap_or(0.0) == 0.0 => (0.0, false, detection_limit),
+            MeasurementData::Detailed { .. } => return None,
+        };
+        let is_valid = value.is_finite()
+            && value >= 0.0
+            && detection_limit.is_none_or(|limit| limit.is_finite() && limit > 0.0);
+        is_valid.then_some((value, detected, detection_limit))
+    }
+}
+
+/// Whether the length and weight, when given, are positive.
+fn size_is_valid(length_cm: Option<f64>, weight_g: Option<f64>) -> bool {
+    [length_cm, weight_g]
+        .into_iter()
+        .flatten()
+        .all(|size| size.is_finite() && size > 0.0)
+}
+
+/// Whether every measurement is of an analyte in the catalog and adds up.
+async fn measurements_are_valid(
+    db_pool: &PgPool,
+    measurements: &HashMap<String, MeasurementData>,
+) -> Result<bool, sqlx::Error> {
+    if measurements.values().any(|data| data.parts().is_none()) {
+        return Ok(false);
+    }
+    let names: Vec<String> = measurements.keys().cloned().collect();
+    analytes_exist(db_pool, &names).await
+}
+
+/// Adds or replaces measurements of a fish. The database copies mercury, pcb,
+/// omega_3, omega_3_ratio and protein to the legacy columns of `fish`.
+#[tracing::instrument(name = "Saving measurements to the database", skip(transaction))]
+async fn save_measurements(
+    transaction: &mut Transaction<'_, Postgres>,
+    fish_id: Uuid,
+    measurements: &HashMap<String, MeasurementData>,
+) -> Result<(), sqlx::Error> {
+    let mut analytes = vec![];
+    let mut values = vec![];
+    let mut detected = vec![];
+    let mut detection_limits = vec![];
+    for (analyte, data) in measurements {
+        if let Some((value, is_detected, detection_limit)) = data.parts() {
+            analytes.push(analyte.clone());
+            values.push(value);
+            detected.push(is_detected);
+            detection_limits.push(detection_limit);
+        }
+    }
+    sqlx::query!(
+        r#"
+        INSERT INTO measurement (fish_id, analyte, value, detected, detection_limit)
+        SELECT $1, analyte, value, detected, detection_limit
+        FROM UNNEST($2::TEXT[], $3::FLOAT8[], $4::BOOLEAN[], $5::FLOAT8[])
+            AS new (analyte, value, detected, detection_limit)
+        ON CONFLICT (fish_id, analyte) DO UPDATE
+        SET
+            value = EXCLUDED.value,
+            detected = EXCLUDED.detected,
+            detection_limit = EXCLUDED.detection_limit;
+        "#,
+        fish_id,
+        &analytes,
+        &values,
+        &detected,
+        &detection_limits as &[Option<f64>]
+    )
+    .execute(&mut **transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
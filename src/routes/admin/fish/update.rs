@@ -1,6 +1,11 @@
+use super::{measurements_are_valid, save_measurements, size_is_valid, MeasurementData};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
 use actix_web::{put, web, HttpResponse};
 use anyhow::Result;
+use chrono::NaiveDate;
 use sqlx::PgPool;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(serde::Deserialize)]
@@ -15,20 +20,51 @@ pub struct FishData {
     pub(crate) omega_3_ratio: f32,
     pub(crate) pcb: f32,
     pub(crate) protein: f32,
+    /// Left as is when not given.
+    pub(crate) sampling_site_id: Option<Uuid>,
+    /// Left as is when not given.
+    pub(crate) sample_date: Option<NaiveDate>,
+    /// Left as is when not given.
+    pub(crate) data_source_id: Option<Uuid>,
+    /// Left as is when not given.
+    pub(crate) length_cm: Option<f64>,
+    /// Left as is when not given.
+    pub(crate) weight_g: Option<f64>,
+    /// Analyte name to `MeasurementData`. Analytes not given are left as is.
+    #[serde(default)]
+    pub(crate) measurements: HashMap<String, MeasurementData>,
 }
 
-#[tracing::instrument(name = "Updating fish data", skip(uuid, data, db_pool))]
+#[tracing::instrument(name = "Updating fish data", skip(uuid, data, db_pool, admin))]
 #[put("/{uuid}")]
 pub async fn update_fish(
     uuid: web::Path<FishUuid>,
     data: web::Json<FishData>,
     db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
 ) -> HttpResponse {
-    match update_fish_db(&db_pool, uuid.uuid, data).await {
+    if !size_is_valid(data.length_cm, data.weight_g) {
+        return HttpResponse::BadRequest().body("Length and weight must be positive.");
+    }
+    match measurements_are_valid(&db_pool, &data.measurements).await {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::BadRequest().body("Unknown analyte or invalid level."),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+    match update_fish_db(&db_pool, admin.user_id, uuid.uuid, data).await {
         Ok(_) => {
             tracing::info!("Fish has been updated.");
             HttpResponse::Ok().finish()
         }
+        Err(e)
+            if e.as_database_error()
+                .is_some_and(|e| e.is_foreign_key_violation()) =>
+        {
+            HttpResponse::BadRequest().body("Unknown sampling site or data source.")
+        }
         Err(e) => {
             tracing::error!("Failed to execute query: {:?}", e);
             HttpResponse::InternalServerError().finish()
@@ -39,9 +75,12 @@ pub async fn update_fish(
 #[tracing::instrument(name = "Saving fish data to the database", skip(db_pool, data))]
 async fn update_fish_db(
     db_pool: &PgPool,
+    actor_id: Uuid,
     fish_uuid: Uuid,
     data: web::Json<FishData>,
 ) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::Fish, fish_uuid).await?;
     sqlx::query!(
         r#"
         UPDATE fish
@@ -50,22 +89,44 @@ async fn update_fish_db(
             omega_3 = $2,
             omega_3_ratio = $3,
             pcb = $4,
-            protein = $5
-        WHERE id = $6
+            protein = $5,
+            sampling_site_id = COALESCE($6, sampling_site_id),
+            sample_date = COALESCE($7, sample_date),
+            data_source_id = COALESCE($8, data_source_id),
+            length_cm = COALESCE($9, length_cm),
+            weight_g = COALESCE($10, weight_g)
+        WHERE id = $11
         "#,
         data.mercury,
         data.omega_3,
         data.omega_3_ratio,
         data.pcb,
         data.protein,
+        data.sampling_site_id,
+        data.sample_date,
+        data.data_source_id,
+        data.length_cm,
+        data.weight_g,
         fish_uuid,
     )
-    .execute(db_pool)
+    .execute(&mut *transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
+    save_measurements(&mut transaction, fish_uuid, &data.measurements).await?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::Fish, fish_uuid).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::Fish,
+        fish_uuid,
+        before,
+        after,
+    )
+    .await?;
 
-    Ok(())
+    transaction.commit().await
 }
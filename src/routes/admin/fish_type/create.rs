@@ -1,27 +1,30 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
 use crate::routes::admin::fish_type::insert_recipes_fish_type;
 use actix_web::{post, web, HttpResponse};
 use anyhow::Result;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
 #[derive(serde::Deserialize)]
 pub struct NewFishType {
-    name: String,
-    anishinaabe_name: String,
-    recipe: Option<Vec<Uuid>>,
-    fish_image: String,
-    woodland_fish_image: Option<String>,
-    about: String,
+    pub(crate) name: String,
+    pub(crate) anishinaabe_name: String,
+    pub(crate) recipe: Option<Vec<Uuid>>,
+    pub(crate) fish_image: String,
+    pub(crate) woodland_fish_image: Option<String>,
+    pub(crate) about: String,
 }
 
-#[tracing::instrument(name = "Creating a new fish type.", skip(data, db_pool))]
+#[tracing::instrument(name = "Creating a new fish type.", skip(data, db_pool, admin))]
 #[post("/")]
 pub async fn create_fish_type(
     data: web::Json<NewFishType>,
     db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
 ) -> Result<HttpResponse, actix_web::Error> {
     let fish_type_id = Uuid::new_v4();
-    match new_fish_type_db(&db_pool, fish_type_id, data).await {
+    match new_fish_type_db(&db_pool, admin.user_id, fish_type_id, data).await {
         Ok(_) => {
             tracing::info!("New fish has been added.");
             Ok(HttpResponse::Ok().finish())
@@ -39,8 +42,21 @@ pub async fn create_fish_type(
 )]
 async fn new_fish_type_db(
     db_pool: &PgPool,
+    actor_id: Uuid,
     fish_type_id: Uuid,
     data: web::Json<NewFishType>,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    insert_fish_type(&mut transaction, actor_id, fish_type_id, &data).await?;
+    transaction.commit().await
+}
+
+/// Inserts a fish type and its recipes, and audits it.
+pub async fn insert_fish_type(
+    transaction: &mut Transaction<'_, Postgres>,
+    actor_id: Uuid,
+    fish_type_id: Uuid,
+    data: &NewFishType,
 ) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
@@ -63,7 +79,7 @@ async fn new_fish_type_db(
         data.woodland_fish_image,
         data.about
     )
-    .execute(db_pool)
+    .execute(&mut **transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
@@ -73,9 +89,18 @@ async fn new_fish_type_db(
     if let Some(recipes) = &data.recipe {
         tracing::info!("Inserting recipes into user_recipe join table.");
         for recipe_id in recipes {
-            insert_recipes_fish_type(db_pool, fish_type_id, *recipe_id).await?;
+            insert_recipes_fish_type(transaction, fish_type_id, *recipe_id).await?;
         }
     };
 
-    Ok(())
+    let after = audit_snapshot(transaction, AuditEntity::FishType, fish_type_id).await?;
+    record_audit(
+        transaction,
+        actor_id,
+        AuditEntity::FishType,
+        fish_type_id,
+        None,
+        after,
+    )
+    .await
 }
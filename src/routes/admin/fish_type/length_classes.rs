@@ -0,0 +1,142 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{put, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct FishTypeId {
+    uuid: Uuid,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct LengthClassData {
+    name: String,
+    #[serde(default)]
+    min_length_cm: f64,
+    max_length_cm: Option<f64>,
+}
+
+impl LengthClassData {
+    /// Names go in query strings, so they're kept to lowercase letters, digits
+    /// and underscores.
+    fn is_valid(&self) -> bool {
+        let is_valid_name = !self.name.is_empty()
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        is_valid_name
+            && self.min_length_cm.is_finite()
+            && self.min_length_cm >= 0.0
+            && self
+                .max_length_cm
+                .is_none_or(|max| max.is_finite() && max > self.min_length_cm)
+    }
+}
+
+/// Replaces the length classes of a fish type.
+///
+/// # Example
+///
+/// `.../admin/fish_type/1fe5c906-d09d-11ed-afa1-0242ac120022/length_classes`
+///
+///```json
+/// [
+///     { "name": "under_38cm", "min_length_cm": 0.0, "max_length_cm": 38.1 },
+///     { "name": "38cm_and_over", "min_length_cm": 38.1, "max_length_cm": null }
+/// ]
+///```
+#[tracing::instrument(name = "Updating length classes.", skip(data, db_pool, admin))]
+#[put("/{uuid}/length_classes")]
+pub async fn update_length_classes(
+    fish_type_id: web::Path<FishTypeId>,
+    data: web::Json<Vec<LengthClassData>>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    if !data.iter().all(LengthClassData::is_valid) {
+        return HttpResponse::BadRequest()
+            .body("Length classes need a lowercase name and a min below their max.");
+    }
+    match update_length_classes_db(&db_pool, admin.user_id, fish_type_id.uuid, &data).await {
+        Ok(_) => {
+            tracing::info!("Length classes have been updated.");
+            HttpResponse::Ok().finish()
+        }
+        Err(e)
+            if e.as_database_error()
+                .is_some_and(|e| e.is_unique_violation()) =>
+        {
+            HttpResponse::BadRequest().body("Length class names must be unique.")
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            match e {
+                sqlx::Error::RowNotFound => HttpResponse::BadRequest().finish(),
+                _ => HttpResponse::InternalServerError().finish(),
+            }
+        }
+    }
+}
+
+#[tracing::instrument(name = "Saving length classes to the database", skip(db_pool, data))]
+async fn update_length_classes_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    fish_type_id: Uuid,
+    data: &[LengthClassData],
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::FishType, fish_type_id).await?;
+    if before.is_none() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    sqlx::query!(
+        r#"
+        DELETE FROM length_class
+        WHERE fish_type_id = $1;
+        "#,
+        fish_type_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    for class in data {
+        sqlx::query!(
+            r#"
+            INSERT INTO length_class (id, fish_type_id, name, min_length_cm, max_length_cm)
+            VALUES ($1, $2, $3, $4, $5);
+            "#,
+            Uuid::new_v4(),
+            fish_type_id,
+            class.name,
+            class.min_length_cm,
+            class.max_length_cm
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to execute the query: {:?}", e);
+            e
+        })?;
+    }
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::FishType, fish_type_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::FishType,
+        fish_type_id,
+        before,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
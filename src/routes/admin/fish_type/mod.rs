@@ -1,11 +1,15 @@
 mod create;
+mod length_classes;
 mod read;
 mod read_all;
 mod update;
 mod update_image;
 
-pub use create::create_fish_type;
+pub use create::{create_fish_type, insert_fish_type, NewFishType};
+pub use length_classes::update_length_classes;
 pub use read::read_fish_type;
 pub use read_all::read_all_fish_types;
-pub use update::{insert_recipes_fish_type, update_fish_type};
+pub use update::{
+    insert_recipes_fish_type, save_fish_type_update, update_fish_type, UpdateFishType,
+};
 pub use update_image::update_fish_type_image;
@@ -1,6 +1,8 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
 use actix_web::{put, web, HttpResponse};
 use anyhow::Result;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
 #[derive(serde::Deserialize, Debug)]
@@ -10,20 +12,21 @@ pub struct FishTypeId {
 
 #[derive(serde::Deserialize)]
 pub struct UpdateFishType {
-    name: String,
-    anishinaabe_name: String,
-    recipe: Option<Vec<Uuid>>,
-    about: String,
+    pub(crate) name: String,
+    pub(crate) anishinaabe_name: String,
+    pub(crate) recipe: Option<Vec<Uuid>>,
+    pub(crate) about: String,
 }
 
-#[tracing::instrument(name = "Updating a fish type.", skip(data, db_pool))]
+#[tracing::instrument(name = "Updating a fish type.", skip(data, db_pool, admin))]
 #[put("/{uuid}")]
 pub async fn update_fish_type(
     fish_type_id: web::Path<FishTypeId>,
     data: web::Json<UpdateFishType>,
     db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    match update_fish_type_db(&db_pool, fish_type_id.uuid, data).await {
+    match update_fish_type_db(&db_pool, admin.user_id, fish_type_id.uuid, data).await {
         Ok(_) => {
             tracing::info!("Fish type has been updated.");
             Ok(HttpResponse::Ok().finish())
@@ -41,9 +44,23 @@ pub async fn update_fish_type(
 )]
 async fn update_fish_type_db(
     db_pool: &PgPool,
+    actor_id: Uuid,
     fish_type_id: Uuid,
     data: web::Json<UpdateFishType>,
 ) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    save_fish_type_update(&mut transaction, actor_id, fish_type_id, &data).await?;
+    transaction.commit().await
+}
+
+/// Updates a fish type, and its recipes when they're given, and audits it.
+pub async fn save_fish_type_update(
+    transaction: &mut Transaction<'_, Postgres>,
+    actor_id: Uuid,
+    fish_type_id: Uuid,
+    data: &UpdateFishType,
+) -> Result<(), sqlx::Error> {
+    let before = audit_snapshot(transaction, AuditEntity::FishType, fish_type_id).await?;
     sqlx::query!(
         r#"
         UPDATE fish_type
@@ -58,7 +75,7 @@ async fn update_fish_type_db(
         data.about,
         fish_type_id
     )
-    .execute(db_pool)
+    .execute(&mut **transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
@@ -66,22 +83,34 @@ async fn update_fish_type_db(
     })?;
 
     if let Some(recipes) = &data.recipe {
-        delete_recipes_fish_type(db_pool, fish_type_id).await?;
+        delete_recipes_fish_type(transaction, fish_type_id).await?;
 
         tracing::info!("Inserting recipes into user_recipe join table.");
         for recipe_id in recipes {
-            insert_recipes_fish_type(db_pool, fish_type_id, *recipe_id).await?;
+            insert_recipes_fish_type(transaction, fish_type_id, *recipe_id).await?;
         }
     };
 
-    Ok(())
+    let after = audit_snapshot(transaction, AuditEntity::FishType, fish_type_id).await?;
+    record_audit(
+        transaction,
+        actor_id,
+        AuditEntity::FishType,
+        fish_type_id,
+        before,
+        after,
+    )
+    .await
 }
 
 #[tracing::instrument(
     name = "Clearing fish type data in the fishtype_recipe table.",
-    skip(db_pool, fish_type_id)
+    skip(transaction, fish_type_id)
 )]
-async fn delete_recipes_fish_type(db_pool: &PgPool, fish_type_id: Uuid) -> Result<(), sqlx::Error> {
+async fn delete_recipes_fish_type(
+    transaction: &mut Transaction<'_, Postgres>,
+    fish_type_id: Uuid,
+) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
         DELETE FROM fishtype_recipe
@@ -89,7 +118,7 @@ async fn delete_recipes_fish_type(db_pool: &PgPool, fish_type_id: Uuid) -> Resul
         "#,
         fish_type_id
     )
-    .execute(db_pool)
+    .execute(&mut **transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
@@ -100,7 +129,7 @@ async fn delete_recipes_fish_type(db_pool: &PgPool, fish_type_id: Uuid) -> Resul
 }
 
 pub async fn insert_recipes_fish_type(
-    db_pool: &PgPool,
+    transaction: &mut Transaction<'_, Postgres>,
     fish_type_id: Uuid,
     recipe_id: Uuid,
 ) -> Result<(), sqlx::Error> {
@@ -116,7 +145,7 @@ pub async fn insert_recipes_fish_type(
         fish_type_id,
         recipe_id
     )
-    .execute(db_pool)
+    .execute(&mut **transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
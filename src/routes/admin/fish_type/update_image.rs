@@ -1,3 +1,5 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
 use actix_web::{put, web, HttpResponse};
 use anyhow::Result;
 use sqlx::PgPool;
@@ -14,15 +16,17 @@ pub struct FishTypeImageData {
     woodland_image_flag: bool,
 }
 
-#[tracing::instrument(name = "Updating a fish image.", skip(data, db_pool))]
+#[tracing::instrument(name = "Updating a fish image.", skip(data, db_pool, admin))]
 #[put("/{uuid}/image")]
 pub async fn update_fish_type_image(
     fish_type_id: web::Path<FishTypeId>,
     data: web::Json<FishTypeImageData>,
     db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
 ) -> HttpResponse {
     if data.woodland_image_flag {
-        match update_woodland_fish_image_db(&db_pool, fish_type_id.uuid, data).await {
+        match update_woodland_fish_image_db(&db_pool, admin.user_id, fish_type_id.uuid, data).await
+        {
             Ok(_) => {
                 tracing::info!("Woodland fish image has been updated.");
                 HttpResponse::Ok().finish()
@@ -33,7 +37,7 @@ pub async fn update_fish_type_image(
             }
         }
     } else {
-        match update_fish_image_db(&db_pool, fish_type_id.uuid, data).await {
+        match update_fish_image_db(&db_pool, admin.user_id, fish_type_id.uuid, data).await {
             Ok(_) => {
                 tracing::info!("Fish image has been updated.");
                 HttpResponse::Ok().finish()
@@ -49,9 +53,12 @@ pub async fn update_fish_type_image(
 #[tracing::instrument(name = "Saving new image url to db.", skip(db_pool, data))]
 async fn update_fish_image_db(
     db_pool: &PgPool,
+    actor_id: Uuid,
     fish_type_id: Uuid,
     data: web::Json<FishTypeImageData>,
 ) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::FishType, fish_type_id).await?;
     sqlx::query!(
         r#"
         UPDATE fish_type
@@ -61,22 +68,36 @@ async fn update_fish_image_db(
         data.image_url,
         fish_type_id,
     )
-    .execute(db_pool)
+    .execute(&mut *transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
 
-    Ok(())
+    let after = audit_snapshot(&mut transaction, AuditEntity::FishType, fish_type_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::FishType,
+        fish_type_id,
+        before,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
 }
 
 #[tracing::instrument(name = "Saving new woodland image url to db.", skip(db_pool, data))]
 async fn update_woodland_fish_image_db(
     db_pool: &PgPool,
+    actor_id: Uuid,
     fish_type_id: Uuid,
     data: web::Json<FishTypeImageData>,
 ) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::FishType, fish_type_id).await?;
     sqlx::query!(
         r#"
         UPDATE fish_type
@@ -86,12 +107,23 @@ async fn update_woodland_fish_image_db(
         data.image_url,
         fish_type_id,
     )
-    .execute(db_pool)
+    .execute(&mut *transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
 
-    Ok(())
+    let after = audit_snapshot(&mut transaction, AuditEntity::FishType, fish_type_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::FishType,
+        fish_type_id,
+        before,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
 }
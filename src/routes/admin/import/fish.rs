@@ -0,0 +1,507 @@
+use super::{parse_csv, ImportQuery, ImportReport, ParsedRows, RowError, RowParser};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use crate::utils::e500;
+use actix_web::{post, web, HttpResponse};
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Columns of `scripts/basic_data/fishs.csv`, and the extras an import can
+/// have. Any other column has to be the name of an analyte, or an analyte's
+/// name followed by `DETECTION_LIMIT_SUFFIX`.
+const FISH_COLUMNS: [&str; 14] = [
+    "id",
+    "fish_type_id",
+    "lake",
+    "date_sampled",
+    "mercury",
+    "omega_3",
+    "omega_3_ratio",
+    "pcb",
+    "protein",
+    "sample_date",
+    "sampling_site_id",
+    "data_source_id",
+    "length_cm",
+    "weight_g",
+];
+
+/// The suffix of the optional columns with an analyte's detection limit, e.g.
+/// `selenium_detection_limit`.
+const DETECTION_LIMIT_SUFFIX: &str = "_detection_limit";
+
+/// The legacy analytes, which are also columns of `fish`.
+const LEGACY_ANALYTES: [&str; 5] = ["mercury", "omega_3", "omega_3_ratio", "pcb", "protein"];
+
+/// What the rows are validated against.
+struct Lookups {
+    fish_types: HashSet<Uuid>,
+    lakes: HashSet<String>,
+    sampling_sites: HashSet<Uuid>,
+    data_sources: HashSet<Uuid>,
+    analytes: HashSet<String>,
+    contaminants: HashSet<String>,
+}
+
+/// A level as written in a cell: a number, `<` the detection limit for a
+/// non-detect, or `ND` for a non-detect of unknown detection limit.
+#[derive(Clone, Copy, Debug)]
+struct Level {
+    value: f64,
+    detected: bool,
+    detection_limit: Option<f64>,
+}
+
+impl Level {
+    /// Zeros of contaminants are non-detects, as they are in the legacy
+    /// columns.
+    fn parse(text: &str, is_contaminant: bool) -> Option<Self> {
+        if text.eq_ignore_ascii_case("ND") {
+            return Some(Level {
+                value: 0.0,
+                detected: false,
+                detection_limit: None,
+            });
+        }
+        if let Some(limit) = text.strip_prefix('<') {
+            return Some(Level {
+                value: 0.0,
+                detected: false,
+                detection_limit: Some(limit.trim().parse().ok()?),
+            });
+        }
+        let value: f64 = text.parse().ok()?;
+        Some(Level {
+            value,
+            detected: value != 0.0 || !is_contaminant,
+            detection_limit: None,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct FishRow {
+    id: Uuid,
+    fish_type_id: Uuid,
+    lake: String,
+    date_sampled: Option<NaiveDateTime>,
+    mercury: Option<f32>,
+    omega_3: Option<f32>,
+    omega_3_ratio: Option<f32>,
+    pcb: Option<f32>,
+    protein: Option<f32>,
+    sample_date: Option<NaiveDate>,
+    sampling_site_id: Option<Uuid>,
+    data_source_id: Option<Uuid>,
+    length_cm: Option<f64>,
+    weight_g: Option<f64>,
+    /// Every analyte, the legacy columns above included.
+    measurements: Vec<(String, Level)>,
+}
+
+/// Imports fish samples from a CSV with the columns of
+/// `scripts/basic_data/fishs.csv`. `fish_type_id` and `lake` are required,
+/// `id` is generated when empty. `sampling_site_id`, `data_source_id`,
+/// `length_cm`, `weight_g` and a column per analyte in the catalog, e.g.
+/// `selenium`, can be added.
+///
+/// Levels are numbers, or non-detects written as the CSV export writes them:
+/// `<` the detection limit, e.g. `<0.01`, or `ND` when it isn't known. A
+/// `<analyte>_detection_limit` column, e.g. `selenium_detection_limit`, can
+/// give the detection limits instead. Non-detects are stored as 0.
+///
+/// Every row is checked before anything is saved, and either every row is
+/// imported or none are. With `dry_run=true` nothing is saved and the report
+/// says what would fail. A failed import returns 400 with the same report.
+///
+/// # Example
+///
+/// `.../admin/import/fish?dry_run=true`
+///
+///```text
+/// fish_type_id,lake,mercury,omega_3,omega_3_ratio,pcb,protein,selenium
+/// aca1fb2e-cffa-11ed-afa1-0242ac120002,Superior,0.065,624.2,2.51,<0.005,17.6,0.4
+/// aca1fb2e-cffa-11ed-afa1-0242ac120002,Atlantis,0.071,598.0,2.43,0.012,17.1,ND
+///```
+///
+///```json
+/// {
+///     "dry_run": true,
+///     "rows": 2,
+///     "imported": 0,
+///     "errors": [
+///         { "row": 3, "column": "lake", "message": "Unknown lake \"Atlantis\"." }
+///     ]
+/// }
+///```
+#[tracing::instrument(name = "Importing fish", skip(body, db_pool, admin))]
+#[post("")]
+pub async fn import_fish(
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let lookups = get_lookups(&db_pool).await.map_err(e500)?;
+    let mut seen_ids = HashSet::new();
+    let ParsedRows {
+        rows,
+        count,
+        mut errors,
+    } = parse_csv(
+        &body,
+        &["fish_type_id", "lake"],
+        |column| {
+            let analyte = column
+                .strip_suffix(DETECTION_LIMIT_SUFFIX)
+                .unwrap_or(column);
+            FISH_COLUMNS.contains(&column) || lookups.analytes.contains(analyte)
+        },
+        |parser| {
+            let fish = parse_fish_row(parser, &lookups)?;
+            if !seen_ids.insert(fish.id) {
+                parser.error(Some("id"), "The id is used by an earlier row.");
+            }
+            Some(fish)
+        },
+    );
+
+    let ids: Vec<Uuid> = rows.iter().map(|(_, fish)| fish.id).collect();
+    let existing = get_existing_fish_ids(&db_pool, &ids).await.map_err(e500)?;
+    for (row, fish) in &rows {
+        if existing.contains(&fish.id) {
+            errors.push(existing_id_error(*row));
+        }
+    }
+    errors.sort_by_key(|error| error.row);
+
+    let mut report = ImportReport {
+        dry_run: query.dry_run,
+        rows: count,
+        imported: 0,
+        errors,
+    };
+    if query.dry_run {
+        tracing::info!("Fish import has been validated.");
+        return Ok(HttpResponse::Ok().json(report));
+    }
+    if !report.errors.is_empty() {
+        tracing::warn!("Fish import has invalid rows.");
+        return Ok(HttpResponse::BadRequest().json(report));
+    }
+
+    // A fish inserted since the check above still makes the row invalid.
+    if let Some(error) = import_fish_db(&db_pool, admin.user_id, &rows)
+        .await
+        .map_err(e500)?
+    {
+        report.errors.push(error);
+        tracing::warn!("Fish import has invalid rows.");
+        return Ok(HttpResponse::BadRequest().json(report));
+    }
+    report.imported = rows.len();
+    tracing::info!("Fish have been imported.");
+    Ok(HttpResponse::Ok().json(report))
+}
+
+fn parse_fish_row(parser: &mut RowParser, lookups: &Lookups) -> Option<FishRow> {
+    let id = parser.parse("id").unwrap_or_else(Uuid::new_v4);
+    let fish_type_id = parser.required("fish_type_id");
+    if fish_type_id.is_some_and(|id| !lookups.fish_types.contains(&id)) {
+        parser.error(Some("fish_type_id"), "Unknown fish type.");
+    }
+    let lake = parser.required_text("lake");
+    if let Some(lake) = lake.as_ref().filter(|lake| !lookups.lakes.contains(*lake)) {
+        let message = format!("Unknown lake {:?}.", lake);
+        parser.error(Some("lake"), message);
+    }
+    let date_sampled = parser.parse_with("date_sampled", |text| {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+            .or_else(|_| text.parse())
+            .ok()
+    });
+    let sample_date = parser.parse("sample_date");
+    let sampling_site_id = parser.parse("sampling_site_id");
+    if sampling_site_id.is_some_and(|id| !lookups.sampling_sites.contains(&id)) {
+        parser.error(Some("sampling_site_id"), "Unknown sampling site.");
+    }
+    let data_source_id = parser.parse("data_source_id");
+    if data_source_id.is_some_and(|id| !lookups.data_sources.contains(&id)) {
+        parser.error(Some("data_source_id"), "Unknown data source.");
+    }
+
+    let level = |parser: &mut RowParser, column: &str| -> Option<Level> {
+        let is_contaminant = lookups.contaminants.contains(column);
+        let limit_column = format!("{}{}", column, DETECTION_LIMIT_SUFFIX);
+        let limit = parser.parse::<f64>(&limit_column);
+        if limit.is_some() && parser.text(column).is_none() {
+            let message = format!("The detection limit has no {} level.", column);
+            parser.error(Some(&limit_column), message);
+        }
+        let mut level = parser.parse_with(column, |text| Level::parse(text, is_contaminant))?;
+        if !level.value.is_finite() || level.value < 0.0 {
+            parser.error(Some(column), "Levels can't be negative.");
+        }
+        if let Some(limit) = limit {
+            if level
+                .detection_limit
+                .is_some_and(|cell_limit| cell_limit != limit)
+            {
+                let message = "The detection limit doesn't match the level's.";
+                parser.error(Some(&limit_column), message);
+            }
+            level.detection_limit = Some(limit);
+        }
+        if level
+            .detection_limit
+            .is_some_and(|limit| !limit.is_finite() || limit <= 0.0)
+        {
+            parser.error(Some(column), "Detection limits must be positive.");
+        }
+        Some(level)
+    };
+    let mut analyte_columns: Vec<String> = parser
+        .headers
+        .keys()
+        .filter(|column| {
+            !FISH_COLUMNS.contains(&column.as_str()) && !column.ends_with(DETECTION_LIMIT_SUFFIX)
+        })
+        .cloned()
+        .chain(LEGACY_ANALYTES.map(str::to_string))
+        .collect();
+    analyte_columns.sort();
+    let mut measurements = vec![];
+    for column in &analyte_columns {
+        if let Some(level) = level(parser, column) {
+            measurements.push((column.clone(), level));
+        }
+    }
+    // The legacy columns are REAL.
+    let legacy_level = |analyte: &str| -> Option<f32> {
+        measurements
+            .iter()
+            .find(|(name, _)| name == analyte)
+            .map(|(_, level)| level.value as f32)
+    };
+    let mercury = legacy_level("mercury");
+    let omega_3 = legacy_level("omega_3");
+    let omega_3_ratio = legacy_level("omega_3_ratio");
+    let pcb = legacy_level("pcb");
+    let protein = legacy_level("protein");
+
+    let size = |parser: &mut RowParser, column: &str| -> Option<f64> {
+        let value: f64 = parser.parse(column)?;
+        if !value.is_finite() || value <= 0.0 {
+            parser.error(Some(column), "Must be positive.");
+        }
+        Some(value)
+    };
+    let length_cm = size(parser, "length_cm");
+    let weight_g = size(parser, "weight_g");
+
+    Some(FishRow {
+        id,
+        fish_type_id: fish_type_id?,
+        lake: lake?,
+        date_sampled,
+        mercury,
+        omega_3,
+        omega_3_ratio,
+        pcb,
+        protein,
+        sample_date,
+        sampling_site_id,
+        data_source_id,
+        length_cm,
+        weight_g,
+        measurements,
+    })
+}
+
+#[tracing::instrument(name = "Querying the database for import lookups", skip(db_pool))]
+async fn get_lookups(db_pool: &PgPool) -> Result<Lookups, sqlx::Error> {
+    let fish_types = sqlx::query_scalar!("SELECT id FROM fish_type;")
+        .fetch_all(db_pool)
+        .await;
+    let lakes = sqlx::query_scalar!("SELECT name FROM lake;")
+        .fetch_all(db_pool)
+        .await;
+    let sampling_sites = sqlx::query_scalar!("SELECT id FROM sampling_site;")
+        .fetch_all(db_pool)
+        .await;
+    let data_sources = sqlx::query_scalar!("SELECT id FROM data_source;")
+        .fetch_all(db_pool)
+        .await;
+    let analytes = sqlx::query_scalar!("SELECT name FROM analyte;")
+        .fetch_all(db_pool)
+        .await;
+    let contaminants = sqlx::query_scalar!("SELECT name FROM analyte WHERE kind = 'contaminant';")
+        .fetch_all(db_pool)
+        .await;
+
+    let lookups = (|| {
+        Ok(Lookups {
+            fish_types: fish_types?.into_iter().collect(),
+            lakes: lakes?.into_iter().collect(),
+            sampling_sites: sampling_sites?.into_iter().collect(),
+            data_sources: data_sources?.into_iter().collect(),
+            analytes: analytes?.into_iter().collect(),
+            contaminants: contaminants?.into_iter().collect(),
+        })
+    })();
+    lookups.map_err(|e: sqlx::Error| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+#[tracing::instrument(name = "Querying the database for existing fish", skip(db_pool, ids))]
+async fn get_existing_fish_ids(
+    db_pool: &PgPool,
+    ids: &[Uuid],
+) -> Result<HashSet<Uuid>, sqlx::Error> {
+    let existing = sqlx::query_scalar!("SELECT id FROM fish WHERE id = ANY($1);", ids)
+        .fetch_all(db_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to execute the query: {:?}", e);
+            e
+        })?;
+
+    Ok(existing.into_iter().collect())
+}
+
+fn existing_id_error(row: u64) -> RowError {
+    RowError {
+        row,
+        column: Some("id".to_string()),
+        message: "A fish with this id already exists.".to_string(),
+    }
+}
+
+/// Nothing is imported when a row's id is taken, that row's error is returned
+/// instead.
+#[tracing::instrument(name = "Saving imported fish to the database", skip(db_pool, fish))]
+async fn import_fish_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    fish: &[(u64, FishRow)],
+) -> Result<Option<RowError>, sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    for (row_number, row) in fish {
+        if let Err(e) = insert_fish(&mut transaction, row).await {
+            if e.as_database_error()
+                .is_some_and(|e| e.is_unique_violation())
+            {
+                return Ok(Some(existing_id_error(*row_number)));
+            }
+            return Err(e);
+        }
+        let after = audit_snapshot(&mut transaction, AuditEntity::Fish, row.id).await?;
+        record_audit(
+            &mut transaction,
+            actor_id,
+            AuditEntity::Fish,
+            row.id,
+            None,
+            after,
+        )
+        .await?;
+    }
+
+    transaction.commit().await?;
+    Ok(None)
+}
+
+async fn insert_fish(
+    transaction: &mut Transaction<'_, Postgres>,
+    row: &FishRow,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO fish (
+            id,
+            fish_type_id,
+            lake,
+            date_sampled,
+            mercury,
+            omega_3,
+            omega_3_ratio,
+            pcb,
+            protein,
+            sample_date,
+            sampling_site_id,
+            data_source_id,
+            length_cm,
+            weight_g
+        )
+        VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14
+        );
+        "#,
+        row.id,
+        row.fish_type_id,
+        row.lake,
+        row.date_sampled,
+        row.mercury,
+        row.omega_3,
+        row.omega_3_ratio,
+        row.pcb,
+        row.protein,
+        row.sample_date,
+        row.sampling_site_id,
+        row.data_source_id,
+        row.length_cm,
+        row.weight_g
+    )
+    .execute(&mut **transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    if row.measurements.is_empty() {
+        return Ok(());
+    }
+    let mut analytes = vec![];
+    let mut values = vec![];
+    let mut detected = vec![];
+    let mut detection_limits = vec![];
+    for (analyte, level) in &row.measurements {
+        analytes.push(analyte.clone());
+        values.push(level.value);
+        detected.push(level.detected);
+        detection_limits.push(level.detection_limit);
+    }
+    // The legacy columns already have a measurement, added by the database.
+    sqlx::query!(
+        r#"
+        INSERT INTO measurement (fish_id, analyte, value, detected, detection_limit)
+        SELECT $1, analyte, value, detected, detection_limit
+        FROM UNNEST($2::TEXT[], $3::FLOAT8[], $4::BOOLEAN[], $5::FLOAT8[])
+            AS new (analyte, value, detected, detection_limit)
+        ON CONFLICT (fish_id, analyte) DO UPDATE
+        SET
+            value = EXCLUDED.value,
+            detected = EXCLUDED.detected,
+            detection_limit = EXCLUDED.detection_limit;
+        "#,
+        row.id,
+        &analytes,
+        &values,
+        &detected,
+        &detection_limits as &[Option<f64>]
+    )
+    .execute(&mut **transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
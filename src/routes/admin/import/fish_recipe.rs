@@ -0,0 +1,238 @@
+use super::{parse_csv, ImportQuery, ParsedRows, RowParser, Upsert, UpsertReport};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use crate::routes::admin::fish_type::insert_recipes_fish_type;
+use crate::utils::e500;
+use actix_web::{post, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Columns of `scripts/basic_data/fish_recipe.csv`, and the names that can be
+/// used instead of the ids.
+const FISH_RECIPE_COLUMNS: [&str; 4] = ["fishtype_id", "recipe_id", "fish_type", "recipe"];
+
+struct Named {
+    id: Uuid,
+    name: String,
+}
+
+/// The fish types and recipes that rows can refer to, by id or by name.
+struct Lookup {
+    ids: HashSet<Uuid>,
+    /// Names can be shared by several fish types.
+    names: HashMap<String, Vec<Uuid>>,
+}
+
+impl Lookup {
+    fn new(rows: Vec<Named>) -> Self {
+        let mut lookup = Self {
+            ids: HashSet::new(),
+            names: HashMap::new(),
+        };
+        for row in rows {
+            lookup.ids.insert(row.id);
+            lookup.names.entry(row.name).or_default().push(row.id);
+        }
+        lookup
+    }
+
+    /// The id in `id_column`, or the one of the name in `name_column`.
+    fn resolve(&self, parser: &mut RowParser, id_column: &str, name_column: &str) -> Option<Uuid> {
+        if parser.text(id_column).is_some() {
+            let id = parser.parse(id_column)?;
+            if !self.ids.contains(&id) {
+                parser.error(Some(id_column), "Unknown id.");
+                return None;
+            }
+            return Some(id);
+        }
+        let Some(name) = parser.text(name_column) else {
+            let message = format!("Either {} or {} is required.", id_column, name_column);
+            parser.error(Some(id_column), message);
+            return None;
+        };
+        match self.names.get(name).map(Vec::as_slice) {
+            Some([id]) => Some(*id),
+            Some(_) => {
+                parser.error(Some(name_column), "Several have this name.");
+                None
+            }
+            None => {
+                let message = format!("Unknown name {:?}.", name);
+                parser.error(Some(name_column), message);
+                None
+            }
+        }
+    }
+}
+
+/// Links fish types to recipes from a CSV with the columns of
+/// `scripts/basic_data/fish_recipe.csv`. Each fish type can be given by
+/// `fishtype_id` or `fish_type`, its name, and each recipe by `recipe_id` or
+/// `recipe`. Links that already exist are unchanged, none are removed.
+///
+/// Either every row is imported or none are, `dry_run=true` reports what
+/// would be done without saving anything.
+///
+/// # Example
+///
+/// `.../admin/import/fish_recipe`
+///
+///```text
+/// fish_type,recipe
+/// Bass,Barbequed Cajun Bass Recipe
+///```
+///
+///```json
+/// {
+///     "dry_run": false,
+///     "rows": 1,
+///     "created": 1,
+///     "updated": 0,
+///     "unchanged": 0,
+///     "errors": []
+/// }
+///```
+#[tracing::instrument(name = "Importing fish type recipes", skip(body, db_pool, admin))]
+#[post("")]
+pub async fn import_fish_recipes(
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let fish_types = Lookup::new(get_fish_types(&db_pool).await.map_err(e500)?);
+    let recipes = Lookup::new(get_recipes(&db_pool).await.map_err(e500)?);
+    let links = get_links(&db_pool).await.map_err(e500)?;
+
+    let mut seen = HashSet::new();
+    let ParsedRows {
+        rows,
+        count,
+        mut errors,
+    } = parse_csv(
+        &body,
+        &[],
+        |column| FISH_RECIPE_COLUMNS.contains(&column),
+        |parser| {
+            let fish_type_id = fish_types.resolve(parser, "fishtype_id", "fish_type");
+            let recipe_id = recipes.resolve(parser, "recipe_id", "recipe");
+            let link = (fish_type_id?, recipe_id?);
+            if !seen.insert(link) {
+                parser.error(None, "The link is repeated.");
+            }
+            Some(link)
+        },
+    );
+    errors.sort_by_key(|error| error.row);
+
+    let plan: Vec<Upsert<Uuid>> = rows
+        .into_iter()
+        .map(|(_, (fish_type_id, recipe_id))| {
+            if links.contains(&(fish_type_id, recipe_id)) {
+                Upsert::Unchanged
+            } else {
+                Upsert::Create(fish_type_id, recipe_id)
+            }
+        })
+        .collect();
+
+    let report = UpsertReport::new(query.dry_run, count, &plan, errors);
+    if query.dry_run {
+        tracing::info!("Fish type recipe import has been validated.");
+        return Ok(HttpResponse::Ok().json(report));
+    }
+    if !report.errors.is_empty() {
+        tracing::warn!("Fish type recipe import has invalid rows.");
+        return Ok(HttpResponse::BadRequest().json(report));
+    }
+
+    import_links_db(&db_pool, admin.user_id, &plan)
+        .await
+        .map_err(e500)?;
+    tracing::info!("Fish type recipes have been imported.");
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[tracing::instrument(name = "Querying the database for fish types", skip(db_pool))]
+async fn get_fish_types(db_pool: &PgPool) -> Result<Vec<Named>, sqlx::Error> {
+    sqlx::query_as!(Named, "SELECT id, name FROM fish_type;")
+        .fetch_all(db_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to execute the query: {:?}", e);
+            e
+        })
+}
+
+#[tracing::instrument(name = "Querying the database for recipes", skip(db_pool))]
+async fn get_recipes(db_pool: &PgPool) -> Result<Vec<Named>, sqlx::Error> {
+    sqlx::query_as!(Named, "SELECT id, name FROM recipe;")
+        .fetch_all(db_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to execute the query: {:?}", e);
+            e
+        })
+}
+
+#[tracing::instrument(name = "Querying the database for fish type recipes", skip(db_pool))]
+async fn get_links(db_pool: &PgPool) -> Result<HashSet<(Uuid, Uuid)>, sqlx::Error> {
+    let links = sqlx::query!(
+        r#"SELECT fishtype_id as "fishtype_id!", recipe_id as "recipe_id!" FROM fishtype_recipe;"#
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(links
+        .into_iter()
+        .map(|link| (link.fishtype_id, link.recipe_id))
+        .collect())
+}
+
+/// Links are audited as a change to their fish type, one entry per fish type.
+#[tracing::instrument(
+    name = "Saving imported fish type recipes to the database",
+    skip(db_pool, plan)
+)]
+async fn import_links_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    plan: &[Upsert<Uuid>],
+) -> Result<(), sqlx::Error> {
+    let mut by_fish_type: Vec<(Uuid, Vec<Uuid>)> = vec![];
+    for row in plan {
+        if let Upsert::Create(fish_type_id, recipe_id) = row {
+            match by_fish_type.iter_mut().find(|(id, _)| id == fish_type_id) {
+                Some((_, recipes)) => recipes.push(*recipe_id),
+                None => by_fish_type.push((*fish_type_id, vec![*recipe_id])),
+            }
+        }
+    }
+
+    let mut transaction = db_pool.begin().await?;
+    for (fish_type_id, recipes) in by_fish_type {
+        let before = audit_snapshot(&mut transaction, AuditEntity::FishType, fish_type_id).await?;
+        for recipe_id in recipes {
+            insert_recipes_fish_type(&mut transaction, fish_type_id, recipe_id).await?;
+        }
+        let after = audit_snapshot(&mut transaction, AuditEntity::FishType, fish_type_id).await?;
+        record_audit(
+            &mut transaction,
+            actor_id,
+            AuditEntity::FishType,
+            fish_type_id,
+            before,
+            after,
+        )
+        .await?;
+    }
+
+    transaction.commit().await
+}
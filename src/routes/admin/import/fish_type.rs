@@ -0,0 +1,200 @@
+use super::{parse_csv, ImportQuery, ParsedRows, RowError, Upsert, UpsertReport};
+use crate::authentication::SessionUser;
+use crate::routes::admin::fish_type::{
+    insert_fish_type, save_fish_type_update, NewFishType, UpdateFishType,
+};
+use crate::utils::e500;
+use actix_web::{post, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Columns of `scripts/basic_data/fish_types.csv`.
+const FISH_TYPE_COLUMNS: [&str; 8] = [
+    "id",
+    "name",
+    "anishinaabe_name",
+    "fish_image",
+    "s3_fish_image",
+    "s3_woodland_image",
+    "woodland_fish_image",
+    "about",
+];
+
+struct ExistingFishType {
+    id: Uuid,
+    name: String,
+    anishinaabe_name: Option<String>,
+    about: String,
+}
+
+/// Imports fish types from a CSV with the columns of
+/// `scripts/basic_data/fish_types.csv`. Rows are matched to the existing fish
+/// types by name: new names are created, with the row's `id` if it has one,
+/// and existing ones have their `anishinaabe_name` and `about` updated.
+/// `name`, `anishinaabe_name` and `about` are required.
+///
+/// `s3_fish_image` and `s3_woodland_image` are only used for new fish types,
+/// images are changed through `update_fish_type_image`. `fish_image` and
+/// `woodland_fish_image`, the images bundled with the app, are ignored.
+///
+/// Either every row is imported or none are, `dry_run=true` reports what
+/// would be done without saving anything.
+///
+/// # Example
+///
+/// `.../admin/import/fish_type`
+///
+///```json
+/// {
+///     "dry_run": false,
+///     "rows": 24,
+///     "created": 2,
+///     "updated": 1,
+///     "unchanged": 21,
+///     "errors": []
+/// }
+///```
+#[tracing::instrument(name = "Importing fish types", skip(body, db_pool, admin))]
+#[post("")]
+pub async fn import_fish_types(
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let existing = get_fish_types(&db_pool).await.map_err(e500)?;
+    let existing_ids: HashSet<Uuid> = existing.iter().map(|fish_type| fish_type.id).collect();
+    let mut by_name: HashMap<&str, Vec<&ExistingFishType>> = HashMap::new();
+    for fish_type in &existing {
+        by_name.entry(&fish_type.name).or_default().push(fish_type);
+    }
+
+    let mut seen_names = HashSet::new();
+    let mut seen_ids = HashSet::new();
+    let ParsedRows {
+        rows,
+        count,
+        mut errors,
+    } = parse_csv(
+        &body,
+        &["name", "anishinaabe_name", "about"],
+        |column| FISH_TYPE_COLUMNS.contains(&column),
+        |parser| {
+            let id: Option<Uuid> = parser.parse("id");
+            let name = parser.required_text("name");
+            let anishinaabe_name = parser.required_text("anishinaabe_name");
+            let about = parser.required_text("about");
+            let fish_image = parser.text("s3_fish_image").unwrap_or_default().to_string();
+            let woodland_fish_image = parser.text("s3_woodland_image").map(str::to_string);
+            if id.is_some_and(|id| !seen_ids.insert(id)) {
+                parser.error(Some("id"), "The id is used by an earlier row.");
+            }
+            if name
+                .as_ref()
+                .is_some_and(|name| !seen_names.insert(name.clone()))
+            {
+                parser.error(Some("name"), "The name is used by an earlier row.");
+            }
+
+            let fish_type = NewFishType {
+                name: name?,
+                anishinaabe_name: anishinaabe_name?,
+                recipe: None,
+                fish_image,
+                woodland_fish_image,
+                about: about?,
+            };
+            Some((id, fish_type))
+        },
+    );
+
+    let mut plan = vec![];
+    for (row, (id, fish_type)) in rows {
+        match by_name.get(fish_type.name.as_str()).map(Vec::as_slice) {
+            None if id.is_some_and(|id| existing_ids.contains(&id)) => errors.push(RowError {
+                row,
+                column: Some("id".to_string()),
+                message: "The id belongs to a fish type with another name.".to_string(),
+            }),
+            None => plan.push(Upsert::Create(id.unwrap_or_else(Uuid::new_v4), fish_type)),
+            Some([existing]) => {
+                if existing.anishinaabe_name.as_deref() == Some(fish_type.anishinaabe_name.as_str())
+                    && existing.about == fish_type.about
+                {
+                    plan.push(Upsert::Unchanged);
+                } else {
+                    plan.push(Upsert::Update(existing.id, fish_type));
+                }
+            }
+            Some(_) => errors.push(RowError {
+                row,
+                column: Some("name".to_string()),
+                message: "Several fish types have this name.".to_string(),
+            }),
+        }
+    }
+    errors.sort_by_key(|error| error.row);
+
+    let report = UpsertReport::new(query.dry_run, count, &plan, errors);
+    if query.dry_run {
+        tracing::info!("Fish type import has been validated.");
+        return Ok(HttpResponse::Ok().json(report));
+    }
+    if !report.errors.is_empty() {
+        tracing::warn!("Fish type import has invalid rows.");
+        return Ok(HttpResponse::BadRequest().json(report));
+    }
+
+    import_fish_types_db(&db_pool, admin.user_id, plan)
+        .await
+        .map_err(e500)?;
+    tracing::info!("Fish types have been imported.");
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[tracing::instrument(name = "Querying the database for fish types", skip(db_pool))]
+async fn get_fish_types(db_pool: &PgPool) -> Result<Vec<ExistingFishType>, sqlx::Error> {
+    sqlx::query_as!(
+        ExistingFishType,
+        "SELECT id, name, anishinaabe_name, about FROM fish_type;"
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+#[tracing::instrument(
+    name = "Saving imported fish types to the database",
+    skip(db_pool, plan)
+)]
+async fn import_fish_types_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    plan: Vec<Upsert<NewFishType>>,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    for row in plan {
+        match row {
+            Upsert::Create(id, fish_type) => {
+                insert_fish_type(&mut transaction, actor_id, id, &fish_type).await?;
+            }
+            Upsert::Update(id, fish_type) => {
+                let update = UpdateFishType {
+                    name: fish_type.name,
+                    anishinaabe_name: fish_type.anishinaabe_name,
+                    recipe: None,
+                    about: fish_type.about,
+                };
+                save_fish_type_update(&mut transaction, actor_id, id, &update).await?;
+            }
+            Upsert::Unchanged => {}
+        }
+    }
+
+    transaction.commit().await
+}
@@ -0,0 +1,272 @@
+mod fish;
+mod fish_recipe;
+mod fish_type;
+mod recipe;
+
+pub use fish::import_fish;
+pub use fish_recipe::import_fish_recipes;
+pub use fish_type::import_fish_types;
+pub use recipe::import_recipes;
+
+use csv::StringRecord;
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Largest CSV the import routes accept, in bytes.
+pub const MAX_IMPORT_SIZE: usize = 10 * 1024 * 1024;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct ImportQuery {
+    /// Validate the rows and report the errors without saving anything.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RowError {
+    /// The line of the CSV, the header being line 1.
+    pub row: u64,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub rows: usize,
+    pub imported: usize,
+    pub errors: Vec<RowError>,
+}
+
+/// The report of the imports that match rows to existing ones by name.
+/// On a dry run the counts are what the import would do.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct UpsertReport {
+    pub dry_run: bool,
+    pub rows: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub errors: Vec<RowError>,
+}
+
+/// What an upsert import does with a row.
+enum Upsert<T> {
+    Create(Uuid, T),
+    Update(Uuid, T),
+    Unchanged,
+}
+
+impl UpsertReport {
+    fn new<T>(dry_run: bool, rows: usize, plan: &[Upsert<T>], errors: Vec<RowError>) -> Self {
+        let count = |wanted: fn(&Upsert<T>) -> bool| plan.iter().filter(|row| wanted(row)).count();
+        Self {
+            dry_run,
+            rows,
+            created: count(|row| matches!(row, Upsert::Create(..))),
+            updated: count(|row| matches!(row, Upsert::Update(..))),
+            unchanged: count(|row| matches!(row, Upsert::Unchanged)),
+            errors,
+        }
+    }
+}
+
+/// The rows of a CSV that parsed without errors, with their line.
+struct ParsedRows<T> {
+    rows: Vec<(u64, T)>,
+    /// Every row, including the ones with errors.
+    count: usize,
+    errors: Vec<RowError>,
+}
+
+/// Reads a CSV, checking the header first. `required` columns must be in it
+/// and every column must be `known`. Rows for which `parse_row` records an
+/// error are left out.
+fn parse_csv<T>(
+    csv: &[u8],
+    required: &[&str],
+    known: impl Fn(&str) -> bool,
+    mut parse_row: impl FnMut(&mut RowParser) -> Option<T>,
+) -> ParsedRows<T> {
+    let mut parsed = ParsedRows {
+        rows: vec![],
+        count: 0,
+        errors: vec![],
+    };
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv);
+    let headers = match reader.headers() {
+        Ok(headers) => header_indexes(headers),
+        Err(e) => {
+            parsed.errors.push(csv_error(1, e));
+            return parsed;
+        }
+    };
+    for column in required {
+        if !headers.contains_key(*column) {
+            parsed.errors.push(RowError {
+                row: 1,
+                column: Some(column.to_string()),
+                message: "Missing column.".to_string(),
+            });
+        }
+    }
+    let mut unknown: Vec<&String> = headers.keys().filter(|column| !known(column)).collect();
+    unknown.sort();
+    for column in unknown {
+        parsed.errors.push(RowError {
+            row: 1,
+            column: Some(column.clone()),
+            message: "Unknown column.".to_string(),
+        });
+    }
+    if !parsed.errors.is_empty() {
+        return parsed;
+    }
+
+    for (i, record) in reader.records().enumerate() {
+        parsed.count += 1;
+        // Used when the record can't be read, assumes one line per record.
+        let fallback_row = i as u64 + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                parsed.errors.push(csv_error(fallback_row, e));
+                continue;
+            }
+        };
+        let row = record
+            .position()
+            .map(|position| position.line())
+            .unwrap_or(fallback_row);
+        let error_count = parsed.errors.len();
+        let mut parser = RowParser {
+            headers: &headers,
+            record: &record,
+            row,
+            errors: &mut parsed.errors,
+        };
+        let value = parse_row(&mut parser);
+        if let Some(value) = value.filter(|_| parsed.errors.len() == error_count) {
+            parsed.rows.push((row, value));
+        }
+    }
+
+    parsed
+}
+
+fn csv_error(row: u64, e: csv::Error) -> RowError {
+    let row = e.position().map(|position| position.line()).unwrap_or(row);
+    RowError {
+        row,
+        column: None,
+        message: format!("Unreadable row: {}", e),
+    }
+}
+
+/// Reads the fields of a CSV row by column name, recording what's wrong with
+/// them instead of stopping at the first error.
+struct RowParser<'a> {
+    headers: &'a HashMap<String, usize>,
+    record: &'a StringRecord,
+    row: u64,
+    errors: &'a mut Vec<RowError>,
+}
+
+impl RowParser<'_> {
+    fn error(&mut self, column: Option<&str>, message: impl Into<String>) {
+        self.errors.push(RowError {
+            row: self.row,
+            column: column.map(str::to_string),
+            message: message.into(),
+        });
+    }
+
+    /// The field, `None` if the column is missing or the field is empty.
+    fn text(&self, column: &str) -> Option<&str> {
+        self.headers
+            .get(column)
+            .and_then(|i| self.record.get(*i))
+            .filter(|field| !field.is_empty())
+    }
+
+    fn required_text(&mut self, column: &str) -> Option<String> {
+        let text = self.text(column).map(str::to_string);
+        if text.is_none() {
+            self.error(Some(column), "Required.");
+        }
+        text
+    }
+
+    fn parse<T: FromStr>(&mut self, column: &str) -> Option<T> {
+        self.parse_with(column, |text| text.parse().ok())
+    }
+
+    fn required<T: FromStr>(&mut self, column: &str) -> Option<T> {
+        if self.text(column).is_none() {
+            self.error(Some(column), "Required.");
+            return None;
+        }
+        self.parse(column)
+    }
+
+    fn parse_with<T>(&mut self, column: &str, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+        let text = self.text(column)?;
+        let value = parse(text);
+        if value.is_none() {
+            let message = format!("Invalid value {:?}.", text);
+            self.error(Some(column), message);
+        }
+        value
+    }
+}
+
+/// Parses a Postgres text array literal, the format `ingredients` and `steps`
+/// are dumped in, e.g. `{"2lbs bass fillet",Salt}`.
+fn parse_text_array(text: &str) -> Option<Vec<String>> {
+    let inner = text.strip_prefix('{')?.strip_suffix('}')?;
+    let mut items = vec![];
+    if inner.trim().is_empty() {
+        return Some(items);
+    }
+    let mut chars = inner.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut item = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => item.push(chars.next()?),
+                    c => item.push(c),
+                }
+            }
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                item.push(c);
+            }
+            item.truncate(item.trim_end().len());
+            if item.is_empty() || item.eq_ignore_ascii_case("null") || item.contains(['"', '{']) {
+                return None;
+            }
+        }
+        items.push(item);
+        match chars.next() {
+            None => return Some(items),
+            Some(',') => continue,
+            Some(_) => return None,
+        }
+    }
+}
+
+/// Maps each column name to its index, lowercased.
+fn header_indexes(headers: &StringRecord) -> HashMap<String, usize> {
+    headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| (header.to_lowercase(), i))
+        .collect()
+}
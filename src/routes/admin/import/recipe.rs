@@ -0,0 +1,180 @@
+use super::{parse_csv, parse_text_array, ImportQuery, ParsedRows, RowError, Upsert, UpsertReport};
+use crate::authentication::SessionUser;
+use crate::routes::admin::recipe::{insert_recipe, save_recipe_update, RecipeData};
+use crate::utils::e500;
+use actix_web::{post, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Columns of `scripts/basic_data/recipes.csv`.
+const RECIPE_COLUMNS: [&str; 5] = ["id", "name", "ingredients", "steps", "image_url"];
+
+struct ExistingRecipe {
+    id: Uuid,
+    name: String,
+    ingredients: Option<Vec<String>>,
+    steps: Option<Vec<String>>,
+}
+
+/// Imports recipes from a CSV with the columns of
+/// `scripts/basic_data/recipes.csv`. Rows are matched to the existing recipes
+/// by name: new names are created, with the row's `id` if it has one, and
+/// existing ones have their `ingredients` and `steps` updated. `ingredients`
+/// and `steps` are Postgres arrays, e.g. `{"1 tsp salt","1 tsp pepper"}`.
+///
+/// `image_url` is only used for new recipes, images are changed through
+/// `update_recipe_image`.
+///
+/// Either every row is imported or none are, `dry_run=true` reports what
+/// would be done without saving anything.
+///
+/// # Example
+///
+/// `.../admin/import/recipe?dry_run=true`
+///
+///```json
+/// {
+///     "dry_run": true,
+///     "rows": 2,
+///     "created": 1,
+///     "updated": 0,
+///     "unchanged": 0,
+///     "errors": [
+///         {
+///             "row": 3,
+///             "column": "steps",
+///             "message": "Invalid value \"Fry the fish\"."
+///         }
+///     ]
+/// }
+///```
+#[tracing::instrument(name = "Importing recipes", skip(body, db_pool, admin))]
+#[post("")]
+pub async fn import_recipes(
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let existing = get_recipes(&db_pool).await.map_err(e500)?;
+    let existing_ids: HashSet<Uuid> = existing.iter().map(|recipe| recipe.id).collect();
+    let by_name: HashMap<&str, &ExistingRecipe> = existing
+        .iter()
+        .map(|recipe| (recipe.name.as_str(), recipe))
+        .collect();
+
+    let mut seen_names = HashSet::new();
+    let mut seen_ids = HashSet::new();
+    let ParsedRows {
+        rows,
+        count,
+        mut errors,
+    } = parse_csv(
+        &body,
+        &["name", "ingredients", "steps"],
+        |column| RECIPE_COLUMNS.contains(&column),
+        |parser| {
+            let id: Option<Uuid> = parser.parse("id");
+            let name = parser.required_text("name");
+            let ingredients = parser.required_text("ingredients");
+            let ingredients =
+                ingredients.and_then(|_| parser.parse_with("ingredients", parse_text_array));
+            let steps = parser.required_text("steps");
+            let steps = steps.and_then(|_| parser.parse_with("steps", parse_text_array));
+            let image_url = parser.text("image_url").unwrap_or_default().to_string();
+            if id.is_some_and(|id| !seen_ids.insert(id)) {
+                parser.error(Some("id"), "The id is used by an earlier row.");
+            }
+            if name
+                .as_ref()
+                .is_some_and(|name| !seen_names.insert(name.clone()))
+            {
+                parser.error(Some("name"), "The name is used by an earlier row.");
+            }
+
+            let recipe = RecipeData {
+                name: name?,
+                ingredients: ingredients?,
+                steps: steps?,
+                image_url,
+            };
+            Some((id, recipe))
+        },
+    );
+
+    let mut plan = vec![];
+    for (row, (id, recipe)) in rows {
+        match by_name.get(recipe.name.as_str()) {
+            None if id.is_some_and(|id| existing_ids.contains(&id)) => errors.push(RowError {
+                row,
+                column: Some("id".to_string()),
+                message: "The id belongs to a recipe with another name.".to_string(),
+            }),
+            None => plan.push(Upsert::Create(id.unwrap_or_else(Uuid::new_v4), recipe)),
+            Some(existing) => {
+                if existing.ingredients.as_ref() == Some(&recipe.ingredients)
+                    && existing.steps.as_ref() == Some(&recipe.steps)
+                {
+                    plan.push(Upsert::Unchanged);
+                } else {
+                    plan.push(Upsert::Update(existing.id, recipe));
+                }
+            }
+        }
+    }
+    errors.sort_by_key(|error| error.row);
+
+    let report = UpsertReport::new(query.dry_run, count, &plan, errors);
+    if query.dry_run {
+        tracing::info!("Recipe import has been validated.");
+        return Ok(HttpResponse::Ok().json(report));
+    }
+    if !report.errors.is_empty() {
+        tracing::warn!("Recipe import has invalid rows.");
+        return Ok(HttpResponse::BadRequest().json(report));
+    }
+
+    import_recipes_db(&db_pool, admin.user_id, plan)
+        .await
+        .map_err(e500)?;
+    tracing::info!("Recipes have been imported.");
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[tracing::instrument(name = "Querying the database for recipes", skip(db_pool))]
+async fn get_recipes(db_pool: &PgPool) -> Result<Vec<ExistingRecipe>, sqlx::Error> {
+    sqlx::query_as!(
+        ExistingRecipe,
+        "SELECT id, name, ingredients, steps FROM recipe;"
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+#[tracing::instrument(name = "Saving imported recipes to the database", skip(db_pool, plan))]
+async fn import_recipes_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    plan: Vec<Upsert<RecipeData>>,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    for row in plan {
+        match row {
+            Upsert::Create(id, recipe) => {
+                insert_recipe(&mut transaction, actor_id, id, &recipe).await?;
+            }
+            Upsert::Update(id, recipe) => {
+                save_recipe_update(&mut transaction, actor_id, id, &recipe).await?;
+            }
+            Upsert::Unchanged => {}
+        }
+    }
+
+    transaction.commit().await
+}
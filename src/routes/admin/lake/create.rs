@@ -0,0 +1,104 @@
+use super::{is_duplicate_name, LakeData};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{post, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Adds a lake fish samples can be from.
+///
+/// # Example
+///
+/// `.../admin/lake/`
+///
+///```json
+/// {
+///     "name": "Erie",
+///     "anishinaabe_name": "Waabishkiigoo-gichigami",
+///     "region": "Great Lakes",
+///     "description": null,
+///     "latitude": 42.2,
+///     "longitude": -81.2,
+///     "image_url": null
+/// }
+///```
+#[tracing::instrument(name = "Creating a new lake.", skip(data, db_pool, admin))]
+#[post("/")]
+pub async fn create_lake(
+    data: web::Json<LakeData>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    if let Err(reason) = data.validate() {
+        return HttpResponse::BadRequest().body(reason);
+    }
+    let lake_id = Uuid::new_v4();
+    match new_lake_db(&db_pool, admin.user_id, lake_id, &data).await {
+        Ok(_) => {
+            tracing::info!("New lake has been added.");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) if is_duplicate_name(&e) => {
+            HttpResponse::BadRequest().body("A lake with that name already exists.")
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Saving new lake data to the database", skip(db_pool, data))]
+async fn new_lake_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    lake_id: Uuid,
+    data: &LakeData,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO lake (
+            id,
+            name,
+            anishinaabe_name,
+            region,
+            description,
+            latitude,
+            longitude,
+            image_url
+        )
+        VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8
+        );
+        "#,
+        lake_id,
+        data.name.trim(),
+        data.anishinaabe_name,
+        data.region,
+        data.description,
+        data.latitude,
+        data.longitude,
+        data.image_url
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::Lake, lake_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::Lake,
+        lake_id,
+        None,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
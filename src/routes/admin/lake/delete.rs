@@ -0,0 +1,71 @@
+use super::LakeUuid;
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{delete, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Deletes a lake. Lakes that still have fish samples or sampling sites can't
+/// be deleted, they have to be moved or deleted first. Logged meals keep their
+/// fish but lose the lake.
+#[tracing::instrument(name = "Deleting lake data", skip(uuid, db_pool, admin))]
+#[delete("/{uuid}")]
+pub async fn delete_lake(
+    uuid: web::Path<LakeUuid>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    match delete_lake_db(&db_pool, admin.user_id, uuid.uuid).await {
+        Ok(_) => {
+            tracing::info!("Lake has been deleted.");
+            HttpResponse::Ok().finish()
+        }
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
+            let dependents = match e.constraint() {
+                Some("sampling_site_lake_fkey") => "sampling sites",
+                _ => "fish samples",
+            };
+            HttpResponse::BadRequest().body(format!("The lake still has {}.", dependents))
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Deleting lake data from the database", skip(db_pool))]
+async fn delete_lake_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    lake_uuid: Uuid,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::Lake, lake_uuid).await?;
+    sqlx::query!(
+        r#"
+        DELETE FROM lake
+        WHERE id = $1;
+        "#,
+        lake_uuid
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::Lake,
+        lake_uuid,
+        before,
+        None,
+    )
+    .await?;
+
+    transaction.commit().await
+}
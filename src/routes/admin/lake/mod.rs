@@ -0,0 +1,52 @@
+mod create;
+mod delete;
+mod update;
+
+pub use create::create_lake;
+pub use delete::delete_lake;
+pub use update::update_lake;
+
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct LakeUuid {
+    uuid: Uuid,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct LakeData {
+    name: String,
+    anishinaabe_name: Option<String>,
+    region: Option<String>,
+    description: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    image_url: Option<String>,
+}
+
+impl LakeData {
+    /// A lake needs a name, and its centroid is either left out or a full,
+    /// in range pair of coordinates.
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.name.trim().is_empty() {
+            return Err("The lake needs a name.");
+        }
+        match (self.latitude, self.longitude) {
+            (None, None) => Ok(()),
+            (Some(latitude), Some(longitude)) => {
+                if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+                    Err("The centroid is out of range.")
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Err("Both latitude and longitude are needed for the centroid."),
+        }
+    }
+}
+
+/// Whether a query failed because another lake already has the name.
+fn is_duplicate_name(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .is_some_and(|e| e.is_unique_violation())
+}
@@ -0,0 +1,109 @@
+use super::{is_duplicate_name, LakeData, LakeUuid};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{put, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Replaces a lake's data. Renaming a lake also renames it on its fish samples
+/// and logged meals.
+///
+/// # Example
+///
+/// `.../admin/lake/1fe5c906-d09d-11ed-afa1-0242ac120002`
+///
+///```json
+/// {
+///     "name": "Superior",
+///     "anishinaabe_name": "Gichigami",
+///     "region": "Great Lakes",
+///     "description": "The largest of the Great Lakes.",
+///     "latitude": 47.7,
+///     "longitude": -87.5,
+///     "image_url": null
+/// }
+///```
+#[tracing::instrument(name = "Updating lake data", skip(uuid, data, db_pool, admin))]
+#[put("/{uuid}")]
+pub async fn update_lake(
+    uuid: web::Path<LakeUuid>,
+    data: web::Json<LakeData>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    if let Err(reason) = data.validate() {
+        return HttpResponse::BadRequest().body(reason);
+    }
+    match update_lake_db(&db_pool, admin.user_id, uuid.uuid, &data).await {
+        Ok(_) => {
+            tracing::info!("Lake has been updated.");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) if is_duplicate_name(&e) => {
+            HttpResponse::BadRequest().body("A lake with that name already exists.")
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            match e {
+                sqlx::Error::RowNotFound => HttpResponse::BadRequest().finish(),
+                _ => HttpResponse::InternalServerError().finish(),
+            }
+        }
+    }
+}
+
+#[tracing::instrument(name = "Saving lake data to the database", skip(db_pool, data))]
+async fn update_lake_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    lake_uuid: Uuid,
+    data: &LakeData,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::Lake, lake_uuid).await?;
+    if before.is_none() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    sqlx::query!(
+        r#"
+        UPDATE lake
+        SET
+            name = $1,
+            anishinaabe_name = $2,
+            region = $3,
+            description = $4,
+            latitude = $5,
+            longitude = $6,
+            image_url = $7
+        WHERE id = $8;
+        "#,
+        data.name.trim(),
+        data.anishinaabe_name,
+        data.region,
+        data.description,
+        data.latitude,
+        data.longitude,
+        data.image_url,
+        lake_uuid,
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::Lake, lake_uuid).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::Lake,
+        lake_uuid,
+        before,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
@@ -0,0 +1,3 @@
+mod read_all;
+
+pub use read_all::read_all_login_attempts;
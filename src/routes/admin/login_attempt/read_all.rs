@@ -0,0 +1,78 @@
+use actix_web::{get, web, HttpResponse};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct LoginAttemptQuery {
+    email: Option<String>,
+    ip_address: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(serde::Serialize)]
+pub struct LoginAttempt {
+    id: Uuid,
+    email: String,
+    ip_address: Option<String>,
+    outcome: String,
+    attempted_at: DateTime<Utc>,
+}
+
+/// Lists the most recent login attempts, optionally filtered by email or IP
+/// address. Returns at most `limit` attempts, 100 by default.
+///
+/// # Example
+///
+/// `.../admin/login_attempt/?email=someone@example.com&limit=20`
+#[tracing::instrument(name = "Retreving login attempts.", skip(query, db_pool))]
+#[get("/")]
+pub async fn read_all_login_attempts(
+    query: web::Query<LoginAttemptQuery>,
+    db_pool: web::Data<PgPool>,
+) -> HttpResponse {
+    match get_login_attempts(&db_pool, query.into_inner()).await {
+        Ok(data) => {
+            tracing::info!("Login attempts have been queried from the db.");
+            HttpResponse::Ok().json(data)
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Querying the database", skip(db_pool))]
+async fn get_login_attempts(
+    db_pool: &PgPool,
+    query: LoginAttemptQuery,
+) -> Result<Vec<LoginAttempt>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        LoginAttempt,
+        r#"
+        SELECT
+            id,
+            email,
+            ip_address,
+            outcome,
+            attempted_at
+        FROM login_attempt
+        WHERE ($1::TEXT IS NULL OR email = $1)
+            AND ($2::TEXT IS NULL OR ip_address = $2)
+        ORDER BY attempted_at DESC
+        LIMIT $3;
+        "#,
+        query.email,
+        query.ip_address,
+        query.limit.unwrap_or(100).clamp(1, 1000)
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
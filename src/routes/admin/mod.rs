@@ -1,11 +1,34 @@
+mod analyte;
 mod analytics;
+mod api_key;
+mod audit;
+mod data_source;
 mod fish;
 mod fish_type;
+mod import;
+mod lake;
+mod login_attempt;
 mod recipe;
+mod sampling_site;
+mod user;
 
+pub use analyte::{create_analyte, update_analyte};
 pub use analytics::get_analytics;
+pub use api_key::{create_api_key, read_all_api_keys, revoke_api_key};
+pub use audit::read_audit_log;
+pub use data_source::{create_data_source, delete_data_source, update_data_source};
 pub use fish::{delete_fish, new_fish, update_fish};
 pub use fish_type::{
-    create_fish_type, read_all_fish_types, read_fish_type, update_fish_type, update_fish_type_image,
+    create_fish_type, read_all_fish_types, read_fish_type, update_fish_type,
+    update_fish_type_image, update_length_classes,
 };
+pub use import::{
+    import_fish, import_fish_recipes, import_fish_types, import_recipes, MAX_IMPORT_SIZE,
+};
+pub use lake::{create_lake, delete_lake, update_lake};
+pub use login_attempt::read_all_login_attempts;
 pub use recipe::{delete_recipe, new_recipe, update_recipe, update_recipe_image};
+pub use sampling_site::{create_sampling_site, delete_sampling_site, update_sampling_site};
+pub use user::{
+    disable_user, enable_user, read_all_users, read_user, reset_user_password, update_user_roles,
+};
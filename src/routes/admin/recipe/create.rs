@@ -1,14 +1,20 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
 use crate::routes::admin::recipe::RecipeData;
 use actix_web::{post, web, HttpResponse};
 use anyhow::Result;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
-#[tracing::instrument(name = "Saving new recipe data", skip(data, db_pool))]
+#[tracing::instrument(name = "Saving new recipe data", skip(data, db_pool, admin))]
 #[post("/")]
-pub async fn new_recipe(data: web::Json<RecipeData>, db_pool: web::Data<PgPool>) -> HttpResponse {
+pub async fn new_recipe(
+    data: web::Json<RecipeData>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
     let recipe_id = Uuid::new_v4();
-    match save_new_recipe(&db_pool, data, recipe_id).await {
+    match save_new_recipe(&db_pool, admin.user_id, data, recipe_id).await {
         Ok(_) => {
             tracing::info!("New recipe has been saved to the database.");
             HttpResponse::Ok().finish()
@@ -23,8 +29,21 @@ pub async fn new_recipe(data: web::Json<RecipeData>, db_pool: web::Data<PgPool>)
 #[tracing::instrument(name = "Saving recipe data to the database", skip(db_pool, data))]
 async fn save_new_recipe(
     db_pool: &PgPool,
+    actor_id: Uuid,
     data: web::Json<RecipeData>,
     recipe_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    insert_recipe(&mut transaction, actor_id, recipe_id, &data).await?;
+    transaction.commit().await
+}
+
+/// Inserts a recipe and audits it.
+pub async fn insert_recipe(
+    transaction: &mut Transaction<'_, Postgres>,
+    actor_id: Uuid,
+    recipe_id: Uuid,
+    data: &RecipeData,
 ) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
@@ -37,12 +56,21 @@ async fn save_new_recipe(
         &data.steps,
         &data.image_url,
     )
-    .execute(db_pool)
+    .execute(&mut **transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
 
-    Ok(())
+    let after = audit_snapshot(transaction, AuditEntity::Recipe, recipe_id).await?;
+    record_audit(
+        transaction,
+        actor_id,
+        AuditEntity::Recipe,
+        recipe_id,
+        None,
+        after,
+    )
+    .await
 }
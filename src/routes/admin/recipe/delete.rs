@@ -1,3 +1,5 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
 use actix_web::{delete, web, HttpResponse};
 use anyhow::Result;
 use sqlx::PgPool;
@@ -8,13 +10,14 @@ pub struct RecipeUuid {
     uuid: Uuid,
 }
 
-#[tracing::instrument(name = "Deleting recipe data", skip(uuid, db_pool))]
+#[tracing::instrument(name = "Deleting recipe data", skip(uuid, db_pool, admin))]
 #[delete("/{uuid}")]
 pub async fn delete_recipe(
     uuid: web::Path<RecipeUuid>,
     db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
 ) -> HttpResponse {
-    match delete_recipe_db(&db_pool, uuid.uuid).await {
+    match delete_recipe_db(&db_pool, admin.user_id, uuid.uuid).await {
         Ok(_) => {
             tracing::info!("Recipe has been deleted.");
             HttpResponse::Ok().finish()
@@ -27,7 +30,13 @@ pub async fn delete_recipe(
 }
 
 #[tracing::instrument(name = "Deleting recipe data from the database", skip(db_pool))]
-async fn delete_recipe_db(db_pool: &PgPool, recipe_uuid: Uuid) -> Result<(), sqlx::Error> {
+async fn delete_recipe_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    recipe_uuid: Uuid,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::Recipe, recipe_uuid).await?;
     tracing::info!("Deleting recipe from fish type join table.");
     sqlx::query!(
         r#"
@@ -36,7 +45,7 @@ async fn delete_recipe_db(db_pool: &PgPool, recipe_uuid: Uuid) -> Result<(), sql
         "#,
         recipe_uuid
     )
-    .execute(db_pool)
+    .execute(&mut *transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
@@ -51,12 +60,22 @@ async fn delete_recipe_db(db_pool: &PgPool, recipe_uuid: Uuid) -> Result<(), sql
         "#,
         recipe_uuid
     )
-    .execute(db_pool)
+    .execute(&mut *transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
 
-    Ok(())
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::Recipe,
+        recipe_uuid,
+        before,
+        None,
+    )
+    .await?;
+
+    transaction.commit().await
 }
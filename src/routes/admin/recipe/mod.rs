@@ -3,7 +3,7 @@ mod delete;
 mod update;
 mod update_image;
 
-pub use create::new_recipe;
+pub use create::{insert_recipe, new_recipe};
 pub use delete::delete_recipe;
-pub use update::{update_recipe, RecipeData};
+pub use update::{save_recipe_update, update_recipe, RecipeData};
 pub use update_image::update_recipe_image;
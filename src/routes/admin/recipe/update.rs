@@ -1,6 +1,8 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
 use actix_web::{put, web, HttpResponse};
 use anyhow::Result;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
 #[derive(serde::Deserialize)]
@@ -16,14 +18,15 @@ pub struct RecipeData {
     pub(crate) image_url: String,
 }
 
-#[tracing::instrument(name = "Updating recipe data", skip(uuid, data, db_pool))]
+#[tracing::instrument(name = "Updating recipe data", skip(uuid, data, db_pool, admin))]
 #[put("/{uuid}")]
 pub async fn update_recipe(
     uuid: web::Path<RecipeUuid>,
     data: web::Json<RecipeData>,
     db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
 ) -> HttpResponse {
-    match update_recipe_db(&db_pool, uuid.uuid, data).await {
+    match update_recipe_db(&db_pool, admin.user_id, uuid.uuid, data).await {
         Ok(_) => {
             tracing::info!("Recipe has been updated.");
             HttpResponse::Ok().finish()
@@ -38,9 +41,23 @@ pub async fn update_recipe(
 #[tracing::instrument(name = "Saving recipe data to the database", skip(db_pool, data))]
 async fn update_recipe_db(
     db_pool: &PgPool,
+    actor_id: Uuid,
     recipe_uuid: Uuid,
     data: web::Json<RecipeData>,
 ) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    save_recipe_update(&mut transaction, actor_id, recipe_uuid, &data).await?;
+    transaction.commit().await
+}
+
+/// Updates a recipe, but not its image, and audits it.
+pub async fn save_recipe_update(
+    transaction: &mut Transaction<'_, Postgres>,
+    actor_id: Uuid,
+    recipe_uuid: Uuid,
+    data: &RecipeData,
+) -> Result<(), sqlx::Error> {
+    let before = audit_snapshot(transaction, AuditEntity::Recipe, recipe_uuid).await?;
     sqlx::query!(
         r#"
         UPDATE recipe
@@ -55,12 +72,21 @@ async fn update_recipe_db(
         &data.steps,
         recipe_uuid
     )
-    .execute(db_pool)
+    .execute(&mut **transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
 
-    Ok(())
+    let after = audit_snapshot(transaction, AuditEntity::Recipe, recipe_uuid).await?;
+    record_audit(
+        transaction,
+        actor_id,
+        AuditEntity::Recipe,
+        recipe_uuid,
+        before,
+        after,
+    )
+    .await
 }
@@ -1,3 +1,5 @@
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
 use actix_web::{put, web, HttpResponse};
 use anyhow::Result;
 use sqlx::PgPool;
@@ -13,14 +15,15 @@ pub struct RecipeImageData {
     image_url: String,
 }
 
-#[tracing::instrument(name = "Updating a recipe image.", skip(data, db_pool))]
+#[tracing::instrument(name = "Updating a recipe image.", skip(data, db_pool, admin))]
 #[put("/{uuid}/image")]
 pub async fn update_recipe_image(
     recipe_id: web::Path<RecipeUuid>,
     data: web::Json<RecipeImageData>,
     db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
 ) -> HttpResponse {
-    match update_recipe_image_db(&db_pool, recipe_id.uuid, data).await {
+    match update_recipe_image_db(&db_pool, admin.user_id, recipe_id.uuid, data).await {
         Ok(_) => {
             tracing::info!("Recipe image has been updated.");
             HttpResponse::Ok().finish()
@@ -35,9 +38,12 @@ pub async fn update_recipe_image(
 #[tracing::instrument(name = "Saving new recipe image url to db.", skip(db_pool, data))]
 async fn update_recipe_image_db(
     db_pool: &PgPool,
+    actor_id: Uuid,
     recipe_id: Uuid,
     data: web::Json<RecipeImageData>,
 ) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::Recipe, recipe_id).await?;
     sqlx::query!(
         r#"
         UPDATE recipe
@@ -47,12 +53,23 @@ async fn update_recipe_image_db(
         data.image_url,
         recipe_id,
     )
-    .execute(db_pool)
+    .execute(&mut *transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
 
-    Ok(())
+    let after = audit_snapshot(&mut transaction, AuditEntity::Recipe, recipe_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::Recipe,
+        recipe_id,
+        before,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
 }
@@ -0,0 +1,100 @@
+use super::SamplingSiteData;
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{post, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Adds a sampling site. Returns the new site's id so samples can be linked to
+/// it.
+///
+/// # Example
+///
+/// `.../admin/sampling_site/`
+///
+///```json
+/// {
+///     "name": "Chequamegon Bay",
+///     "water_body": "Chequamegon Bay",
+///     "lake": "Superior",
+///     "agency": "GLIFWC",
+///     "latitude": 46.65,
+///     "longitude": -90.85
+/// }
+///```
+#[tracing::instrument(name = "Creating a new sampling site.", skip(data, db_pool, admin))]
+#[post("/")]
+pub async fn create_sampling_site(
+    data: web::Json<SamplingSiteData>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> Result<HttpResponse, actix_web::Error> {
+    data.validate(&db_pool).await?;
+    let site_id = Uuid::new_v4();
+    match new_sampling_site_db(&db_pool, admin.user_id, site_id, &data).await {
+        Ok(_) => {
+            tracing::info!("New sampling site has been added.");
+            Ok(HttpResponse::Ok().json(serde_json::json!({ "id": site_id })))
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[tracing::instrument(
+    name = "Saving new sampling site data to the database",
+    skip(db_pool, data)
+)]
+async fn new_sampling_site_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    site_id: Uuid,
+    data: &SamplingSiteData,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO sampling_site (
+            id,
+            name,
+            water_body,
+            lake,
+            agency,
+            latitude,
+            longitude
+        )
+        VALUES (
+            $1, $2, $3, $4, $5, $6, $7
+        );
+        "#,
+        site_id,
+        data.name.trim(),
+        data.water_body.trim(),
+        data.lake,
+        data.agency,
+        data.latitude,
+        data.longitude
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::SamplingSite, site_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::SamplingSite,
+        site_id,
+        None,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
@@ -0,0 +1,63 @@
+use super::SamplingSiteUuid;
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{delete, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Deletes a sampling site. Its samples are kept, but no longer linked to a
+/// site.
+#[tracing::instrument(name = "Deleting sampling site data", skip(uuid, db_pool, admin))]
+#[delete("/{uuid}")]
+pub async fn delete_sampling_site(
+    uuid: web::Path<SamplingSiteUuid>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    match delete_sampling_site_db(&db_pool, admin.user_id, uuid.uuid).await {
+        Ok(_) => {
+            tracing::info!("Sampling site has been deleted.");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Deleting sampling site data from the database", skip(db_pool))]
+async fn delete_sampling_site_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    site_uuid: Uuid,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::SamplingSite, site_uuid).await?;
+    sqlx::query!(
+        r#"
+        DELETE FROM sampling_site
+        WHERE id = $1;
+        "#,
+        site_uuid
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::SamplingSite,
+        site_uuid,
+        before,
+        None,
+    )
+    .await?;
+
+    transaction.commit().await
+}
@@ -0,0 +1,46 @@
+mod create;
+mod delete;
+mod update;
+
+pub use create::create_sampling_site;
+pub use delete::delete_sampling_site;
+pub use update::update_sampling_site;
+
+use crate::routes::lake_exists;
+use crate::utils::{e400, e500};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct SamplingSiteUuid {
+    uuid: Uuid,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct SamplingSiteData {
+    name: String,
+    water_body: String,
+    lake: Option<String>,
+    agency: Option<String>,
+    latitude: f64,
+    longitude: f64,
+}
+
+impl SamplingSiteData {
+    /// The site needs a name and water body, coordinates on the globe, and a
+    /// lake from the lake list if it names one.
+    async fn validate(&self, db_pool: &PgPool) -> Result<(), actix_web::Error> {
+        if self.name.trim().is_empty() || self.water_body.trim().is_empty() {
+            return Err(e400("The site needs a name and a water body."));
+        }
+        if !(-90.0..=90.0).contains(&self.latitude) || !(-180.0..=180.0).contains(&self.longitude) {
+            return Err(e400("The coordinates are out of range."));
+        }
+        if let Some(lake) = &self.lake {
+            if !lake_exists(db_pool, lake).await.map_err(e500)? {
+                return Err(e400("Unknown lake."));
+            }
+        }
+        Ok(())
+    }
+}
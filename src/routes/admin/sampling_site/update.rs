@@ -0,0 +1,87 @@
+use super::{SamplingSiteData, SamplingSiteUuid};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use actix_web::{put, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[tracing::instrument(name = "Updating sampling site data", skip(uuid, data, db_pool, admin))]
+#[put("/{uuid}")]
+pub async fn update_sampling_site(
+    uuid: web::Path<SamplingSiteUuid>,
+    data: web::Json<SamplingSiteData>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> Result<HttpResponse, actix_web::Error> {
+    data.validate(&db_pool).await?;
+    match update_sampling_site_db(&db_pool, admin.user_id, uuid.uuid, &data).await {
+        Ok(_) => {
+            tracing::info!("Sampling site has been updated.");
+            Ok(HttpResponse::Ok().finish())
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            match e {
+                sqlx::Error::RowNotFound => Ok(HttpResponse::BadRequest().finish()),
+                _ => Ok(HttpResponse::InternalServerError().finish()),
+            }
+        }
+    }
+}
+
+#[tracing::instrument(
+    name = "Saving sampling site data to the database",
+    skip(db_pool, data)
+)]
+async fn update_sampling_site_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    site_uuid: Uuid,
+    data: &SamplingSiteData,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::SamplingSite, site_uuid).await?;
+    if before.is_none() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    sqlx::query!(
+        r#"
+        UPDATE sampling_site
+        SET
+            name = $1,
+            water_body = $2,
+            lake = $3,
+            agency = $4,
+            latitude = $5,
+            longitude = $6
+        WHERE id = $7;
+        "#,
+        data.name.trim(),
+        data.water_body.trim(),
+        data.lake,
+        data.agency,
+        data.latitude,
+        data.longitude,
+        site_uuid,
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::SamplingSite, site_uuid).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::SamplingSite,
+        site_uuid,
+        before,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
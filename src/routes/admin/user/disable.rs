@@ -0,0 +1,107 @@
+use super::read::UserUuid;
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::{revoke_user_sessions, SessionUser};
+use crate::utils::e500;
+use actix_web::{post, web, HttpResponse};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Disables a user's account. They're logged out everywhere and can't log in
+/// again until the account is enabled.
+#[tracing::instrument(name = "Disabling a user.", skip(db_pool, admin))]
+#[post("/{uuid}/disable")]
+pub async fn disable_user(
+    user_id: web::Path<UserUuid>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> Result<HttpResponse, actix_web::Error> {
+    if user_id.uuid == admin.user_id {
+        return Ok(HttpResponse::BadRequest().body("You can't disable your own account."));
+    }
+
+    match set_disabled_db(&db_pool, admin.user_id, user_id.uuid, true).await {
+        Ok(true) => {
+            revoke_user_sessions(&db_pool, user_id.uuid)
+                .await
+                .map_err(e500)?;
+            tracing::info!("User has been disabled.");
+            Ok(HttpResponse::Ok().finish())
+        }
+        Ok(false) => Ok(HttpResponse::BadRequest().finish()),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Enables a disabled account again.
+#[tracing::instrument(name = "Enabling a user.", skip(db_pool, admin))]
+#[post("/{uuid}/enable")]
+pub async fn enable_user(
+    user_id: web::Path<UserUuid>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> HttpResponse {
+    match set_disabled_db(&db_pool, admin.user_id, user_id.uuid, false).await {
+        Ok(true) => {
+            tracing::info!("User has been enabled.");
+            HttpResponse::Ok().finish()
+        }
+        Ok(false) => HttpResponse::BadRequest().finish(),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Returns whether the user exists. Disabling an already disabled account
+/// keeps the original `disabled_at`.
+#[tracing::instrument(
+    name = "Updating a user's disabled state in the database",
+    skip(db_pool)
+)]
+async fn set_disabled_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    user_id: Uuid,
+    disabled: bool,
+) -> Result<bool, sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::User, user_id).await?;
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET disabled_at = CASE
+            WHEN $2 THEN COALESCE(disabled_at, $3)
+            ELSE NULL
+        END
+        WHERE id = $1;
+        "#,
+        user_id,
+        disabled,
+        Utc::now()
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::User, user_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::User,
+        user_id,
+        before,
+        after,
+    )
+    .await?;
+    transaction.commit().await?;
+
+    Ok(result.rows_affected() == 1)
+}
@@ -0,0 +1,11 @@
+mod disable;
+mod read;
+mod read_all;
+mod reset_password;
+mod update_roles;
+
+pub use disable::{disable_user, enable_user};
+pub use read::read_user;
+pub use read_all::read_all_users;
+pub use reset_password::reset_user_password;
+pub use update_roles::update_user_roles;
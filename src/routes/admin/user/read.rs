@@ -0,0 +1,75 @@
+use actix_web::{get, web, HttpResponse};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct UserUuid {
+    pub(super) uuid: Uuid,
+}
+
+#[derive(serde::Serialize)]
+pub struct ManagedUser {
+    pub(super) id: Uuid,
+    pub(super) email: String,
+    pub(super) first_name: Option<String>,
+    pub(super) last_name: Option<String>,
+    pub(super) roles: Vec<String>,
+    pub(super) created_at: Option<DateTime<Utc>>,
+    pub(super) latest_login: Option<DateTime<Utc>>,
+    pub(super) email_verified_at: Option<DateTime<Utc>>,
+    pub(super) disabled_at: Option<DateTime<Utc>>,
+}
+
+#[tracing::instrument(name = "Retreving a user.", skip(db_pool))]
+#[get("/{uuid}")]
+pub async fn read_user(db_pool: web::Data<PgPool>, user_id: web::Path<UserUuid>) -> HttpResponse {
+    match get_user_db(&db_pool, user_id.uuid).await {
+        Ok(data) => {
+            tracing::info!("User has been queried from the db.");
+            HttpResponse::Ok().json(data)
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            match e {
+                sqlx::Error::RowNotFound => HttpResponse::BadRequest().finish(),
+                _ => HttpResponse::InternalServerError().finish(),
+            }
+        }
+    }
+}
+
+#[tracing::instrument(name = "Querying the database for a user", skip(db_pool))]
+pub(super) async fn get_user_db(
+    db_pool: &PgPool,
+    user_id: Uuid,
+) -> Result<ManagedUser, sqlx::Error> {
+    let data = sqlx::query_as!(
+        ManagedUser,
+        r#"
+        SELECT
+            id,
+            email,
+            first_name,
+            last_name,
+            ARRAY(
+                SELECT role FROM user_roles WHERE user_id = users.id ORDER BY role
+            ) as "roles!",
+            created_at,
+            latest_login,
+            email_verified_at,
+            disabled_at
+        FROM users
+        WHERE id = $1;
+        "#,
+        user_id
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
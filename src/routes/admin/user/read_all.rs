@@ -0,0 +1,160 @@
+use super::read::ManagedUser;
+use actix_web::{get, web, HttpResponse};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct UserQuery {
+    /// Part of the email, case insensitive.
+    email: Option<String>,
+    role: Option<String>,
+    disabled: Option<bool>,
+    created_after: Option<DateTime<Utc>>,
+    created_before: Option<DateTime<Utc>>,
+    logged_in_after: Option<DateTime<Utc>>,
+    logged_in_before: Option<DateTime<Utc>>,
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+#[derive(serde::Serialize)]
+pub struct UserPage {
+    users: Vec<ManagedUser>,
+    page: i64,
+    per_page: i64,
+    total: i64,
+}
+
+/// Lists users, newest first, with optional filters. Pages start at 1 and hold
+/// `per_page` users, 50 by default.
+///
+/// # Example
+///
+/// `.../admin/users/?email=gmail&logged_in_after=2024-08-01T00:00:00Z&page=2`
+///
+///```json
+/// {
+///     "users": [
+///         {
+///             "id": "1fe5c906-d09d-11ed-afa1-0242ac120002",
+///             "email": "someone@gmail.com",
+///             "first_name": "Some",
+///             "last_name": "One",
+///             "roles": ["content_editor"],
+///             "created_at": "2024-05-01T12:00:00Z",
+///             "latest_login": "2024-08-20T12:00:00Z",
+///             "email_verified_at": "2024-05-01T12:05:00Z",
+///             "disabled_at": null
+///         }
+///     ],
+///     "page": 2,
+///     "per_page": 50,
+///     "total": 51
+/// }
+///```
+#[tracing::instrument(name = "Retreving users.", skip(query, db_pool))]
+#[get("/")]
+pub async fn read_all_users(
+    query: web::Query<UserQuery>,
+    db_pool: web::Data<PgPool>,
+) -> HttpResponse {
+    match get_users(&db_pool, query.into_inner()).await {
+        Ok(data) => {
+            tracing::info!("Users have been queried from the db.");
+            HttpResponse::Ok().json(data)
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Querying the database for users", skip(db_pool))]
+async fn get_users(db_pool: &PgPool, query: UserQuery) -> Result<UserPage, sqlx::Error> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(50).clamp(1, 500);
+
+    let total = sqlx::query!(
+        r#"
+        SELECT count(*) as "count!"
+        FROM users
+        WHERE ($1::TEXT IS NULL OR email ILIKE '%' || $1 || '%')
+            AND ($2::TEXT IS NULL OR EXISTS(
+                SELECT 1 FROM user_roles WHERE user_id = users.id AND role = $2
+            ))
+            AND ($3::BOOL IS NULL OR (disabled_at IS NOT NULL) = $3)
+            AND ($4::TIMESTAMPTZ IS NULL OR created_at >= $4)
+            AND ($5::TIMESTAMPTZ IS NULL OR created_at < $5)
+            AND ($6::TIMESTAMPTZ IS NULL OR latest_login >= $6)
+            AND ($7::TIMESTAMPTZ IS NULL OR latest_login < $7);
+        "#,
+        query.email,
+        query.role,
+        query.disabled,
+        query.created_after,
+        query.created_before,
+        query.logged_in_after,
+        query.logged_in_before
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?
+    .count;
+
+    let users = sqlx::query_as!(
+        ManagedUser,
+        r#"
+        SELECT
+            id,
+            email,
+            first_name,
+            last_name,
+            ARRAY(
+                SELECT role FROM user_roles WHERE user_id = users.id ORDER BY role
+            ) as "roles!",
+            created_at,
+            latest_login,
+            email_verified_at,
+            disabled_at
+        FROM users
+        WHERE ($1::TEXT IS NULL OR email ILIKE '%' || $1 || '%')
+            AND ($2::TEXT IS NULL OR EXISTS(
+                SELECT 1 FROM user_roles WHERE user_id = users.id AND role = $2
+            ))
+            AND ($3::BOOL IS NULL OR (disabled_at IS NOT NULL) = $3)
+            AND ($4::TIMESTAMPTZ IS NULL OR created_at >= $4)
+            AND ($5::TIMESTAMPTZ IS NULL OR created_at < $5)
+            AND ($6::TIMESTAMPTZ IS NULL OR latest_login >= $6)
+            AND ($7::TIMESTAMPTZ IS NULL OR latest_login < $7)
+        ORDER BY created_at DESC NULLS LAST, id
+        LIMIT $8
+        OFFSET $9;
+        "#,
+        query.email,
+        query.role,
+        query.disabled,
+        query.created_after,
+        query.created_before,
+        query.logged_in_after,
+        query.logged_in_before,
+        per_page,
+        (page - 1) * per_page
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(UserPage {
+        users,
+        page,
+        per_page,
+        total,
+    })
+}
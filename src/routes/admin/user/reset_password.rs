@@ -0,0 +1,54 @@
+use super::read::{get_user_db, UserUuid};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use crate::email_client::EmailClient;
+use crate::routes::send_password_reset_email;
+use crate::utils::e500;
+use actix_web::{post, web, HttpResponse};
+use sqlx::PgPool;
+
+/// Emails the user a password reset code, the same one they'd get by asking
+/// for it themselves. The reset is recorded in the audit log as an update of
+/// the user.
+#[tracing::instrument(
+    name = "Resetting a user's password.",
+    skip(db_pool, email_client, admin)
+)]
+#[post("/{uuid}/password_reset")]
+pub async fn reset_user_password(
+    user_id: web::Path<UserUuid>,
+    db_pool: web::Data<PgPool>,
+    email_client: web::Data<dyn EmailClient>,
+    admin: web::ReqData<SessionUser>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = match get_user_db(&db_pool, user_id.uuid).await {
+        Ok(user) => user,
+        Err(sqlx::Error::RowNotFound) => return Ok(HttpResponse::BadRequest().finish()),
+        Err(e) => return Err(e500(e)),
+    };
+
+    let mut transaction = db_pool.begin().await.map_err(e500)?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::User, user.id)
+        .await
+        .map_err(e500)?;
+    send_password_reset_email(&mut transaction, email_client.as_ref(), user.id, user.email)
+        .await
+        .map_err(e500)?;
+    let after = audit_snapshot(&mut transaction, AuditEntity::User, user.id)
+        .await
+        .map_err(e500)?;
+    record_audit(
+        &mut transaction,
+        admin.user_id,
+        AuditEntity::User,
+        user.id,
+        before,
+        after,
+    )
+    .await
+    .map_err(e500)?;
+    transaction.commit().await.map_err(e500)?;
+    tracing::info!("Password reset email has been sent.");
+
+    Ok(HttpResponse::Ok().finish())
+}
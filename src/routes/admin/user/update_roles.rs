@@ -0,0 +1,140 @@
+use super::read::{get_user_db, UserUuid};
+use crate::audit::{audit_snapshot, record_audit, AuditEntity};
+use crate::authentication::SessionUser;
+use crate::utils::e500;
+use actix_web::{put, web, HttpResponse};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct UserRoles {
+    roles: Vec<String>,
+}
+
+/// Replaces a user's roles. An empty list removes the user's access to the
+/// admin routes. Admins can't change their own roles, so they can't lock
+/// themselves out by mistake.
+///
+/// # Example
+///
+/// `.../admin/users/1fe5c906-d09d-11ed-afa1-0242ac120002/roles`
+///
+///```json
+/// {
+///     "roles": ["data_steward", "content_editor"]
+/// }
+///```
+#[tracing::instrument(name = "Updating a user's roles.", skip(data, db_pool, admin))]
+#[put("/{uuid}/roles")]
+pub async fn update_user_roles(
+    user_id: web::Path<UserUuid>,
+    data: web::Json<UserRoles>,
+    db_pool: web::Data<PgPool>,
+    admin: web::ReqData<SessionUser>,
+) -> Result<HttpResponse, actix_web::Error> {
+    if user_id.uuid == admin.user_id {
+        return Ok(HttpResponse::BadRequest().body("You can't change your own roles."));
+    }
+    let mut roles = data.into_inner().roles;
+    roles.sort();
+    roles.dedup();
+    if !roles_exist(&db_pool, &roles).await.map_err(e500)? {
+        return Ok(HttpResponse::BadRequest().body("Unknown role."));
+    }
+
+    match set_roles_db(&db_pool, admin.user_id, user_id.uuid, &roles).await {
+        Ok(_) => {
+            tracing::info!("User roles have been updated.");
+            let user = get_user_db(&db_pool, user_id.uuid).await.map_err(e500)?;
+            Ok(HttpResponse::Ok().json(user))
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            match e {
+                sqlx::Error::RowNotFound => Ok(HttpResponse::BadRequest().finish()),
+                _ => Ok(HttpResponse::InternalServerError().finish()),
+            }
+        }
+    }
+}
+
+#[tracing::instrument(name = "Checking that roles exist", skip(db_pool))]
+async fn roles_exist(db_pool: &PgPool, roles: &[String]) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT count(*) as "count!"
+        FROM roles
+        WHERE name = ANY($1);
+        "#,
+        roles
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.count as usize == roles.len())
+}
+
+#[tracing::instrument(name = "Saving a user's roles to the database", skip(db_pool))]
+async fn set_roles_db(
+    db_pool: &PgPool,
+    actor_id: Uuid,
+    user_id: Uuid,
+    roles: &[String],
+) -> Result<(), sqlx::Error> {
+    let mut transaction = db_pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+        SELECT id FROM users WHERE id = $1 FOR UPDATE;
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    let before = audit_snapshot(&mut transaction, AuditEntity::User, user_id).await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM user_roles WHERE user_id = $1;
+        "#,
+        user_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO user_roles (user_id, role)
+        SELECT $1, role FROM UNNEST($2::TEXT[]) AS role;
+        "#,
+        user_id,
+        roles
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    let after = audit_snapshot(&mut transaction, AuditEntity::User, user_id).await?;
+    record_audit(
+        &mut transaction,
+        actor_id,
+        AuditEntity::User,
+        user_id,
+        before,
+        after,
+    )
+    .await?;
+
+    transaction.commit().await
+}
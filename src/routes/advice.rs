@@ -0,0 +1,265 @@
+use crate::advisory::{advise, ContaminantLevels, ReferenceDoses, UserProfile};
+use crate::authentication::OptionalSessionUser;
+use crate::routes::{length_class_exists, Substitution};
+use crate::utils::{e400, e500};
+use actix_web::{get, web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct FishUuid {
+    uuid: Uuid,
+}
+
+#[derive(serde::Deserialize)]
+pub struct FishQuery {
+    fishtype_id: Uuid,
+    length_class: Option<String>,
+}
+
+/// Returns how many meals of a fish sample the user can safely eat, based on
+/// its mercury and PCB levels, the user's profile and the reference doses in
+/// the analyte catalog. If no session is
+/// included, or the user hasn't filled in their profile, a 180 lb adult eating
+/// 8 oz portions is assumed. When the mercury or PCB level isn't known the
+/// `category`, the meals and the `limiting_contaminant` are null.
+///
+/// # Example
+///
+/// `/fish/1fe5c906-d09d-11ed-afa1-0242ac120002/advice`
+///
+///```json
+/// {
+///     "fish_id": "1fe5c906-d09d-11ed-afa1-0242ac120002",
+///     "name": "Herring",
+///     "category": "eight_per_month",
+///     "meals_per_month": 8.0,
+///     "meals_per_week": 1.85,
+///     "limiting_contaminant": "pcb",
+///     "reference_doses": { "mercury": 0.3, "pcb": 0.02 },
+///     "mercury": {
+///         "concentration": 0.032,
+///         "reference_dose": 0.3,
+///         "allowable_intake": 745.4,
+///         "intake_per_meal": 7.26,
+///         "meals_per_month": 102.7,
+///         "category": "sixteen_per_month"
+///     },
+///     "pcb": {
+///         // ...
+///     },
+///     "profile": {
+///         "weight_kg": 81.6,
+///         "portion_g": 226.8,
+///         "sensitive": false,
+///         "is_default": true
+///     }
+/// }
+///```
+#[tracing::instrument(name = "Calculating advice for a fish", skip(uuid, db_pool))]
+#[get("/fish/{uuid}/advice")]
+pub async fn fish_advice(
+    uuid: web::Path<FishUuid>,
+    db_pool: web::Data<PgPool>,
+    user: OptionalSessionUser,
+) -> Result<HttpResponse, actix_web::Error> {
+    let profile = get_profile(&db_pool, user.user_id()).await.map_err(e500)?;
+    let reference_doses = get_reference_doses(&db_pool, &profile)
+        .await
+        .map_err(e500)?;
+    match get_fish_levels(&db_pool, uuid.uuid).await {
+        Ok(levels) => {
+            tracing::info!("Fish levels have been queried from the db.");
+            Ok(HttpResponse::Ok().json(advise(&profile, reference_doses, levels)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            match e {
+                sqlx::Error::RowNotFound => Ok(HttpResponse::BadRequest().finish()),
+                _ => Ok(HttpResponse::InternalServerError().finish()),
+            }
+        }
+    }
+}
+
+/// Same as `/fish/{uuid}/advice`, but uses the averages of every sample of a
+/// fish type, or just those in `length_class`. A fish type without samples
+/// gets no advice.
+///
+/// # Example
+///
+/// `.../fish_avg/advice?fishtype_id=1fe5c906-d09d-11ed-afa1-0242ac120022&length_class=under_38cm`
+#[tracing::instrument(name = "Calculating advice for a fish type", skip(query, db_pool))]
+#[get("/fish_avg/advice")]
+pub async fn fish_avg_advice(
+    query: web::Query<FishQuery>,
+    db_pool: web::Data<PgPool>,
+    user: OptionalSessionUser,
+) -> Result<HttpResponse, actix_web::Error> {
+    let profile = get_profile(&db_pool, user.user_id()).await.map_err(e500)?;
+    let reference_doses = get_reference_doses(&db_pool, &profile)
+        .await
+        .map_err(e500)?;
+    if let Some(length_class) = &query.length_class {
+        if !length_class_exists(&db_pool, length_class, Some(query.fishtype_id))
+            .await
+            .map_err(e500)?
+        {
+            return Err(e400("Unknown length class."));
+        }
+    }
+    match get_fish_avg_levels(&db_pool, query.fishtype_id, query.length_class.as_deref()).await {
+        Ok(levels) => {
+            tracing::info!("Avg fish levels have been queried from the db.");
+            Ok(HttpResponse::Ok().json(advise(&profile, reference_doses, levels)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            match e {
+                sqlx::Error::RowNotFound => Ok(HttpResponse::BadRequest().finish()),
+                _ => Ok(HttpResponse::InternalServerError().finish()),
+            }
+        }
+    }
+}
+
+/// Gets the user's profile, falling back to the defaults if there's no user.
+#[tracing::instrument(name = "Querying the database for a profile", skip(db_pool))]
+pub async fn get_profile(
+    db_pool: &PgPool,
+    user_id: Option<Uuid>,
+) -> Result<UserProfile, sqlx::Error> {
+    let Some(user_id) = user_id else {
+        return Ok(UserProfile::default());
+    };
+
+    let profile = sqlx::query_as!(
+        UserProfile,
+        r#"
+        SELECT
+            weight,
+            age,
+            plan_to_get_pregnant,
+            portion_size
+        FROM users
+        WHERE id = $1;
+        "#,
+        user_id
+    )
+    .fetch_optional(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?
+    .unwrap_or_default();
+
+    Ok(profile)
+}
+
+/// The reference doses of mercury and PCBs in the analyte catalog that apply to
+/// the profile.
+#[tracing::instrument(name = "Querying the database for reference doses", skip(db_pool))]
+pub async fn get_reference_doses(
+    db_pool: &PgPool,
+    profile: &UserProfile,
+) -> Result<ReferenceDoses, anyhow::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            MAX(dose.value) FILTER (WHERE analyte.name = 'mercury') as mercury,
+            MAX(dose.value) FILTER (WHERE analyte.name = 'pcb') as pcb
+        FROM analyte
+        CROSS JOIN LATERAL (
+            SELECT CASE
+                WHEN $1 THEN analyte.reference_dose
+                ELSE COALESCE(analyte.general_reference_dose, analyte.reference_dose)
+            END AS value
+        ) AS dose;
+        "#,
+        profile.is_sensitive()
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(ReferenceDoses {
+        mercury: row
+            .mercury
+            .context("Mercury has no reference dose in the catalog.")?,
+        pcb: row
+            .pcb
+            .context("PCBs have no reference dose in the catalog.")?,
+    })
+}
+
+#[tracing::instrument(name = "Querying the database for fish levels", skip(db_pool))]
+async fn get_fish_levels(
+    db_pool: &PgPool,
+    fish_uuid: Uuid,
+) -> Result<ContaminantLevels, sqlx::Error> {
+    let data = sqlx::query_as!(
+        ContaminantLevels,
+        r#"
+        SELECT
+            fish.id as fish_id,
+            fish_type.name,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'mercury') as mercury,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'pcb') as pcb
+        FROM fish
+        JOIN fish_type ON fish.fish_type_id=fish_type.id
+        LEFT JOIN substituted_measurement($2) AS measurement ON measurement.fish_id = fish.id
+        WHERE fish.id = $1
+        GROUP BY fish.id, fish_type.name;
+        "#,
+        fish_uuid,
+        Substitution::default().factor()
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
+
+#[tracing::instrument(name = "Querying the database for avg fish levels", skip(db_pool))]
+async fn get_fish_avg_levels(
+    db_pool: &PgPool,
+    fishtype_id: Uuid,
+    length_class: Option<&str>,
+) -> Result<ContaminantLevels, sqlx::Error> {
+    let data = sqlx::query_as!(
+        ContaminantLevels,
+        r#"
+        SELECT
+            fish_type.id as fish_id,
+            fish_type.name,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'mercury') as mercury,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'pcb') as pcb
+        FROM fish_type
+        LEFT JOIN fish ON fish.fish_type_id = fish_type.id
+            AND ($2::TEXT IS NULL OR in_length_class(fish.fish_type_id, fish.length_cm, $2))
+        LEFT JOIN substituted_measurement($3) AS measurement ON measurement.fish_id = fish.id
+        WHERE fish_type.id = $1
+        GROUP BY fish_type.id;
+        "#,
+        fishtype_id,
+        length_class,
+        Substitution::default().factor()
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
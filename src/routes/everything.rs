@@ -1,4 +1,4 @@
-use crate::routes::Recipe;
+use crate::routes::{get_analyte_data, Analyte, DataSource, Recipe};
 use actix_web::{get, web, HttpResponse};
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -18,17 +18,25 @@ struct Fish {
     pub omega_3_ratio: Option<f32>,
     pub pcb: Option<f32>,
     pub protein: Option<f32>,
+    /// Every analyte measured in the sample, by name.
+    pub measurements: serde_json::Value,
     pub recipes: Option<Vec<Uuid>>,
     pub date_sampled: Option<chrono::NaiveDateTime>,
+    pub length_cm: Option<f64>,
+    pub weight_g: Option<f64>,
+    pub data_source_id: Option<Uuid>,
 }
 
 #[derive(serde::Serialize)]
 pub struct Everything {
     fishs: Vec<Fish>,
     recipes: Vec<Recipe>,
+    analytes: Vec<Analyte>,
+    data_sources: Vec<DataSource>,
 }
 
-/// Returns a JSON with all fish and all recipes.
+/// Returns a JSON with all fish, all recipes, the analytes fish are measured
+/// for and the sources fish cite.
 ///
 /// # Example
 ///
@@ -41,9 +49,15 @@ pub struct Everything {
 ///       "id": "1fe5c906-d09d-11ed-afa1-0242ac120022",
 ///       "name": "Herring",
 ///       ...
+///       "measurements": {
+///          "mercury": 0.032,
+///          "selenium": 0.41,
+///          ...
+///       },
 ///       "recipes": [
 ///          "1fe5c906-d09d-11ed-afa1-0242ac120022", "1fe5c906-d09d-11ed-afa1-0242ac120022"
-///       ]
+///       ],
+///       "data_source_id": "1fe5c906-d09d-11ed-afa1-0242ac120022"
 ///      },
 ///       ...
 ///     ],
@@ -54,7 +68,20 @@ pub struct Everything {
 ///       ...
 ///      },
 ///     ],
-///     ...
+///     "analytes": [
+///      {
+///       "name": "selenium",
+///       "unit": "ppm",
+///       ...
+///      },
+///     ],
+///     "data_sources": [
+///      {
+///       "id": "1fe5c906-d09d-11ed-afa1-0242ac120022",
+///       "agency": "GLIFWC",
+///       ...
+///      },
+///     ]
 /// }
 ///```
 ///
@@ -76,8 +103,15 @@ pub async fn everything(db_pool: web::Data<PgPool>) -> HttpResponse {
 async fn get_everything(db_pool: &PgPool) -> Result<Everything, sqlx::Error> {
     let fishs = get_fish_data(db_pool).await?;
     let recipes = get_recipe_data(db_pool).await?;
+    let analytes = get_analyte_data(db_pool).await?;
+    let data_sources = get_data_source_data(db_pool).await?;
 
-    Ok(Everything { fishs, recipes })
+    Ok(Everything {
+        fishs,
+        recipes,
+        analytes,
+        data_sources,
+    })
 }
 
 #[tracing::instrument(name = "Querying the database", skip(db_pool))]
@@ -100,6 +134,17 @@ async fn get_fish_data(db_pool: &PgPool) -> Result<Vec<Fish>, sqlx::Error> {
             fish.omega_3_ratio,
             fish.omega_3,
             fish.date_sampled,
+            fish.length_cm,
+            fish.weight_g,
+            fish.data_source_id,
+            COALESCE(
+                (
+                    SELECT jsonb_object_agg(analyte, value)
+                    FROM measurement
+                    WHERE measurement.fish_id = fish.id
+                ),
+                '{}'
+            ) as "measurements!",
             array(
                 SELECT recipe_id
                 FROM fishtype_recipe
@@ -142,3 +187,29 @@ async fn get_recipe_data(db_pool: &PgPool) -> Result<Vec<Recipe>, sqlx::Error> {
 
     Ok(data)
 }
+
+#[tracing::instrument(name = "Querying the database for data sources", skip(db_pool))]
+async fn get_data_source_data(db_pool: &PgPool) -> Result<Vec<DataSource>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        DataSource,
+        r#"
+        SELECT
+            id,
+            agency,
+            study,
+            url,
+            lab_method,
+            collection_year,
+            license
+        FROM data_source
+        "#,
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
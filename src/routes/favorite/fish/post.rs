@@ -1,5 +1,7 @@
-use crate::utils::get_user_id;
-use actix_web::{post, web, HttpRequest, HttpResponse};
+use crate::authentication::{is_email_verified, SessionUser};
+use crate::configuration::UnverifiedUserPolicy;
+use crate::utils::e500;
+use actix_web::{post, web, HttpResponse};
 use anyhow::Result;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -9,15 +11,22 @@ pub struct FishUuid {
     uuid: Uuid,
 }
 
-#[tracing::instrument(name = "Favoriting a fish", skip(uuid, db_pool))]
+#[tracing::instrument(name = "Favoriting a fish", skip(uuid, db_pool, unverified_users))]
 #[post("/fish/{uuid}")]
 pub async fn favorite_fish(
     uuid: web::Path<FishUuid>,
     db_pool: web::Data<PgPool>,
-    req: HttpRequest,
+    user: SessionUser,
+    unverified_users: web::Data<UnverifiedUserPolicy>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_id(req)?;
-    match favorite_fish_db(&db_pool, user_id, uuid.uuid).await {
+    if !unverified_users.can_favorite
+        && !is_email_verified(&db_pool, user.user_id)
+            .await
+            .map_err(e500)?
+    {
+        return Ok(HttpResponse::Forbidden().finish());
+    }
+    match favorite_fish_db(&db_pool, user.user_id, uuid.uuid).await {
         Ok(_) => {
             tracing::info!("Fish has been favorited.");
             Ok(HttpResponse::Ok().finish())
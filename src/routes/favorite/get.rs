@@ -1,6 +1,6 @@
+use crate::authentication::SessionUser;
 use crate::routes::{FishType, Recipe};
-use crate::utils::get_user_id;
-use actix_web::{get, web, HttpRequest, HttpResponse};
+use actix_web::{get, web, HttpResponse};
 use anyhow::Result;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -49,10 +49,9 @@ pub struct Favorites {
 #[get("/")]
 pub async fn favorites(
     db_pool: web::Data<PgPool>,
-    req: HttpRequest,
+    user: SessionUser,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_id(req)?;
-    match favorites_db(&db_pool, user_id).await {
+    match favorites_db(&db_pool, user.user_id).await {
         Ok(data) => {
             tracing::info!("Favorites have been found.");
             Ok(HttpResponse::Ok().json(data))
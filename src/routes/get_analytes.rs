@@ -0,0 +1,90 @@
+use crate::routes::Analyte;
+use actix_web::{get, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+
+/// Retrives every contaminant and nutrient samples can be measured for.
+///
+/// # Example
+///
+/// `/analytes`
+///
+///```json
+/// [
+///   {
+///     "id": uuid,
+///     "name": "mercury",
+///     "display_name": "Mercury",
+///     "kind": "contaminant",
+///     "unit": "ppm",
+///     "reference_dose": 0.1,
+///     "general_reference_dose": 0.3,
+///     "detection_limit": null
+///   },
+///   ...
+/// ]
+///```
+///
+#[tracing::instrument(name = "Retreving all analytes", skip(db_pool))]
+#[get("/analytes")]
+pub async fn analytes(db_pool: web::Data<PgPool>) -> HttpResponse {
+    match get_analyte_data(&db_pool).await {
+        Ok(data) => {
+            tracing::info!("Analyte data has been queried from the db.");
+            HttpResponse::Ok().json(data)
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Querying the database for analytes", skip(db_pool))]
+pub async fn get_analyte_data(db_pool: &PgPool) -> Result<Vec<Analyte>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        Analyte,
+        r#"
+        SELECT
+            id,
+            name,
+            display_name,
+            kind,
+            unit,
+            reference_dose,
+            general_reference_dose,
+            detection_limit
+        FROM analyte
+        ORDER BY kind, name;
+        "#
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
+
+/// Whether every one of the given analyte names is in the catalog.
+#[tracing::instrument(name = "Checking that analytes exist", skip(db_pool))]
+pub async fn analytes_exist(db_pool: &PgPool, names: &[String]) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT count(*) as "count!"
+        FROM analyte
+        WHERE name = ANY($1);
+        "#,
+        names
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.count as usize == names.len())
+}
@@ -0,0 +1,350 @@
+use crate::advisory::benefit_risk_ratio;
+use crate::configuration::ScoringSettings;
+use crate::routes::{get_analyte_data, lake_exists, Substitution};
+use crate::utils::{e400, e500};
+use actix_web::{get, web, HttpResponse};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Most fish types that can be compared at once.
+const MAX_COMPARED: usize = 10;
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+pub struct AttrRanks {
+    pub protein: Option<i64>,
+    pub pcb: Option<i64>,
+    pub mercury: Option<i64>,
+    pub omega_3: Option<i64>,
+    pub omega_3_ratio: Option<i64>,
+    pub benefit_risk_ratio: Option<i64>,
+}
+
+/// The average level of an analyte, and how it ranks among the compared fish.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ComparedMeasurement {
+    pub analyte: String,
+    pub value: Option<f64>,
+    pub rank: Option<i64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ComparedFish {
+    pub fish_type_id: Uuid,
+    pub name: String,
+    pub anishinaabe_name: Option<String>,
+    pub samples: i64,
+    pub protein: Option<f64>,
+    pub pcb: Option<f64>,
+    pub mercury: Option<f64>,
+    pub omega_3: Option<f64>,
+    pub omega_3_ratio: Option<f64>,
+    /// Omega-3 per unit of mercury, higher is better. Mercury below the
+    /// scoring floor counts as the floor, like in the rankings.
+    pub benefit_risk_ratio: Option<f64>,
+    pub ranks: AttrRanks,
+    /// The analytes asked for with `attr`, in that order.
+    pub measurements: Vec<ComparedMeasurement>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Comparison {
+    pub lake: Option<String>,
+    pub fish: Vec<ComparedFish>,
+}
+
+struct AvgRow {
+    fish_type_id: Uuid,
+    name: String,
+    anishinaabe_name: Option<String>,
+    samples: i64,
+    protein: Option<f64>,
+    pcb: Option<f64>,
+    mercury: Option<f64>,
+    omega_3: Option<f64>,
+    omega_3_ratio: Option<f64>,
+}
+
+struct MeasurementAvgRow {
+    fish_type_id: Uuid,
+    analyte: String,
+    value: f64,
+}
+
+/// Compares the average levels of several fish types, optionally in a single
+/// lake. Fish are returned in the order they were asked for. Each attribute
+/// is ranked among the compared fish, 1 being the best: the lowest for
+/// contaminants, the highest for everything else. Ties share a rank.
+///
+/// `measurements` has the analytes given with `attr`, which can be repeated,
+/// or every analyte in the catalog.
+///
+/// # Example
+///
+/// `.../compare?fish_type=1fe5c906-d09d-11ed-afa1-0242ac120022&fish_type=1fe5c906-d09d-11ed-afa1-0242ac120023&lake=Huron&attr=selenium`
+///
+///```json
+/// {
+///     "lake": "Huron",
+///     "fish": [
+///         {
+///             "fish_type_id": "1fe5c906-d09d-11ed-afa1-0242ac120022",
+///             "name": "Herring",
+///             "anishinaabe_name": "Okewis",
+///             "samples": 12,
+///             "protein": 21.1,
+///             "pcb": 0.0002,
+///             "mercury": 0.032,
+///             "omega_3": 0.212,
+///             "omega_3_ratio": 8.12,
+///             "benefit_risk_ratio": 6.63,
+///             "ranks": {
+///                 "protein": 2,
+///                 "pcb": 1,
+///                 "mercury": 1,
+///                 "omega_3": 1,
+///                 "omega_3_ratio": 1,
+///                 "benefit_risk_ratio": 1
+///             },
+///             "measurements": [
+///                 { "analyte": "selenium", "value": 0.38, "rank": 2 }
+///             ]
+///         },
+///         // ...
+///     ]
+/// }
+///```
+#[tracing::instrument(name = "Comparing fish types", skip(query, db_pool, scoring))]
+#[get("/compare")]
+pub async fn compare(
+    query: web::Query<Vec<(String, String)>>,
+    db_pool: web::Data<PgPool>,
+    scoring: web::Data<ScoringSettings>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let mut fish_type_ids: Vec<Uuid> = vec![];
+    let mut lake = None;
+    let mut attrs: Vec<String> = vec![];
+    for (key, value) in query.into_inner() {
+        match key.as_str() {
+            "attr" if !attrs.contains(&value) => attrs.push(value),
+            "fish_type" => {
+                let id = value.parse().map_err(|_| e400("Invalid fish type."))?;
+                if !fish_type_ids.contains(&id) {
+                    fish_type_ids.push(id);
+                }
+            }
+            "lake" => lake = Some(value),
+            _ => {}
+        }
+    }
+    if fish_type_ids.is_empty() || fish_type_ids.len() > MAX_COMPARED {
+        return Err(e400("Between 1 and 10 fish types can be compared."));
+    }
+    if let Some(lake) = &lake {
+        if !lake_exists(&db_pool, lake).await.map_err(e500)? {
+            return Err(e400("Invalid lake."));
+        }
+    }
+
+    let analytes = get_analyte_data(&db_pool).await.map_err(e500)?;
+    if attrs.is_empty() {
+        attrs = analytes
+            .iter()
+            .map(|analyte| analyte.name.clone())
+            .collect();
+    }
+    let mut lower_is_better = vec![];
+    for attr in &attrs {
+        let analyte = analytes
+            .iter()
+            .find(|analyte| &analyte.name == attr)
+            .ok_or_else(|| e400("Invalid attr."))?;
+        lower_is_better.push(analyte.kind == "contaminant");
+    }
+
+    let mut rows = get_averages_db(&db_pool, &fish_type_ids, lake.as_deref())
+        .await
+        .map_err(e500)?;
+    if rows.len() != fish_type_ids.len() {
+        return Err(e400("Unknown fish type."));
+    }
+    rows.sort_by_key(|row| fish_type_ids.iter().position(|id| *id == row.fish_type_id));
+
+    let ratios: Vec<Option<f64>> = rows
+        .iter()
+        .map(|row| benefit_risk_ratio(&scoring, row.omega_3, row.mercury))
+        .collect();
+    let protein = rank(rows.iter().map(|row| row.protein), false);
+    let pcb = rank(rows.iter().map(|row| row.pcb), true);
+    let mercury = rank(rows.iter().map(|row| row.mercury), true);
+    let omega_3 = rank(rows.iter().map(|row| row.omega_3), false);
+    let omega_3_ratio = rank(rows.iter().map(|row| row.omega_3_ratio), false);
+    let benefit_risk = rank(ratios.iter().copied(), false);
+
+    let averages = get_measurement_averages_db(&db_pool, &fish_type_ids, lake.as_deref(), &attrs)
+        .await
+        .map_err(e500)?;
+    let average = |fish_type_id: Uuid, attr: &str| {
+        averages
+            .iter()
+            .find(|avg| avg.fish_type_id == fish_type_id && avg.analyte == attr)
+            .map(|avg| avg.value)
+    };
+    // Per attr, the value and rank of every fish.
+    let values: Vec<Vec<Option<f64>>> = attrs
+        .iter()
+        .map(|attr| {
+            rows.iter()
+                .map(|row| average(row.fish_type_id, attr))
+                .collect()
+        })
+        .collect();
+    let measurement_ranks: Vec<Vec<Option<i64>>> = values
+        .iter()
+        .zip(lower_is_better)
+        .map(|(values, lower_is_better)| rank(values.iter().copied(), lower_is_better))
+        .collect();
+
+    let fish = rows
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| ComparedFish {
+            fish_type_id: row.fish_type_id,
+            name: row.name,
+            anishinaabe_name: row.anishinaabe_name,
+            samples: row.samples,
+            protein: row.protein,
+            pcb: row.pcb,
+            mercury: row.mercury,
+            omega_3: row.omega_3,
+            omega_3_ratio: row.omega_3_ratio,
+            benefit_risk_ratio: ratios[i],
+            ranks: AttrRanks {
+                protein: protein[i],
+                pcb: pcb[i],
+                mercury: mercury[i],
+                omega_3: omega_3[i],
+                omega_3_ratio: omega_3_ratio[i],
+                benefit_risk_ratio: benefit_risk[i],
+            },
+            measurements: attrs
+                .iter()
+                .enumerate()
+                .map(|(j, attr)| ComparedMeasurement {
+                    analyte: attr.clone(),
+                    value: values[j][i],
+                    rank: measurement_ranks[j][i],
+                })
+                .collect(),
+        })
+        .collect();
+
+    tracing::info!("Fish comparison has been queried from the db.");
+    Ok(HttpResponse::Ok().json(Comparison { lake, fish }))
+}
+
+/// Standard competition ranking ("1224"), values that aren't known aren't
+/// ranked.
+fn rank(values: impl Iterator<Item = Option<f64>>, lower_is_better: bool) -> Vec<Option<i64>> {
+    let values: Vec<Option<f64>> = values.collect();
+    values
+        .iter()
+        .map(|value| {
+            value.map(|value| {
+                let better = values
+                    .iter()
+                    .flatten()
+                    .filter(|other| {
+                        if lower_is_better {
+                            **other < value
+                        } else {
+                            **other > value
+                        }
+                    })
+                    .count();
+                better as i64 + 1
+            })
+        })
+        .collect()
+}
+
+/// Fish types without samples (in the lake) are still returned, without
+/// averages.
+#[tracing::instrument(name = "Querying the database for fish averages", skip(db_pool))]
+async fn get_averages_db(
+    db_pool: &PgPool,
+    fish_type_ids: &[Uuid],
+    lake: Option<&str>,
+) -> Result<Vec<AvgRow>, sqlx::Error> {
+    sqlx::query_as!(
+        AvgRow,
+        r#"
+        SELECT
+            fish_type.id as fish_type_id,
+            fish_type.name,
+            fish_type.anishinaabe_name,
+            count(DISTINCT fish.id) as "samples!",
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'protein') as protein,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'pcb') as pcb,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'mercury') as mercury,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'omega_3') as omega_3,
+            AVG(measurement.value) FILTER (
+                WHERE measurement.analyte = 'omega_3_ratio'
+            ) as omega_3_ratio
+        FROM fish_type
+        LEFT JOIN fish
+            ON fish.fish_type_id = fish_type.id
+            AND ($2::TEXT IS NULL OR fish.lake = $2)
+        LEFT JOIN substituted_measurement($3) AS measurement ON measurement.fish_id = fish.id
+        WHERE fish_type.id = ANY($1)
+        GROUP BY fish_type.id;
+        "#,
+        fish_type_ids,
+        lake,
+        Substitution::default().factor()
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+/// The average of each of the analytes for each of the fish types that has
+/// measurements of it.
+#[tracing::instrument(
+    name = "Querying the database for fish measurement averages",
+    skip(db_pool)
+)]
+async fn get_measurement_averages_db(
+    db_pool: &PgPool,
+    fish_type_ids: &[Uuid],
+    lake: Option<&str>,
+    analytes: &[String],
+) -> Result<Vec<MeasurementAvgRow>, sqlx::Error> {
+    sqlx::query_as!(
+        MeasurementAvgRow,
+        r#"
+        SELECT
+            fish.fish_type_id,
+            measurement.analyte as "analyte!",
+            AVG(measurement.value) as "value!"
+        FROM substituted_measurement($4) AS measurement
+        JOIN fish ON fish.id = measurement.fish_id
+        WHERE fish.fish_type_id = ANY($1)
+            AND ($2::TEXT IS NULL OR fish.lake = $2)
+            AND measurement.analyte = ANY($3)
+        GROUP BY fish.fish_type_id, measurement.analyte;
+        "#,
+        fish_type_ids,
+        lake,
+        analytes,
+        Substitution::default().factor()
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
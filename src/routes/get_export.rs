@@ -0,0 +1,281 @@
+use crate::routes::{get_analyte_data, lake_exists};
+use crate::utils::{e400, e500};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{get, rt, web, HttpResponse};
+use chrono::NaiveDate;
+use futures_util::{stream, StreamExt};
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Rows encoded ahead of a slow client.
+const EXPORT_BUFFER: usize = 64;
+
+/// Columns of the CSV export before the one per analyte.
+const EXPORT_COLUMNS: [&str; 11] = [
+    "id",
+    "fish_type_id",
+    "fish_type",
+    "anishinaabe_name",
+    "lake",
+    "sampling_site",
+    "data_source",
+    "study",
+    "sample_date",
+    "length_cm",
+    "weight_g",
+];
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Jsonl,
+    /// A CSV starting with a byte order mark, so spreadsheet apps read it as
+    /// UTF-8.
+    Excel,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct ExportQuery {
+    format: Option<ExportFormat>,
+    lake: Option<String>,
+    fish_type: Option<Uuid>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+#[derive(serde::Serialize)]
+struct ExportRow {
+    id: Uuid,
+    fish_type_id: Uuid,
+    fish_type: String,
+    anishinaabe_name: Option<String>,
+    lake: String,
+    sampling_site: Option<String>,
+    /// The agency of the data source.
+    data_source: Option<String>,
+    study: Option<String>,
+    sample_date: Option<NaiveDate>,
+    length_cm: Option<f64>,
+    weight_g: Option<f64>,
+    /// Analyte name to `value`, `detected` and `detection_limit`.
+    measurements: serde_json::Value,
+}
+
+/// Streams every fish sample, optionally in a lake, of a fish type and
+/// sampled between `from` and `to` (inclusive), with its fish type, sampling
+/// site, data source and measurements. A sample's date is its `sample_date`,
+/// or else the day of its `date_sampled`.
+///
+/// `format` is one of:
+/// - `csv` (the default): a column per analyte in the catalog, non-detects
+///   are written as `<` the detection limit, or `ND` when it isn't known.
+/// - `excel`: the same CSV for spreadsheet apps.
+/// - `jsonl`: a JSON object per line.
+///
+/// # Example
+///
+/// `.../export/fish?format=jsonl&lake=Huron&from=2020-01-01`
+///
+///```text
+/// {"id":"1fe5c906-d09d-11ed-afa1-0242ac120002","fish_type_id":"1fe5c906-d09d-11ed-afa1-0242ac120022","fish_type":"Herring","anishinaabe_name":"Okewis","lake":"Huron","sampling_site":"Thunder Bay","data_source":"GLIFWC","study":null,"sample_date":"2021-06-02","length_cm":31.5,"weight_g":null,"measurements":{"mercury":{"value":0.032,"detected":true,"detection_limit":null},"pcb":{"value":0.0,"detected":false,"detection_limit":0.0005}}}
+/// // ...
+///```
+#[tracing::instrument(name = "Exporting fish data", skip(db_pool))]
+#[get("/export/fish")]
+pub async fn export_fish(
+    query: web::Query<ExportQuery>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let query = query.into_inner();
+    let format = query.format.unwrap_or_default();
+    if let Some(lake) = &query.lake {
+        if !lake_exists(&db_pool, lake).await.map_err(e500)? {
+            return Err(e400("Invalid lake."));
+        }
+    }
+    if query.from.zip(query.to).is_some_and(|(from, to)| from > to) {
+        return Err(e400("from can't be after to."));
+    }
+
+    let analytes: Vec<String> = get_analyte_data(&db_pool)
+        .await
+        .map_err(e500)?
+        .into_iter()
+        .map(|analyte| analyte.name)
+        .collect();
+    let (sender, receiver) = mpsc::channel(EXPORT_BUFFER);
+    let header = match format {
+        ExportFormat::Csv => Some(csv_header(&analytes)),
+        ExportFormat::Excel => Some([b"\xEF\xBB\xBF".to_vec(), csv_header(&analytes)].concat()),
+        ExportFormat::Jsonl => None,
+    };
+    if let Some(header) = header {
+        // The channel is empty, this can't fail.
+        let _ = sender.try_send(Ok(web::Bytes::from(header)));
+    }
+    rt::spawn(stream_rows(
+        db_pool.get_ref().clone(),
+        query,
+        format,
+        analytes,
+        sender,
+    ));
+
+    let (content_type, extension) = match format {
+        ExportFormat::Csv | ExportFormat::Excel => ("text/csv; charset=utf-8", "csv"),
+        ExportFormat::Jsonl => ("application/jsonl", "jsonl"),
+    };
+    let body = stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+    tracing::info!("Fish data export has started.");
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("fish.{}", extension))],
+        })
+        .streaming(body))
+}
+
+/// Sends the rows as they're read from the database. Stops when the client
+/// goes away, and ends the response with an error when the query fails so an
+/// incomplete export can't be mistaken for a complete one.
+async fn stream_rows(
+    db_pool: PgPool,
+    query: ExportQuery,
+    format: ExportFormat,
+    analytes: Vec<String>,
+    sender: mpsc::Sender<Result<web::Bytes, actix_web::Error>>,
+) {
+    let mut rows = sqlx::query_as!(
+        ExportRow,
+        r#"
+        SELECT
+            fish.id,
+            fish.fish_type_id,
+            fish_type.name as fish_type,
+            fish_type.anishinaabe_name,
+            fish.lake,
+            sampling_site.name as "sampling_site?",
+            data_source.agency as "data_source?",
+            data_source.study as "study?",
+            COALESCE(fish.sample_date, fish.date_sampled::DATE) as sample_date,
+            fish.length_cm,
+            fish.weight_g,
+            COALESCE(
+                (
+                    SELECT jsonb_object_agg(
+                        measurement.analyte,
+                        jsonb_build_object(
+                            'value', measurement.value,
+                            'detected', measurement.detected,
+                            'detection_limit',
+                            COALESCE(measurement.detection_limit, analyte.detection_limit)
+                        )
+                    )
+                    FROM measurement
+                    JOIN analyte ON analyte.name = measurement.analyte
+                    WHERE measurement.fish_id = fish.id
+                ),
+                '{}'
+            ) as "measurements!"
+        FROM fish
+        JOIN fish_type ON fish.fish_type_id = fish_type.id
+        LEFT JOIN sampling_site ON sampling_site.id = fish.sampling_site_id
+        LEFT JOIN data_source ON data_source.id = fish.data_source_id
+        WHERE ($1::TEXT IS NULL OR fish.lake = $1)
+            AND ($2::UUID IS NULL OR fish.fish_type_id = $2)
+            AND ($3::DATE IS NULL OR COALESCE(fish.sample_date, fish.date_sampled::DATE) >= $3)
+            AND ($4::DATE IS NULL OR COALESCE(fish.sample_date, fish.date_sampled::DATE) <= $4)
+        ORDER BY fish.lake, fish_type.name, fish.id;
+        "#,
+        query.lake,
+        query.fish_type,
+        query.from,
+        query.to
+    )
+    .fetch(&db_pool);
+
+    while let Some(row) = rows.next().await {
+        let chunk = match row {
+            Ok(row) => match format {
+                ExportFormat::Csv | ExportFormat::Excel => Ok(csv_row(&row, &analytes)),
+                ExportFormat::Jsonl => jsonl_row(&row).map_err(e500),
+            },
+            Err(e) => {
+                tracing::error!("Failed to execute the query: {:?}", e);
+                Err(e500(e))
+            }
+        };
+        let failed = chunk.is_err();
+        if sender.send(chunk.map(web::Bytes::from)).await.is_err() {
+            tracing::warn!("Fish data export was cancelled.");
+            return;
+        }
+        if failed {
+            return;
+        }
+    }
+    tracing::info!("Fish data has been exported.");
+}
+
+fn csv_header(analytes: &[String]) -> Vec<u8> {
+    let columns = EXPORT_COLUMNS
+        .iter()
+        .map(|column| column.to_string())
+        .chain(analytes.iter().cloned());
+    csv_line(columns)
+}
+
+fn csv_row(row: &ExportRow, analytes: &[String]) -> Vec<u8> {
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    let number = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+    let fields = [
+        row.id.to_string(),
+        row.fish_type_id.to_string(),
+        row.fish_type.clone(),
+        text(&row.anishinaabe_name),
+        row.lake.clone(),
+        text(&row.sampling_site),
+        text(&row.data_source),
+        text(&row.study),
+        row.sample_date
+            .map(|date| date.to_string())
+            .unwrap_or_default(),
+        number(row.length_cm),
+        number(row.weight_g),
+    ];
+    let measurements = analytes.iter().map(|analyte| {
+        let measurement = &row.measurements[analyte];
+        if measurement.is_null() {
+            return String::new();
+        }
+        if measurement["detected"].as_bool().unwrap_or(true) {
+            return number(measurement["value"].as_f64());
+        }
+        match measurement["detection_limit"].as_f64() {
+            Some(limit) => format!("<{}", limit),
+            None => "ND".to_string(),
+        }
+    });
+    csv_line(fields.into_iter().chain(measurements))
+}
+
+fn csv_line(fields: impl IntoIterator<Item = String>) -> Vec<u8> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    let line = writer
+        .write_record(fields.into_iter().collect::<Vec<_>>())
+        .and_then(|_| writer.into_inner().map_err(|e| e.into_error().into()));
+    // Writing to a Vec can't fail.
+    line.unwrap_or_default()
+}
+
+fn jsonl_row(row: &ExportRow) -> Result<Vec<u8>, serde_json::Error> {
+    let mut line = serde_json::to_vec(row)?;
+    line.push(b'\n');
+    Ok(line)
+}
@@ -1,8 +1,8 @@
 use crate::{
-    routes::{Fish, Recipe},
-    utils::get_optional_user_id,
+    authentication::OptionalSessionUser,
+    routes::{DataSource, Fish, Measurement, Recipe},
 };
-use actix_web::{get, web, HttpRequest, HttpResponse};
+use actix_web::{get, web, HttpResponse};
 use anyhow::Result;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -15,6 +15,11 @@ pub struct FishUuid {
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct FishResponse {
     pub fish_data: Fish,
+    /// Every analyte measured in the sample, including those without a field
+    /// in `fish_data`.
+    pub measurements: Vec<Measurement>,
+    /// Where the figures come from, if recorded.
+    pub data_source: Option<DataSource>,
     pub recipe_data: Vec<Recipe>,
     pub is_favorite: bool,
 }
@@ -39,7 +44,30 @@ pub struct FishResponse {
 ///         "omega_3": 0.212,
 ///         "omega_3_ratio": 8.12,
 ///         "pcb": 0.0002,
-///         "protein": 21.1
+///         "protein": 21.1,
+///         "length_cm": 31.5,
+///         "weight_g": 410.0
+///       },
+///       "measurements": [
+///         {
+///           "analyte": "selenium",
+///           "display_name": "Selenium",
+///           "kind": "nutrient",
+///           "unit": "ppm",
+///           "value": 0.41,
+///           "detected": true,
+///           "detection_limit": 0.01
+///         },
+///         ...
+///       ],
+///       "data_source": {
+///         "id": uuid,
+///         "agency": "GLIFWC",
+///         "study": "Mercury in Ceded Territory Walleye",
+///         "url": "https://glifwc.org/mercury",
+///         "lab_method": "EPA Method 7473",
+///         "collection_year": 2019,
+///         "license": "CC BY 4.0"
 ///       },
 ///       "recipe_data": {
 ///         [
@@ -65,10 +93,9 @@ pub struct FishResponse {
 pub async fn fish(
     uuid: web::Path<FishUuid>,
     db_pool: web::Data<PgPool>,
-    req: HttpRequest,
+    user: OptionalSessionUser,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_optional_user_id(req)?;
-    match get_all_fish_data(&db_pool, uuid.uuid, user_id).await {
+    match get_all_fish_data(&db_pool, uuid.uuid, user.user_id()).await {
         Ok(data) => {
             tracing::info!("Fish type data has been queried from the db.");
             Ok(HttpResponse::Ok().json(data))
@@ -89,6 +116,8 @@ async fn get_all_fish_data(
     user_id: Option<Uuid>,
 ) -> Result<FishResponse, sqlx::Error> {
     let fish_data = get_fish_data(db_pool, fish_uuid).await?;
+    let measurements = get_measurement_data(db_pool, fish_uuid).await?;
+    let data_source = get_data_source(db_pool, fish_uuid).await?;
     let recipe_data = get_recipe_data(db_pool, fish_data.fish_type_id).await?;
     let is_favorite = match user_id {
         Some(user_id) => get_is_favorite(db_pool, fish_data.fish_type_id, user_id).await?,
@@ -97,6 +126,8 @@ async fn get_all_fish_data(
 
     Ok(FishResponse {
         fish_data,
+        measurements,
+        data_source,
         recipe_data,
         is_favorite,
     })
@@ -122,7 +153,9 @@ async fn get_fish_data(db_pool: &PgPool, fish_uuid: Uuid) -> Result<Fish, sqlx::
             fish.pcb,
             fish.protein,
             fish.lake,
-            fish_type.about
+            fish_type.about,
+            fish.length_cm,
+            fish.weight_g
         FROM fish_type
         INNER JOIN fish
         ON fish_type.id=fish.fish_type_id
@@ -140,6 +173,71 @@ async fn get_fish_data(db_pool: &PgPool, fish_uuid: Uuid) -> Result<Fish, sqlx::
     Ok(data)
 }
 
+#[tracing::instrument(name = "Querying the database for measurements", skip(db_pool))]
+async fn get_measurement_data(
+    db_pool: &PgPool,
+    fish_uuid: Uuid,
+) -> Result<Vec<Measurement>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        Measurement,
+        r#"
+        SELECT
+            analyte.name as analyte,
+            analyte.display_name,
+            analyte.kind,
+            analyte.unit,
+            measurement.value,
+            measurement.detected,
+            COALESCE(measurement.detection_limit, analyte.detection_limit) as detection_limit
+        FROM measurement
+        JOIN analyte ON measurement.analyte = analyte.name
+        WHERE measurement.fish_id = $1
+        ORDER BY analyte.kind, analyte.name;
+        "#,
+        fish_uuid
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
+
+#[tracing::instrument(name = "Querying the database for a data source", skip(db_pool))]
+async fn get_data_source(
+    db_pool: &PgPool,
+    fish_uuid: Uuid,
+) -> Result<Option<DataSource>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        DataSource,
+        r#"
+        SELECT
+            data_source.id,
+            data_source.agency,
+            data_source.study,
+            data_source.url,
+            data_source.lab_method,
+            data_source.collection_year,
+            data_source.license
+        FROM data_source
+        JOIN fish ON fish.data_source_id = data_source.id
+        WHERE fish.id = $1;
+        "#,
+        fish_uuid
+    )
+    .fetch_optional(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
+
 #[tracing::instrument(
     name = "Querying the database for recipes",
     skip(fish_type_id, db_pool)
@@ -1,8 +1,12 @@
 use crate::{
-    routes::{get_is_favorite, Recipe},
-    utils::get_optional_user_id,
+    authentication::OptionalSessionUser,
+    routes::{
+        get_is_favorite, get_length_class_data, length_class_exists, DataSource, LengthClass,
+        Recipe, Substitution,
+    },
+    utils::{e400, e500},
 };
-use actix_web::{get, web, HttpRequest, HttpResponse};
+use actix_web::{get, web, HttpResponse};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -23,9 +27,29 @@ struct Fish {
     pub protein: Option<f64>,
 }
 
+/// The average level of an analyte over the samples of a fish type.
+#[derive(serde::Serialize)]
+struct AvgMeasurement {
+    pub analyte: String,
+    pub display_name: String,
+    pub kind: String,
+    pub unit: String,
+    pub value: f64,
+    pub samples: i64,
+    /// Samples where the analyte wasn't detected.
+    pub censored: i64,
+}
+
 #[derive(serde::Serialize)]
 pub struct FishData {
     fish_data: Fish,
+    measurements: Vec<AvgMeasurement>,
+    substitution: Substitution,
+    length_class: Option<String>,
+    /// The classes `length_class` can be.
+    length_classes: Vec<LengthClass>,
+    /// Where the averaged samples come from.
+    data_sources: Vec<DataSource>,
     recipe_data: Vec<Recipe>,
     is_favorite: bool,
 }
@@ -33,14 +57,18 @@ pub struct FishData {
 #[derive(serde::Deserialize)]
 pub struct FishQuery {
     fishtype_id: Uuid,
+    substitution: Option<Substitution>,
+    length_class: Option<String>,
 }
 
 /// Retrives average data for a fish type specified by its uuid. If no or an invalid
-/// uuid is given a 400 Bad Request will be returned.
+/// uuid is given a 400 Bad Request will be returned. Non-detects count as 0, or
+/// as a fraction of their detection limit with `substitution` set to `half_dl`
+/// or `dl`. With a `length_class` only samples of that length are averaged.
 ///
 /// # Example
 ///
-/// `.../fish_avg?fishtype_id=1fe5c906-d09d-11ed-afa1-0242ac120022`
+/// `.../fish_avg?fishtype_id=1fe5c906-d09d-11ed-afa1-0242ac120022&substitution=half_dl&length_class=under_38cm`
 ///
 ///```json
 /// {
@@ -57,6 +85,36 @@ pub struct FishQuery {
 ///         "pcb": 0.0002,
 ///         "protein": 21.1
 ///       },
+///       measurements: [
+///         {
+///           "analyte": "selenium",
+///           "display_name": "Selenium",
+///           "kind": "nutrient",
+///           "unit": "ppm",
+///           "value": 0.38,
+///           "samples": 12,
+///           "censored": 2
+///         },
+///         ...
+///       ],
+///       substitution: "half_dl",
+///       length_class: "under_38cm",
+///       length_classes: [
+///         {
+///           "name": "under_38cm",
+///           "min_length_cm": 0.0,
+///           "max_length_cm": 38.1
+///         },
+///         ...
+///       ],
+///       data_sources: [
+///         {
+///           "id": uuid,
+///           "agency": "GLIFWC",
+///           ...
+///         },
+///         ...
+///       ],
 ///       recipe_data: {
 ///         [
 ///           "id": uuid,
@@ -80,10 +138,18 @@ pub struct FishQuery {
 pub async fn fish_avg(
     query: web::Query<FishQuery>,
     db_pool: web::Data<PgPool>,
-    req: HttpRequest,
+    user: OptionalSessionUser,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_optional_user_id(req)?;
-    match get_all_fish_data(query.fishtype_id, user_id, &db_pool).await {
+    let query = query.into_inner();
+    if let Some(length_class) = &query.length_class {
+        if !length_class_exists(&db_pool, length_class, Some(query.fishtype_id))
+            .await
+            .map_err(e500)?
+        {
+            return Err(e400("Unknown length class."));
+        }
+    }
+    match get_all_fish_data(query, user.user_id(), &db_pool).await {
         Ok(data) => {
             tracing::info!("Avg fish data has been queried from the db.");
             Ok(HttpResponse::Ok().json(data))
@@ -99,11 +165,17 @@ pub async fn fish_avg(
 }
 
 async fn get_all_fish_data(
-    fish_uuid: Uuid,
+    query: FishQuery,
     user_id: Option<Uuid>,
     db_pool: &PgPool,
 ) -> Result<FishData, sqlx::Error> {
-    let fish_data = get_fish_data(fish_uuid, db_pool).await?;
+    let fish_uuid = query.fishtype_id;
+    let substitution = query.substitution.unwrap_or_default();
+    let length_class = query.length_class.as_deref();
+    let fish_data = get_fish_data(fish_uuid, substitution, length_class, db_pool).await?;
+    let measurements = get_measurement_data(fish_uuid, substitution, length_class, db_pool).await?;
+    let length_classes = get_length_class_data(db_pool, fish_uuid).await?;
+    let data_sources = get_data_sources(fish_uuid, length_class, db_pool).await?;
     let recipe_data = get_recipe_data(fish_uuid, db_pool).await?;
     let is_favorite = match user_id {
         Some(user_id) => get_is_favorite(db_pool, fish_uuid, user_id).await?,
@@ -112,13 +184,23 @@ async fn get_all_fish_data(
 
     Ok(FishData {
         fish_data,
+        measurements,
+        substitution,
+        length_class: query.length_class,
+        length_classes,
+        data_sources,
         recipe_data,
         is_favorite,
     })
 }
 
 #[tracing::instrument(name = "Querying the database", skip(db_pool))]
-async fn get_fish_data(fishtype_id: Uuid, db_pool: &PgPool) -> Result<Fish, sqlx::Error> {
+async fn get_fish_data(
+    fishtype_id: Uuid,
+    substitution: Substitution,
+    length_class: Option<&str>,
+    db_pool: &PgPool,
+) -> Result<Fish, sqlx::Error> {
     let data = sqlx::query_as!(
         Fish,
         r#"
@@ -131,17 +213,33 @@ async fn get_fish_data(fishtype_id: Uuid, db_pool: &PgPool) -> Result<Fish, sqlx
             fish_type.s3_fish_image,
             fish_type.s3_woodland_image,
             fish_type.about,
-            AVG(pcb) as pcb,
-            AVG(protein) as protein,
-            AVG(mercury) as mercury,
-            AVG(omega_3_ratio) as omega_3_ratio,
-            AVG(omega_3) as omega_3
+            AVG(value) FILTER (WHERE analyte = 'pcb') as pcb,
+            AVG(value) FILTER (WHERE analyte = 'protein') as protein,
+            AVG(value) FILTER (WHERE analyte = 'mercury') as mercury,
+            AVG(value) FILTER (WHERE analyte = 'omega_3_ratio') as omega_3_ratio,
+            AVG(value) FILTER (WHERE analyte = 'omega_3') as omega_3
         FROM fish 
         JOIN fish_type ON fish.fish_type_id=fish_type.id
+        LEFT JOIN (
+            SELECT
+                measurement.fish_id,
+                measurement.analyte,
+                substituted_value(
+                    measurement.detected,
+                    measurement.value,
+                    COALESCE(measurement.detection_limit, analyte.detection_limit),
+                    $2
+                ) as value
+            FROM measurement
+            JOIN analyte ON measurement.analyte = analyte.name
+        ) AS measurement ON measurement.fish_id = fish.id
         WHERE fish_type.id=$1
+            AND ($3::TEXT IS NULL OR in_length_class(fish.fish_type_id, fish.length_cm, $3))
         GROUP BY fish_type.id;
         "#,
-        fishtype_id
+        fishtype_id,
+        substitution.factor(),
+        length_class
     )
     .fetch_one(db_pool)
     .await
@@ -153,6 +251,93 @@ async fn get_fish_data(fishtype_id: Uuid, db_pool: &PgPool) -> Result<Fish, sqlx
     Ok(data)
 }
 
+/// The average of every analyte measured in samples of the fish type.
+#[tracing::instrument(name = "Querying the database for avg measurements", skip(db_pool))]
+async fn get_measurement_data(
+    fishtype_id: Uuid,
+    substitution: Substitution,
+    length_class: Option<&str>,
+    db_pool: &PgPool,
+) -> Result<Vec<AvgMeasurement>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        AvgMeasurement,
+        r#"
+        SELECT
+            analyte.name as analyte,
+            analyte.display_name,
+            analyte.kind,
+            analyte.unit,
+            AVG(
+                substituted_value(
+                    measurement.detected,
+                    measurement.value,
+                    COALESCE(measurement.detection_limit, analyte.detection_limit),
+                    $2
+                )
+            ) as "value!",
+            count(*) as "samples!",
+            count(*) FILTER (WHERE NOT measurement.detected) as "censored!"
+        FROM measurement
+        JOIN fish ON measurement.fish_id = fish.id
+        JOIN analyte ON measurement.analyte = analyte.name
+        WHERE fish.fish_type_id = $1
+            AND ($3::TEXT IS NULL OR in_length_class(fish.fish_type_id, fish.length_cm, $3))
+        GROUP BY analyte.id
+        ORDER BY analyte.kind, analyte.name;
+        "#,
+        fishtype_id,
+        substitution.factor(),
+        length_class
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
+
+#[tracing::instrument(name = "Querying the database for data sources", skip(db_pool))]
+async fn get_data_sources(
+    fishtype_id: Uuid,
+    length_class: Option<&str>,
+    db_pool: &PgPool,
+) -> Result<Vec<DataSource>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        DataSource,
+        r#"
+        SELECT
+            id,
+            agency,
+            study,
+            url,
+            lab_method,
+            collection_year,
+            license
+        FROM data_source
+        WHERE id IN (
+            SELECT data_source_id
+            FROM fish
+            WHERE fish_type_id = $1
+                AND ($2::TEXT IS NULL OR in_length_class(fish_type_id, length_cm, $2))
+        )
+        ORDER BY agency, collection_year;
+        "#,
+        fishtype_id,
+        length_class
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
+
 #[tracing::instrument(
     name = "Querying the database for recipes",
     skip(fish_type_id, db_pool)
@@ -1,3 +1,4 @@
+use crate::routes::Substitution;
 use actix_web::{get, web, HttpResponse};
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -65,15 +66,19 @@ async fn get_all_fish_data(db_pool: &PgPool) -> Result<Vec<Fish>, sqlx::Error> {
             fish_type.s3_fish_image,
             fish_type.s3_woodland_image,
             fish_type.about,
-            AVG(pcb) as pcb,
-            AVG(protein) as protein,
-            AVG(mercury) as mercury,
-            AVG(omega_3_ratio) as omega_3_ratio,
-            AVG(omega_3) as omega_3
-        FROM fish 
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'pcb') as pcb,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'protein') as protein,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'mercury') as mercury,
+            AVG(measurement.value) FILTER (
+                WHERE measurement.analyte = 'omega_3_ratio'
+            ) as omega_3_ratio,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'omega_3') as omega_3
+        FROM fish
         JOIN fish_type ON fish.fish_type_id=fish_type.id
+        LEFT JOIN substituted_measurement($1) AS measurement ON measurement.fish_id = fish.id
         GROUP BY fish_type.id;
         "#,
+        Substitution::default().factor()
     )
     .fetch_all(db_pool)
     .await
@@ -1,18 +1,21 @@
-use crate::routes::{Fish, VALID_LAKES};
+use crate::routes::{lake_exists, length_class_exists, Fish};
 use actix_web::{get, web, HttpResponse};
 use sqlx::PgPool;
 
 #[derive(serde::Deserialize)]
 pub struct FishQuery {
     lake: Option<String>,
+    length_class: Option<String>,
 }
 
 /// Returns a JSON of all fish for a given lake. If no lake is supplied
-/// or an invalid lake is supplied the 'store' fish will be returned.
+/// or an invalid lake is supplied the 'store' fish will be returned. With a
+/// `length_class`, only fish measured to be in their fish type's class of that
+/// name are returned.
 ///
 /// # Example
 ///
-/// `.../fishs?lake=Huron`
+/// `.../fishs?lake=Huron&length_class=under_38cm`
 ///
 ///```json
 /// {
@@ -26,16 +29,33 @@ pub struct FishQuery {
 /// }
 ///```
 ///
-#[tracing::instrument(name = "Retreving all fish data", skip(lake, db_pool))]
+#[tracing::instrument(name = "Retreving all fish data", skip(query, db_pool))]
 #[get("/fishs")]
-pub async fn fishs(lake: web::Query<FishQuery>, db_pool: web::Data<PgPool>) -> HttpResponse {
-    let lake = lake.lake.clone();
+pub async fn fishs(query: web::Query<FishQuery>, db_pool: web::Data<PgPool>) -> HttpResponse {
+    let lake = query.lake.clone();
     let mut lake = lake.unwrap_or("Store".to_string());
-    if !VALID_LAKES.iter().any(|e| e == &lake) {
-        tracing::warn!("Invalid lake supplied. Falling back to Store.");
-        lake = "Store".to_string();
+    match lake_exists(&db_pool, &lake).await {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::warn!("Invalid lake supplied. Falling back to Store.");
+            lake = "Store".to_string();
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+    if let Some(length_class) = &query.length_class {
+        match length_class_exists(&db_pool, length_class, None).await {
+            Ok(true) => {}
+            Ok(false) => return HttpResponse::BadRequest().body("Unknown length class."),
+            Err(e) => {
+                tracing::error!("Failed to execute query: {:?}", e);
+                return HttpResponse::InternalServerError().finish();
+            }
+        }
     }
-    match get_fish_data(&lake, &db_pool).await {
+    match get_fish_data(&lake, query.length_class.as_deref(), &db_pool).await {
         Ok(data) => {
             tracing::info!("All fish type data has been queried from the db.");
             HttpResponse::Ok().json(data)
@@ -48,7 +68,11 @@ pub async fn fishs(lake: web::Query<FishQuery>, db_pool: web::Data<PgPool>) -> H
 }
 
 #[tracing::instrument(name = "Querying the database", skip(db_pool))]
-async fn get_fish_data(lake: &str, db_pool: &PgPool) -> Result<Vec<Fish>, sqlx::Error> {
+async fn get_fish_data(
+    lake: &str,
+    length_class: Option<&str>,
+    db_pool: &PgPool,
+) -> Result<Vec<Fish>, sqlx::Error> {
     let data = sqlx::query_as!(
         Fish,
         r#"
@@ -67,13 +91,17 @@ async fn get_fish_data(lake: &str, db_pool: &PgPool) -> Result<Vec<Fish>, sqlx::
             fish.omega_3_ratio,
             fish.mercury,
             fish.lake,
-            fish_type.about
+            fish_type.about,
+            fish.length_cm,
+            fish.weight_g
         FROM fish
         JOIN fish_type
         ON fish.fish_type_id=fish_type.id
-        WHERE fish.lake=$1;
+        WHERE fish.lake=$1
+            AND ($2::TEXT IS NULL OR in_length_class(fish.fish_type_id, fish.length_cm, $2));
         "#,
-        lake
+        lake,
+        length_class
     )
     .fetch_all(db_pool)
     .await
@@ -0,0 +1,89 @@
+use crate::routes::Lake;
+use actix_web::{get, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+
+/// Retrives every lake fish samples can come from. `latitude` and `longitude`
+/// are the centroid of the lake and are null for e.g. `Store`.
+///
+/// # Example
+///
+/// `/lakes`
+///
+///```json
+/// [
+///   {
+///     "id": uuid,
+///     "name": "Superior",
+///     "anishinaabe_name": "Gichigami",
+///     "region": "Great Lakes",
+///     "description": null,
+///     "latitude": 47.7,
+///     "longitude": -87.5,
+///     "image_url": null
+///   },
+///   ...
+/// ]
+///```
+///
+#[tracing::instrument(name = "Retreving all lakes", skip(db_pool))]
+#[get("/lakes")]
+pub async fn lakes(db_pool: web::Data<PgPool>) -> HttpResponse {
+    match get_lake_data(&db_pool).await {
+        Ok(data) => {
+            tracing::info!("Lake data has been queried from the db.");
+            HttpResponse::Ok().json(data)
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Querying the database for lakes", skip(db_pool))]
+async fn get_lake_data(db_pool: &PgPool) -> Result<Vec<Lake>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        Lake,
+        r#"
+        SELECT
+            id,
+            name,
+            anishinaabe_name,
+            region,
+            description,
+            latitude,
+            longitude,
+            image_url
+        FROM lake
+        ORDER BY name;
+        "#
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
+
+/// Whether a lake with the given name exists.
+#[tracing::instrument(name = "Checking that a lake exists", skip(db_pool))]
+pub async fn lake_exists(db_pool: &PgPool, name: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT EXISTS(SELECT 1 FROM lake WHERE name = $1) as "exists!";
+        "#,
+        name
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.exists)
+}
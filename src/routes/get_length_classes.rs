@@ -0,0 +1,108 @@
+use crate::routes::LengthClass;
+use actix_web::{get, web, HttpResponse};
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct FishTypeUuid {
+    uuid: Uuid,
+}
+
+/// Retrives the length classes of a fish type, shortest first. They can be
+/// passed as `length_class` to `fishs`, `fish_avg` and `fish_avg/advice`.
+///
+/// # Example
+///
+/// `/fish_type/1fe5c906-d09d-11ed-afa1-0242ac120022/length_classes`
+///
+///```json
+/// [
+///   {
+///     "name": "under_38cm",
+///     "min_length_cm": 0.0,
+///     "max_length_cm": 38.1
+///   },
+///   {
+///     "name": "38cm_and_over",
+///     "min_length_cm": 38.1,
+///     "max_length_cm": null
+///   }
+/// ]
+///```
+///
+#[tracing::instrument(name = "Retreving length classes", skip(uuid, db_pool))]
+#[get("/fish_type/{uuid}/length_classes")]
+pub async fn length_classes(
+    uuid: web::Path<FishTypeUuid>,
+    db_pool: web::Data<PgPool>,
+) -> HttpResponse {
+    match get_length_class_data(&db_pool, uuid.uuid).await {
+        Ok(data) => {
+            tracing::info!("Length class data has been queried from the db.");
+            HttpResponse::Ok().json(data)
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[tracing::instrument(name = "Querying the database for length classes", skip(db_pool))]
+pub async fn get_length_class_data(
+    db_pool: &PgPool,
+    fish_type_id: Uuid,
+) -> Result<Vec<LengthClass>, sqlx::Error> {
+    let data = sqlx::query_as!(
+        LengthClass,
+        r#"
+        SELECT
+            name,
+            min_length_cm,
+            max_length_cm
+        FROM length_class
+        WHERE fish_type_id = $1
+        ORDER BY min_length_cm, max_length_cm NULLS LAST;
+        "#,
+        fish_type_id
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(data)
+}
+
+/// Whether the fish type, or any fish type if not given, has a length class
+/// with the name.
+#[tracing::instrument(name = "Checking that a length class exists", skip(db_pool))]
+pub async fn length_class_exists(
+    db_pool: &PgPool,
+    name: &str,
+    fish_type_id: Option<Uuid>,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM length_class
+            WHERE name = $1
+                AND ($2::UUID IS NULL OR fish_type_id = $2)
+        ) as "exists!";
+        "#,
+        name,
+        fish_type_id
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.exists)
+}
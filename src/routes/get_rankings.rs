@@ -0,0 +1,232 @@
+use crate::advisory::{benefit_risk_ratio, benefit_risk_score};
+use crate::configuration::ScoringSettings;
+use crate::routes::{get_analyte_data, lake_exists, Substitution};
+use crate::utils::{e400, e500};
+use actix_web::{get, web, HttpResponse};
+use sqlx::PgPool;
+use std::cmp::Ordering;
+use uuid::Uuid;
+
+/// The default `sort`, by `advisory::benefit_risk_score`.
+const BENEFIT_RISK: &str = "benefit_risk";
+
+#[derive(serde::Deserialize, Debug)]
+pub struct RankingQuery {
+    lake: Option<String>,
+    sort: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct RankedFish {
+    pub rank: usize,
+    pub fish_type_id: Uuid,
+    pub name: String,
+    pub anishinaabe_name: Option<String>,
+    pub fish_image: Option<String>,
+    pub s3_fish_image: Option<String>,
+    pub samples: i64,
+    pub protein: Option<f64>,
+    pub pcb: Option<f64>,
+    pub mercury: Option<f64>,
+    pub omega_3: Option<f64>,
+    pub omega_3_ratio: Option<f64>,
+    pub benefit_risk_ratio: Option<f64>,
+    pub score: Option<f64>,
+    /// The average of the analyte the fish are sorted by, None when sorted by
+    /// `benefit_risk`.
+    pub sort_value: Option<f64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Rankings {
+    pub lake: Option<String>,
+    pub sort: String,
+    pub fish: Vec<RankedFish>,
+}
+
+struct AvgRow {
+    fish_type_id: Uuid,
+    name: String,
+    anishinaabe_name: Option<String>,
+    fish_image: Option<String>,
+    s3_fish_image: Option<String>,
+    samples: i64,
+    protein: Option<f64>,
+    pcb: Option<f64>,
+    mercury: Option<f64>,
+    omega_3: Option<f64>,
+    omega_3_ratio: Option<f64>,
+    sort_value: Option<f64>,
+}
+
+/// Ranks the fish types sampled in a lake, or in every lake, best first.
+/// `sort` is either `benefit_risk` (the default, see
+/// `advisory::benefit_risk_score`) or an analyte in the catalog, contaminants
+/// lowest first and nutrients highest first. Fish without a value to sort by
+/// come last. Returns at most `limit` fish, 10 by default.
+///
+/// # Example
+///
+/// `.../rankings?lake=Huron&sort=benefit_risk&limit=3`
+///
+///```json
+/// {
+///     "lake": "Huron",
+///     "sort": "benefit_risk",
+///     "fish": [
+///         {
+///             "rank": 1,
+///             "fish_type_id": "1fe5c906-d09d-11ed-afa1-0242ac120022",
+///             "name": "Herring",
+///             "anishinaabe_name": "Okewis",
+///             "fish_image": "herring.png",
+///             "s3_fish_image": "",
+///             "samples": 12,
+///             "protein": 21.1,
+///             "pcb": 0.0002,
+///             "mercury": 0.032,
+///             "omega_3": 0.212,
+///             "omega_3_ratio": 8.12,
+///             "benefit_risk_ratio": 6.63,
+///             "score": 6.62,
+///             "sort_value": null
+///         },
+///         // ...
+///     ]
+/// }
+///```
+#[tracing::instrument(name = "Ranking fish types", skip(db_pool, scoring))]
+#[get("/rankings")]
+pub async fn rankings(
+    query: web::Query<RankingQuery>,
+    db_pool: web::Data<PgPool>,
+    scoring: web::Data<ScoringSettings>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let query = query.into_inner();
+    let sort = query.sort.unwrap_or_else(|| BENEFIT_RISK.to_string());
+    let limit = query.limit.unwrap_or(10).clamp(1, 100);
+    if let Some(lake) = &query.lake {
+        if !lake_exists(&db_pool, lake).await.map_err(e500)? {
+            return Err(e400("Invalid lake."));
+        }
+    }
+    // The analyte to sort by, and whether it's a contaminant.
+    let sort_analyte = if sort == BENEFIT_RISK {
+        None
+    } else {
+        let analyte = get_analyte_data(&db_pool)
+            .await
+            .map_err(e500)?
+            .into_iter()
+            .find(|analyte| analyte.name == sort)
+            .ok_or_else(|| e400("Invalid sort."))?;
+        Some((analyte.name, analyte.kind == "contaminant"))
+    };
+
+    let rows = get_averages_db(
+        &db_pool,
+        query.lake.as_deref(),
+        sort_analyte.as_ref().map(|(name, _)| name.as_str()),
+    )
+    .await
+    .map_err(e500)?;
+    let mut fish: Vec<(Option<f64>, AvgRow)> = rows
+        .into_iter()
+        .map(|row| {
+            let score = benefit_risk_score(&scoring, row.omega_3, row.mercury, row.pcb);
+            (score, row)
+        })
+        .collect();
+    fish.sort_by(|(a_score, a), (b_score, b)| {
+        let (a_key, b_key) = match sort_analyte {
+            None => (*a_score, *b_score),
+            Some((_, true)) => (a.sort_value.map(|v| -v), b.sort_value.map(|v| -v)),
+            Some((_, false)) => (a.sort_value, b.sort_value),
+        };
+        compare_best_first(a_key, b_key).then_with(|| a.name.cmp(&b.name))
+    });
+
+    let fish = fish
+        .into_iter()
+        .take(limit)
+        .enumerate()
+        .map(|(i, (score, row))| RankedFish {
+            rank: i + 1,
+            benefit_risk_ratio: benefit_risk_ratio(&scoring, row.omega_3, row.mercury),
+            score,
+            sort_value: row.sort_value,
+            fish_type_id: row.fish_type_id,
+            name: row.name,
+            anishinaabe_name: row.anishinaabe_name,
+            fish_image: row.fish_image,
+            s3_fish_image: row.s3_fish_image,
+            samples: row.samples,
+            protein: row.protein,
+            pcb: row.pcb,
+            mercury: row.mercury,
+            omega_3: row.omega_3,
+            omega_3_ratio: row.omega_3_ratio,
+        })
+        .collect();
+
+    tracing::info!("Fish rankings have been queried from the db.");
+    Ok(HttpResponse::Ok().json(Rankings {
+        lake: query.lake,
+        sort,
+        fish,
+    }))
+}
+
+/// Highest first, missing values last.
+fn compare_best_first(a: Option<f64>, b: Option<f64>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => b.total_cmp(&a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// The same samples `fishs` returns, averaged per fish type.
+#[tracing::instrument(name = "Querying the database for fish averages", skip(db_pool))]
+async fn get_averages_db(
+    db_pool: &PgPool,
+    lake: Option<&str>,
+    sort_analyte: Option<&str>,
+) -> Result<Vec<AvgRow>, sqlx::Error> {
+    sqlx::query_as!(
+        AvgRow,
+        r#"
+        SELECT
+            fish_type.id as fish_type_id,
+            fish_type.name,
+            fish_type.anishinaabe_name,
+            fish_type.fish_image,
+            fish_type.s3_fish_image,
+            count(DISTINCT fish.id) as "samples!",
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'protein') as protein,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'pcb') as pcb,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'mercury') as mercury,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'omega_3') as omega_3,
+            AVG(measurement.value) FILTER (
+                WHERE measurement.analyte = 'omega_3_ratio'
+            ) as omega_3_ratio,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = $2) as sort_value
+        FROM fish
+        JOIN fish_type ON fish.fish_type_id=fish_type.id
+        LEFT JOIN substituted_measurement($3) AS measurement ON measurement.fish_id = fish.id
+        WHERE ($1::TEXT IS NULL OR fish.lake = $1)
+        GROUP BY fish_type.id;
+        "#,
+        lake,
+        sort_analyte,
+        Substitution::default().factor()
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
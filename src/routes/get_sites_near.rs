@@ -0,0 +1,253 @@
+use crate::routes::Substitution;
+use crate::utils::{e400, e500};
+use actix_web::{get, web, HttpResponse};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Radius used when the query doesn't give one, in km.
+const DEFAULT_RADIUS_KM: f64 = 50.0;
+/// Largest radius accepted, in km.
+const MAX_RADIUS_KM: f64 = 500.0;
+/// Mean radius of the Earth, in km.
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct NearQuery {
+    lat: f64,
+    lon: f64,
+    radius_km: Option<f64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SiteSpecies {
+    pub fish_type_id: Uuid,
+    pub name: String,
+    pub anishinaabe_name: Option<String>,
+    pub samples: i64,
+    pub mercury: Option<f64>,
+    pub pcb: Option<f64>,
+    pub omega_3: Option<f64>,
+    pub omega_3_ratio: Option<f64>,
+    pub protein: Option<f64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct NearbySite {
+    pub id: Uuid,
+    pub name: String,
+    pub water_body: String,
+    pub lake: Option<String>,
+    pub agency: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub distance_km: f64,
+    /// Every fish type sampled at the site, with its average levels there.
+    pub species: Vec<SiteSpecies>,
+}
+
+struct SiteRow {
+    id: Uuid,
+    name: String,
+    water_body: String,
+    lake: Option<String>,
+    agency: Option<String>,
+    latitude: f64,
+    longitude: f64,
+    distance_km: f64,
+}
+
+struct SpeciesRow {
+    sampling_site_id: Uuid,
+    fish_type_id: Uuid,
+    name: String,
+    anishinaabe_name: Option<String>,
+    samples: i64,
+    mercury: Option<f64>,
+    pcb: Option<f64>,
+    omega_3: Option<f64>,
+    omega_3_ratio: Option<f64>,
+    protein: Option<f64>,
+}
+
+/// Returns the sampling sites within `radius_km` (50 km by default, at most
+/// 500 km) of a point, closest first, with the species sampled at each site and
+/// their average contaminant levels.
+///
+/// # Example
+///
+/// `.../sites/near?lat=46.8&lon=-90.7&radius_km=25`
+///
+///```json
+/// [
+///     {
+///         "id": "1fe5c906-d09d-11ed-afa1-0242ac120002",
+///         "name": "Chequamegon Bay",
+///         "water_body": "Chequamegon Bay",
+///         "lake": "Superior",
+///         "agency": "GLIFWC",
+///         "latitude": 46.65,
+///         "longitude": -90.85,
+///         "distance_km": 20.2,
+///         "species": [
+///             {
+///                 "fish_type_id": "1fe5c906-d09d-11ed-afa1-0242ac120022",
+///                 "name": "Walleye",
+///                 "anishinaabe_name": "Ogaa",
+///                 "samples": 12,
+///                 "mercury": 0.21,
+///                 "pcb": 0.03,
+///                 "omega_3": 0.3,
+///                 "omega_3_ratio": 1.9,
+///                 "protein": 19.1
+///             }
+///         ]
+///     }
+/// ]
+///```
+#[tracing::instrument(name = "Retrieving sampling sites near a point", skip(db_pool))]
+#[get("/sites/near")]
+pub async fn sites_near(
+    query: web::Query<NearQuery>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    if !(-90.0..=90.0).contains(&query.lat) || !(-180.0..=180.0).contains(&query.lon) {
+        return Err(e400("The point is out of range."));
+    }
+    let radius_km = query.radius_km.unwrap_or(DEFAULT_RADIUS_KM);
+    if !(radius_km > 0.0 && radius_km <= MAX_RADIUS_KM) {
+        return Err(e400("The radius has to be between 0 and 500 km."));
+    }
+
+    let sites = get_sites_near_db(&db_pool, query.lat, query.lon, radius_km)
+        .await
+        .map_err(e500)?;
+    let site_ids: Vec<Uuid> = sites.iter().map(|site| site.id).collect();
+    let mut species = get_site_species_db(&db_pool, &site_ids)
+        .await
+        .map_err(e500)?
+        .into_iter()
+        .fold(HashMap::<Uuid, Vec<SiteSpecies>>::new(), |mut map, row| {
+            map.entry(row.sampling_site_id)
+                .or_default()
+                .push(SiteSpecies {
+                    fish_type_id: row.fish_type_id,
+                    name: row.name,
+                    anishinaabe_name: row.anishinaabe_name,
+                    samples: row.samples,
+                    mercury: row.mercury,
+                    pcb: row.pcb,
+                    omega_3: row.omega_3,
+                    omega_3_ratio: row.omega_3_ratio,
+                    protein: row.protein,
+                });
+            map
+        });
+
+    let sites: Vec<NearbySite> = sites
+        .into_iter()
+        .map(|site| NearbySite {
+            species: species.remove(&site.id).unwrap_or_default(),
+            id: site.id,
+            name: site.name,
+            water_body: site.water_body,
+            lake: site.lake,
+            agency: site.agency,
+            latitude: site.latitude,
+            longitude: site.longitude,
+            distance_km: site.distance_km,
+        })
+        .collect();
+
+    tracing::info!("Nearby sampling sites have been queried from the db.");
+    Ok(HttpResponse::Ok().json(sites))
+}
+
+/// Great circle distances are worked out with the haversine formula, after
+/// narrowing the sites down to the band of latitudes the radius can reach.
+#[tracing::instrument(name = "Querying the database for nearby sites", skip(db_pool))]
+async fn get_sites_near_db(
+    db_pool: &PgPool,
+    lat: f64,
+    lon: f64,
+    radius_km: f64,
+) -> Result<Vec<SiteRow>, sqlx::Error> {
+    let lat_delta = (radius_km / EARTH_RADIUS_KM).to_degrees();
+    sqlx::query_as!(
+        SiteRow,
+        r#"
+        SELECT
+            id,
+            name,
+            water_body,
+            lake,
+            agency,
+            latitude,
+            longitude,
+            distance_km as "distance_km!"
+        FROM (
+            SELECT
+                sampling_site.*,
+                2 * $4::FLOAT8 * asin(least(1, sqrt(
+                    power(sin(radians(latitude - $1) / 2), 2)
+                    + cos(radians($1)) * cos(radians(latitude))
+                    * power(sin(radians(longitude - $2) / 2), 2)
+                ))) AS distance_km
+            FROM sampling_site
+            WHERE latitude BETWEEN $1 - $5 AND $1 + $5
+        ) AS sites
+        WHERE distance_km <= $3
+        ORDER BY distance_km, name;
+        "#,
+        lat,
+        lon,
+        radius_km,
+        EARTH_RADIUS_KM,
+        lat_delta
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+#[tracing::instrument(name = "Querying the database for species at sites", skip(db_pool))]
+async fn get_site_species_db(
+    db_pool: &PgPool,
+    site_ids: &[Uuid],
+) -> Result<Vec<SpeciesRow>, sqlx::Error> {
+    sqlx::query_as!(
+        SpeciesRow,
+        r#"
+        SELECT
+            fish.sampling_site_id as "sampling_site_id!",
+            fish_type.id as fish_type_id,
+            fish_type.name,
+            fish_type.anishinaabe_name,
+            count(DISTINCT fish.id) as "samples!",
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'mercury') as mercury,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'pcb') as pcb,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'omega_3') as omega_3,
+            AVG(measurement.value) FILTER (
+                WHERE measurement.analyte = 'omega_3_ratio'
+            ) as omega_3_ratio,
+            AVG(measurement.value) FILTER (WHERE measurement.analyte = 'protein') as protein
+        FROM fish
+        JOIN fish_type ON fish.fish_type_id=fish_type.id
+        LEFT JOIN substituted_measurement($2) AS measurement ON measurement.fish_id = fish.id
+        WHERE fish.sampling_site_id = ANY($1)
+        GROUP BY fish.sampling_site_id, fish_type.id
+        ORDER BY fish_type.name;
+        "#,
+        site_ids,
+        Substitution::default().factor()
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
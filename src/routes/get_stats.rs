@@ -0,0 +1,221 @@
+use crate::routes::{analytes_exist, get_analyte_data, lake_exists, Substitution};
+use crate::utils::{e400, e500};
+use actix_web::{get, web, HttpResponse};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct StatsQuery {
+    attr: Option<String>,
+    lake: Option<String>,
+    fish_type: Option<Uuid>,
+    substitution: Option<Substitution>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+pub struct Percentiles {
+    pub p10: Option<f64>,
+    pub p25: Option<f64>,
+    pub p50: Option<f64>,
+    pub p75: Option<f64>,
+    pub p90: Option<f64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+pub struct AttrStats {
+    pub attr: String,
+    /// Samples with a value for the attribute.
+    pub count: i64,
+    /// Samples where the attribute wasn't detected. They're included in the
+    /// other statistics as set by `substitution`.
+    pub below_detection: i64,
+    pub mean: Option<f64>,
+    pub median: Option<f64>,
+    /// Sample standard deviation, None with fewer than two samples.
+    pub std_dev: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub percentiles: Percentiles,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct StatsResult {
+    pub lake: Option<String>,
+    pub fish_type: Option<Uuid>,
+    pub substitution: Substitution,
+    pub stats: Vec<AttrStats>,
+}
+
+struct StatsRow {
+    attr: String,
+    count: i64,
+    below_detection: i64,
+    mean: Option<f64>,
+    std_dev: Option<f64>,
+    min: Option<f64>,
+    max: Option<f64>,
+    percentiles: Option<Vec<f64>>,
+}
+
+/// Returns summary statistics of the fish samples for every analyte in the
+/// catalog, or just `attr` if given. Samples can be narrowed down to a lake
+/// and a fish type.
+/// Non-detects are counted as set by `substitution` (see `fish_avg`).
+///
+/// # Example
+///
+/// `.../stats?lake=Huron&attr=mercury`
+///
+///```json
+/// {
+///     "lake": "Huron",
+///     "fish_type": null,
+///     "substitution": "zero",
+///     "stats": [
+///         {
+///             "attr": "mercury",
+///             "count": 42,
+///             "below_detection": 3,
+///             "mean": 0.19,
+///             "median": 0.15,
+///             "std_dev": 0.11,
+///             "min": 0.0,
+///             "max": 0.52,
+///             "percentiles": {
+///                 "p10": 0.04,
+///                 "p25": 0.09,
+///                 "p50": 0.15,
+///                 "p75": 0.27,
+///                 "p90": 0.36
+///             }
+///         }
+///     ]
+/// }
+///```
+#[tracing::instrument(name = "Retrieving fish statistics", skip(db_pool))]
+#[get("/stats")]
+pub async fn stats(
+    query: web::Query<StatsQuery>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let query = query.into_inner();
+    let attrs: Vec<String> = match &query.attr {
+        Some(attr) => {
+            if !analytes_exist(&db_pool, std::slice::from_ref(attr))
+                .await
+                .map_err(e500)?
+            {
+                return Err(e400("Invalid attr."));
+            }
+            vec![attr.clone()]
+        }
+        None => get_analyte_data(&db_pool)
+            .await
+            .map_err(e500)?
+            .into_iter()
+            .map(|analyte| analyte.name)
+            .collect(),
+    };
+    if let Some(lake) = &query.lake {
+        if !lake_exists(&db_pool, lake).await.map_err(e500)? {
+            return Err(e400("Invalid lake."));
+        }
+    }
+
+    let substitution = query.substitution.unwrap_or_default();
+    let mut rows = get_stats_db(
+        &db_pool,
+        query.lake.as_deref(),
+        query.fish_type,
+        substitution,
+    )
+    .await
+    .map_err(e500)?;
+    let stats = attrs
+        .into_iter()
+        .map(|attr| match rows.iter().position(|row| row.attr == attr) {
+            Some(index) => {
+                let row = rows.swap_remove(index);
+                let percentile = |i: usize| row.percentiles.as_ref().map(|p| p[i]);
+                AttrStats {
+                    percentiles: Percentiles {
+                        p10: percentile(0),
+                        p25: percentile(1),
+                        p50: percentile(2),
+                        p75: percentile(3),
+                        p90: percentile(4),
+                    },
+                    median: percentile(2),
+                    attr: row.attr,
+                    count: row.count,
+                    below_detection: row.below_detection,
+                    mean: row.mean,
+                    std_dev: row.std_dev,
+                    min: row.min,
+                    max: row.max,
+                }
+            }
+            None => AttrStats {
+                attr,
+                ..Default::default()
+            },
+        })
+        .collect();
+
+    tracing::info!("Fish statistics have been queried from the db.");
+    Ok(HttpResponse::Ok().json(StatsResult {
+        lake: query.lake,
+        fish_type: query.fish_type,
+        substitution,
+        stats,
+    }))
+}
+
+/// Works out the statistics of every attribute in one pass over the samples'
+/// measurements.
+#[tracing::instrument(name = "Querying the database for fish statistics", skip(db_pool))]
+async fn get_stats_db(
+    db_pool: &PgPool,
+    lake: Option<&str>,
+    fish_type_id: Option<Uuid>,
+    substitution: Substitution,
+) -> Result<Vec<StatsRow>, sqlx::Error> {
+    sqlx::query_as!(
+        StatsRow,
+        r#"
+        SELECT
+            measurement.analyte as attr,
+            count(*) as "count!",
+            count(*) FILTER (WHERE NOT measurement.detected) as "below_detection!",
+            AVG(level.value) as mean,
+            stddev_samp(level.value) as std_dev,
+            MIN(level.value) as min,
+            MAX(level.value) as max,
+            percentile_cont(ARRAY[0.1, 0.25, 0.5, 0.75, 0.9])
+                WITHIN GROUP (ORDER BY level.value) as percentiles
+        FROM measurement
+        JOIN analyte ON analyte.name = measurement.analyte
+        JOIN fish ON fish.id = measurement.fish_id
+        CROSS JOIN LATERAL (
+            SELECT substituted_value(
+                measurement.detected,
+                measurement.value,
+                COALESCE(measurement.detection_limit, analyte.detection_limit),
+                $3
+            ) as value
+        ) AS level
+        WHERE ($1::TEXT IS NULL OR fish.lake = $1)
+            AND ($2::UUID IS NULL OR fish.fish_type_id = $2)
+        GROUP BY measurement.analyte;
+        "#,
+        lake,
+        fish_type_id,
+        substitution.factor()
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
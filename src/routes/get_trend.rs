@@ -0,0 +1,257 @@
+use crate::routes::{analytes_exist, lake_exists};
+use crate::utils::{e400, e500};
+use actix_web::{get, web, HttpResponse};
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const TREND_BUCKETS: [&str; 3] = ["year", "quarter", "month"];
+
+#[derive(serde::Deserialize, Debug)]
+pub struct FishTypeUuid {
+    uuid: Uuid,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct TrendQuery {
+    attr: String,
+    lake: Option<String>,
+    bucket: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct TrendPeriod {
+    /// The first day of the period.
+    pub period: NaiveDate,
+    pub mean: f64,
+    pub median: f64,
+    pub count: i64,
+}
+
+/// Least squares fit of the individual samples against their sample date.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Trend {
+    /// Change per year in the unit of the attribute, negative when going down.
+    /// None with fewer than two sample dates.
+    pub slope_per_year: Option<f64>,
+    /// The slope as a percentage of the mean of all samples.
+    pub percent_per_year: Option<f64>,
+    /// How much of the variation the trend explains, between 0 and 1.
+    pub r_squared: Option<f64>,
+    pub samples: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct TrendResult {
+    pub fish_type_id: Uuid,
+    pub name: String,
+    pub attr: String,
+    pub lake: Option<String>,
+    pub bucket: String,
+    pub periods: Vec<TrendPeriod>,
+    pub trend: Trend,
+}
+
+struct TrendRow {
+    slope_per_year: Option<f64>,
+    mean: Option<f64>,
+    r_squared: Option<f64>,
+    samples: i64,
+}
+
+/// Returns how an analyte of a fish type changed over time, optionally in a
+/// single lake. Samples are grouped by `bucket` (`year`, the default,
+/// `quarter` or `month`), and a linear trend is fitted through all of them.
+/// A sample's date is its `sample_date`, or else the day of its
+/// `date_sampled`; samples with neither are left out.
+///
+/// # Example
+///
+/// `.../fish_type/1fe5c906-d09d-11ed-afa1-0242ac120022/trend?attr=mercury&lake=Superior`
+///
+///```json
+/// {
+///     "fish_type_id": "1fe5c906-d09d-11ed-afa1-0242ac120022",
+///     "name": "Walleye",
+///     "attr": "mercury",
+///     "lake": "Superior",
+///     "bucket": "year",
+///     "periods": [
+///         { "period": "2019-01-01", "mean": 0.31, "median": 0.3, "count": 14 },
+///         { "period": "2020-01-01", "mean": 0.28, "median": 0.27, "count": 11 },
+///         // ...
+///     ],
+///     "trend": {
+///         "slope_per_year": -0.012,
+///         "percent_per_year": -4.2,
+///         "r_squared": 0.18,
+///         "samples": 61
+///     }
+/// }
+///```
+#[tracing::instrument(name = "Retrieving a fish type trend", skip(uuid, db_pool))]
+#[get("/fish_type/{uuid}/trend")]
+pub async fn fish_type_trend(
+    uuid: web::Path<FishTypeUuid>,
+    query: web::Query<TrendQuery>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let query = query.into_inner();
+    if !analytes_exist(&db_pool, std::slice::from_ref(&query.attr))
+        .await
+        .map_err(e500)?
+    {
+        return Err(e400("Invalid attr."));
+    }
+    let bucket = query.bucket.unwrap_or_else(|| "year".to_string());
+    if !TREND_BUCKETS.contains(&bucket.as_str()) {
+        return Err(e400("Invalid bucket."));
+    }
+    if let Some(lake) = &query.lake {
+        if !lake_exists(&db_pool, lake).await.map_err(e500)? {
+            return Err(e400("Invalid lake."));
+        }
+    }
+    let name = get_fish_type_name_db(&db_pool, uuid.uuid)
+        .await
+        .map_err(e500)?
+        .ok_or_else(|| e400("Unknown fish type."))?;
+
+    let periods = get_trend_periods_db(
+        &db_pool,
+        uuid.uuid,
+        &query.attr,
+        query.lake.as_deref(),
+        &bucket,
+    )
+    .await
+    .map_err(e500)?;
+    let trend = get_trend_db(&db_pool, uuid.uuid, &query.attr, query.lake.as_deref())
+        .await
+        .map_err(e500)?;
+    let percent_per_year = match (trend.slope_per_year, trend.mean) {
+        (Some(slope), Some(mean)) if mean != 0.0 => Some(slope / mean * 100.0),
+        _ => None,
+    };
+
+    tracing::info!("Trend data has been queried from the db.");
+    Ok(HttpResponse::Ok().json(TrendResult {
+        fish_type_id: uuid.uuid,
+        name,
+        attr: query.attr,
+        lake: query.lake,
+        bucket,
+        periods,
+        trend: Trend {
+            slope_per_year: trend.slope_per_year,
+            percent_per_year,
+            r_squared: trend.r_squared,
+            samples: trend.samples,
+        },
+    }))
+}
+
+#[tracing::instrument(name = "Querying the database for a fish type name", skip(db_pool))]
+async fn get_fish_type_name_db(
+    db_pool: &PgPool,
+    fish_type_id: Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT name FROM fish_type WHERE id = $1;
+        "#,
+        fish_type_id
+    )
+    .fetch_optional(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+#[tracing::instrument(name = "Querying the database for trend periods", skip(db_pool))]
+async fn get_trend_periods_db(
+    db_pool: &PgPool,
+    fish_type_id: Uuid,
+    attr: &str,
+    lake: Option<&str>,
+    bucket: &str,
+) -> Result<Vec<TrendPeriod>, sqlx::Error> {
+    sqlx::query_as!(
+        TrendPeriod,
+        r#"
+        SELECT
+            date_trunc($4, sample_date)::DATE as "period!",
+            AVG(value) as "mean!",
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY value) as "median!",
+            count(*) as "count!"
+        FROM (
+            SELECT
+                COALESCE(fish.sample_date, fish.date_sampled::DATE) as sample_date,
+                measurement.value
+            FROM measurement
+            JOIN fish ON fish.id = measurement.fish_id
+            WHERE fish.fish_type_id = $1
+                AND measurement.analyte = $2
+                AND ($3::TEXT IS NULL OR fish.lake = $3)
+                AND COALESCE(fish.sample_date, fish.date_sampled::DATE) IS NOT NULL
+        ) AS samples
+        GROUP BY 1
+        ORDER BY 1;
+        "#,
+        fish_type_id,
+        attr,
+        lake,
+        bucket
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+/// The slope is fitted against the sample date in years, so it comes out per
+/// year whatever the bucket.
+#[tracing::instrument(name = "Querying the database for a trend", skip(db_pool))]
+async fn get_trend_db(
+    db_pool: &PgPool,
+    fish_type_id: Uuid,
+    attr: &str,
+    lake: Option<&str>,
+) -> Result<TrendRow, sqlx::Error> {
+    sqlx::query_as!(
+        TrendRow,
+        r#"
+        SELECT
+            regr_slope(value, years) as slope_per_year,
+            AVG(value) as mean,
+            regr_r2(value, years) as r_squared,
+            count(*) as "samples!"
+        FROM (
+            SELECT
+                EXTRACT(
+                    EPOCH FROM COALESCE(fish.sample_date, fish.date_sampled::DATE)::TIMESTAMP
+                )::FLOAT8 / 31557600 AS years,
+                measurement.value
+            FROM measurement
+            JOIN fish ON fish.id = measurement.fish_id
+            WHERE fish.fish_type_id = $1
+                AND measurement.analyte = $2
+                AND ($3::TEXT IS NULL OR fish.lake = $3)
+                AND COALESCE(fish.sample_date, fish.date_sampled::DATE) IS NOT NULL
+        ) AS samples;
+        "#,
+        fish_type_id,
+        attr,
+        lake
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
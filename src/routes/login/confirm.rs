@@ -0,0 +1,81 @@
+use crate::authentication::hash_token;
+use crate::utils::e500;
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct TokenQuery {
+    token: String,
+}
+
+/// Verifies the user's email with the token from the link sent on register.
+/// Returns a 400 if the token is unknown, expired or has already been used.
+///
+/// # Example
+///
+/// `/v1/register/confirm?token=4f0e...`
+#[tracing::instrument(name = "Confirming a user's email", skip(query, db_pool))]
+pub async fn confirm_registration(
+    query: web::Query<TokenQuery>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    match verify_email(&db_pool, &hash_token(&query.token))
+        .await
+        .map_err(e500)?
+    {
+        Some(_) => Ok(HttpResponse::Ok()
+            .content_type("text/plain")
+            .body("Your email has been verified, you can head back to the app.")),
+        None => Ok(HttpResponse::BadRequest().finish()),
+    }
+}
+
+/// Uses the token and marks the user's email as verified, returning the user
+/// it belonged to if it was still valid.
+#[tracing::instrument(name = "Verifying an email in the database", skip(db_pool, token_hash))]
+async fn verify_email(db_pool: &PgPool, token_hash: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    let now = Utc::now();
+    let mut transaction = db_pool.begin().await?;
+
+    let Some(row) = sqlx::query!(
+        r#"
+        UPDATE email_verification_token
+        SET used_at = $2
+        WHERE token_hash = $1 AND used_at IS NULL AND expires_at > $2
+        RETURNING user_id;
+        "#,
+        token_hash,
+        now
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?
+    else {
+        return Ok(None);
+    };
+
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET email_verified_at = $2
+        WHERE id = $1 AND email_verified_at IS NULL;
+        "#,
+        row.user_id,
+        now
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    transaction.commit().await?;
+
+    Ok(Some(row.user_id))
+}
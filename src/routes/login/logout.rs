@@ -0,0 +1,22 @@
+use crate::authentication::{revoke_session, session_cookie, SessionUser};
+use crate::utils::e500;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+/// Ends the session the request was made with and clears the session cookie.
+#[tracing::instrument(name = "Logging out a user", skip(db_pool))]
+pub async fn logout(
+    user: SessionUser,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    if let Some(session_id) = user.session_id {
+        revoke_session(&db_pool, session_id, user.user_id)
+            .await
+            .map_err(e500)?;
+    }
+
+    let mut cookie = session_cookie("");
+    cookie.make_removal();
+
+    Ok(HttpResponse::Ok().cookie(cookie).finish())
+}
@@ -1,5 +1,11 @@
+mod confirm;
+mod logout;
 mod post;
+mod refresh;
 mod register;
 
+pub use confirm::*;
+pub use logout::*;
 pub use post::*;
+pub use refresh::*;
 pub use register::*;
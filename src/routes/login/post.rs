@@ -1,5 +1,11 @@
-use crate::authentication::{validate_credentials, AuthError, Credentials};
-use actix_web::{error::InternalError, web, HttpResponse};
+use super::SessionResponse;
+use crate::authentication::{
+    client_ip, create_session, is_email_verified, login_lockout, record_login_attempt,
+    session_cookie, validate_credentials, AuthError, Credentials, LoginOutcome, SessionDevice,
+};
+use crate::configuration::{LoginThrottleSettings, SessionSettings, UnverifiedUserPolicy};
+use actix_web::http::header::RETRY_AFTER;
+use actix_web::{error::InternalError, web, HttpRequest, HttpResponse};
 use chrono::Utc;
 use secrecy::Secret;
 use sqlx::PgPool;
@@ -15,10 +21,12 @@ pub struct FormData {
 pub struct LoginResponse {
     user_id: Uuid,
     is_admin: bool,
+    #[serde(flatten)]
+    session: SessionResponse,
     data: UserData,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, Default)]
 pub struct UserData {
     weight: Option<i16>,
     age: Option<i16>,
@@ -30,42 +38,118 @@ pub struct UserData {
     last_name: Option<String>,
 }
 
-/// Checks that the provided credentials are correct.
+/// Checks that the provided credentials are correct and starts a new session.
+/// The signed session token is returned in the body and set as the `session`
+/// cookie; it needs to be sent with every user-scoped request. The refresh token
+/// in the body can be exchanged at `/refresh` for a new session token once the
+/// current one expires. Depending on the `unverified_users` policy, users that
+/// haven't verified their email get a 403.
+///
+/// Too many failed attempts for an email, or from an IP address, lock logins
+/// out for a while. During a lockout a 429 is returned, with the seconds to
+/// wait in the `Retry-After` header.
 #[tracing::instrument(
     name="Logging in a user",
-    skip(form, db_pool),
+    skip(req, form, db_pool, session_settings, unverified_users, login_throttle),
     fields(
         subscriber_name = %form.email
         )
     )]
 pub async fn login(
+    req: HttpRequest,
     form: web::Form<FormData>,
     db_pool: web::Data<PgPool>,
+    session_settings: web::Data<SessionSettings>,
+    unverified_users: web::Data<UnverifiedUserPolicy>,
+    login_throttle: web::Data<LoginThrottleSettings>,
 ) -> Result<HttpResponse, InternalError<LoginError>> {
+    let email = form.0.email;
+    let ip_address = client_ip(&req).map(|ip| ip.to_string());
+    let ip_address = ip_address.as_deref();
+
+    let lockout = login_lockout(&db_pool, &email, ip_address, &login_throttle)
+        .await
+        .map_err(|e| {
+            InternalError::from_response(
+                LoginError::UnexpectedError(e.into()),
+                HttpResponse::InternalServerError().finish(),
+            )
+        })?;
+    if let Some(wait) = lockout {
+        record_attempt(&db_pool, &email, ip_address, LoginOutcome::LockedOut).await;
+        // Round up so clients don't retry a moment too early.
+        let retry_after = (wait.num_milliseconds() + 999) / 1000;
+        return Err(InternalError::from_response(
+            LoginError::TooManyAttempts,
+            HttpResponse::TooManyRequests()
+                .insert_header((RETRY_AFTER, retry_after.to_string()))
+                .finish(),
+        ));
+    }
+
     let credentials = Credentials {
-        email: form.0.email,
+        email: email.clone(),
         password: form.0.password,
     };
     tracing::Span::current().record("username", tracing::field::display(&credentials.email));
     match validate_credentials(credentials, &db_pool).await {
-        Ok(user_data) => {
-            tracing::Span::current().record("user_id", tracing::field::display(&user_data.0));
-            match get_user_db(&db_pool, user_data.0).await {
-                Ok(data) => Ok(HttpResponse::Ok().json(LoginResponse {
-                    user_id: user_data.0,
-                    is_admin: user_data.1,
-                    data,
-                })),
-                // In the error case still allow the user to login.
-                Err(e) => {
-                    tracing::error!("User was validated, but unable to retrieve their data: {e}");
-                    Ok(HttpResponse::Ok().json(user_data))
+        Ok((user_id, is_admin)) => {
+            tracing::Span::current().record("user_id", tracing::field::display(&user_id));
+            if !unverified_users.can_login {
+                let is_verified = is_email_verified(&db_pool, user_id).await.map_err(|e| {
+                    InternalError::from_response(
+                        LoginError::UnexpectedError(e.into()),
+                        HttpResponse::InternalServerError().finish(),
+                    )
+                })?;
+                if !is_verified {
+                    record_attempt(&db_pool, &email, ip_address, LoginOutcome::EmailNotVerified)
+                        .await;
+                    return Err(InternalError::from_response(
+                        LoginError::EmailNotVerified,
+                        HttpResponse::Forbidden().finish(),
+                    ));
                 }
             }
+            let device = SessionDevice::from_request(&req);
+            let session = create_session(&db_pool, user_id, &device, &session_settings)
+                .await
+                .map_err(|e| {
+                    InternalError::from_response(
+                        LoginError::UnexpectedError(e),
+                        HttpResponse::InternalServerError().finish(),
+                    )
+                })?;
+            record_attempt(&db_pool, &email, ip_address, LoginOutcome::Success).await;
+            let data = get_user_db(&db_pool, user_id)
+                .await
+                // In the error case still allow the user to login.
+                .unwrap_or_else(|e| {
+                    tracing::error!("User was validated, but unable to retrieve their data: {e}");
+                    UserData::default()
+                });
+            Ok(HttpResponse::Ok()
+                .cookie(session_cookie(&session.token))
+                .json(LoginResponse {
+                    user_id,
+                    is_admin,
+                    session: session.into(),
+                    data,
+                }))
         }
         Err(e) => {
             let e = match e {
-                AuthError::InvalidCredentials(_) => LoginError::AuthError(e.into()),
+                AuthError::InvalidCredentials(_) => {
+                    record_attempt(&db_pool, &email, ip_address, LoginOutcome::Failure).await;
+                    LoginError::AuthError(e.into())
+                }
+                AuthError::AccountDisabled => {
+                    record_attempt(&db_pool, &email, ip_address, LoginOutcome::Failure).await;
+                    return Err(InternalError::from_response(
+                        LoginError::AccountDisabled,
+                        HttpResponse::Forbidden().finish(),
+                    ));
+                }
                 AuthError::UnexpectedError(_) => LoginError::UnexpectedError(e.into()),
             };
             let response = HttpResponse::Unauthorized().finish();
@@ -74,6 +158,18 @@ pub async fn login(
     }
 }
 
+/// A failure to record the attempt shouldn't stop the user from logging in.
+async fn record_attempt(
+    db_pool: &PgPool,
+    email: &str,
+    ip_address: Option<&str>,
+    outcome: LoginOutcome,
+) {
+    if let Err(e) = record_login_attempt(db_pool, email, ip_address, outcome).await {
+        tracing::error!("Unable to record the login attempt: {e}");
+    }
+}
+
 #[tracing::instrument(name = "Getting user details from the db.", skip(db_pool, user_id))]
 async fn get_user_db(db_pool: &PgPool, user_id: Uuid) -> Result<UserData, sqlx::Error> {
     let user_data = sqlx::query_as!(
@@ -119,6 +215,12 @@ async fn get_user_db(db_pool: &PgPool, user_id: Uuid) -> Result<UserData, sqlx::
 pub enum LoginError {
     #[error("Authentication failed")]
     AuthError(#[source] anyhow::Error),
+    #[error("Too many failed login attempts")]
+    TooManyAttempts,
+    #[error("The email address has not been verified")]
+    EmailNotVerified,
+    #[error("The account has been disabled")]
+    AccountDisabled,
     #[error("Something went wrong")]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -0,0 +1,45 @@
+use crate::authentication::{refresh_session, session_cookie, SessionError, SessionToken};
+use crate::configuration::SessionSettings;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(serde::Deserialize)]
+pub struct FormData {
+    refresh_token: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct SessionResponse {
+    session_token: String,
+    session_expires_at: DateTime<Utc>,
+    refresh_token: String,
+    refresh_expires_at: DateTime<Utc>,
+}
+
+impl From<SessionToken> for SessionResponse {
+    fn from(session: SessionToken) -> Self {
+        Self {
+            session_token: session.token,
+            session_expires_at: session.expires_at,
+            refresh_token: session.refresh_token,
+            refresh_expires_at: session.refresh_expires_at,
+        }
+    }
+}
+
+/// Exchanges a refresh token for a new session token, so clients can stay
+/// logged in without storing the user's password. The refresh token can only
+/// be used once; a new one is returned alongside the session token.
+#[tracing::instrument(name = "Refreshing a session", skip(form, db_pool, session_settings))]
+pub async fn refresh(
+    form: web::Form<FormData>,
+    db_pool: web::Data<PgPool>,
+    session_settings: web::Data<SessionSettings>,
+) -> Result<HttpResponse, SessionError> {
+    let session = refresh_session(&db_pool, &form.refresh_token, &session_settings).await?;
+
+    Ok(HttpResponse::Ok()
+        .cookie(session_cookie(&session.token))
+        .json(SessionResponse::from(session)))
+}
@@ -1,22 +1,37 @@
-use crate::authentication::compute_password_hash;
+use crate::authentication::{compute_password_hash, generate_token, hash_token};
+use crate::email_client::{Email, EmailClient};
+use crate::startup::ApplicationBaseUrl;
 use crate::utils::e500;
 use actix_web::{web, HttpResponse};
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use secrecy::{ExposeSecret, Secret};
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
+/// How long the link in the verification email works for.
+const VERIFICATION_TOKEN_TTL_HOURS: i64 = 48;
+
 #[derive(serde::Deserialize)]
 pub struct FormData {
     email: String,
     password: Secret<String>,
 }
 
+/// Why a verification email is sent, which picks its subject and greeting.
+#[derive(Clone, Copy, Debug)]
+pub enum VerificationReason {
+    Registered,
+    Resent,
+    EmailChanged,
+}
+
 /// Adds a new user to the database and returns a 200 OK response on success.
-/// Expects the user's email and password to be included in the form data.
+/// Expects the user's email and password to be included in the form data. A
+/// link to verify the email address is sent to the user; returns a 400 if the
+/// email isn't a valid address and a 409 if it's already registered.
 #[tracing::instrument(
     name="Registering a new user",
-    skip(form, db_pool),
+    skip(form, db_pool, email_client, base_url),
     fields(
         subscriber_name = %form.email
         )
@@ -24,28 +39,169 @@ pub struct FormData {
 pub async fn register(
     form: web::Form<FormData>,
     db_pool: web::Data<PgPool>,
+    email_client: web::Data<dyn EmailClient>,
+    base_url: web::Data<ApplicationBaseUrl>,
 ) -> Result<HttpResponse, actix_web::Error> {
     let email = &form.email;
+    if email.parse::<lettre::Address>().is_err() {
+        return Ok(HttpResponse::BadRequest().finish());
+    }
     let password = &form.password;
     let password_hash = compute_password_hash(password.clone()).map_err(e500)?;
-    match insert_user(&db_pool, email.to_string(), password_hash).await {
+
+    let mut transaction = db_pool.begin().await.map_err(e500)?;
+    let user_id = match insert_user(&mut transaction, email.to_string(), password_hash).await {
         Ok(user_id) => {
             tracing::info!("New user details have been saved.");
-            Ok(HttpResponse::Ok().json(user_id))
+            user_id
+        }
+        Err(e)
+            if e.as_database_error()
+                .is_some_and(|e| e.is_unique_violation()) =>
+        {
+            tracing::warn!("The email is already registered.");
+            return Ok(HttpResponse::Conflict().finish());
         }
         Err(e) => {
             tracing::error!("Failed to execute query: {:?}", e);
-            Ok(HttpResponse::InternalServerError().finish())
+            return Ok(HttpResponse::InternalServerError().finish());
         }
-    }
+    };
+
+    // Only keep the user if the email could be sent, so they can try again.
+    send_verification_email(
+        &mut transaction,
+        email_client.as_ref(),
+        &base_url.0,
+        user_id,
+        email,
+        VerificationReason::Registered,
+    )
+    .await
+    .map_err(e500)?;
+    transaction.commit().await.map_err(e500)?;
+
+    Ok(HttpResponse::Ok().json(user_id))
+}
+
+#[derive(serde::Deserialize)]
+pub struct ResendFormData {
+    email: String,
+}
+
+/// Sends a new link to verify the email address, the links sent before stop
+/// working. Responds with a 200 OK whether or not the email belongs to a user
+/// that still has to verify it, so it can't be used to find out who has an
+/// account.
+#[tracing::instrument(
+    name = "Resending a verification email",
+    skip(form, db_pool, email_client, base_url),
+    fields(
+        subscriber_name = %form.email
+        )
+    )]
+pub async fn resend_verification(
+    form: web::Form<ResendFormData>,
+    db_pool: web::Data<PgPool>,
+    email_client: web::Data<dyn EmailClient>,
+    base_url: web::Data<ApplicationBaseUrl>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let Some(user_id) = get_unverified_user_id(&db_pool, &form.email)
+        .await
+        .map_err(e500)?
+    else {
+        tracing::info!("Verification requested for an unknown or verified email.");
+        return Ok(HttpResponse::Ok().finish());
+    };
+
+    let mut transaction = db_pool.begin().await.map_err(e500)?;
+    send_verification_email(
+        &mut transaction,
+        email_client.as_ref(),
+        &base_url.0,
+        user_id,
+        &form.email,
+        VerificationReason::Resent,
+    )
+    .await
+    .map_err(e500)?;
+    transaction.commit().await.map_err(e500)?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Creates a new verification token for the user and emails them the link to
+/// use it. The token is only kept if the transaction is committed.
+#[tracing::instrument(
+    name = "Sending a verification email",
+    skip(transaction, email_client, base_url, email)
+)]
+pub async fn send_verification_email(
+    transaction: &mut Transaction<'_, Postgres>,
+    email_client: &dyn EmailClient,
+    base_url: &str,
+    user_id: Uuid,
+    email: &str,
+    reason: VerificationReason,
+) -> Result<(), anyhow::Error> {
+    let token = generate_token();
+    store_token(transaction, user_id, &hash_token(&token)).await?;
+
+    let link = format!("{}/v1/register/confirm?token={}", base_url, token);
+    let (subject, greeting) = match reason {
+        VerificationReason::Registered => ("Welcome to Fishy Edge!", "Thanks for signing up! "),
+        VerificationReason::Resent => ("Verify your Fishy Edge email", ""),
+        VerificationReason::EmailChanged => (
+            "Verify your new Fishy Edge email",
+            "The email of your account has been changed. ",
+        ),
+    };
+
+    email_client
+        .send_email(Email {
+            to: email.to_string(),
+            subject: subject.to_string(),
+            text_body: format!(
+                "{greeting}Open the link below to verify your email address:\n\n\
+                {link}\n\n\
+                The link expires in {VERIFICATION_TOKEN_TTL_HOURS} hours."
+            ),
+        })
+        .await
+}
+
+#[tracing::instrument(
+    name = "Querying the database for an unverified user",
+    skip(db_pool, email)
+)]
+async fn get_unverified_user_id(
+    db_pool: &PgPool,
+    email: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT id
+        FROM users
+        WHERE email = $1 AND email_verified_at IS NULL;
+        "#,
+        email
+    )
+    .fetch_optional(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.map(|row| row.id))
 }
 
 #[tracing::instrument(
     name = "Saving new user details to the db.",
-    skip(email, password_hash, db_pool)
+    skip(email, password_hash, transaction)
 )]
 async fn insert_user(
-    db_pool: &PgPool,
+    transaction: &mut Transaction<'_, Postgres>,
     email: String,
     password_hash: Secret<String>,
 ) -> Result<Uuid, sqlx::Error> {
@@ -61,7 +217,7 @@ async fn insert_user(
         Utc::now(),
         Utc::now(),
     )
-    .execute(db_pool)
+    .execute(&mut **transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
@@ -70,3 +226,50 @@ async fn insert_user(
 
     Ok(user_id)
 }
+
+/// Saves the new token, invalidating any the user hasn't used yet.
+#[tracing::instrument(
+    name = "Saving an email verification token",
+    skip(transaction, token_hash)
+)]
+async fn store_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    token_hash: &str,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+    sqlx::query!(
+        r#"
+        UPDATE email_verification_token
+        SET used_at = $2
+        WHERE user_id = $1 AND used_at IS NULL;
+        "#,
+        user_id,
+        now
+    )
+    .execute(&mut **transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO email_verification_token (token_hash, user_id, created_at, expires_at)
+        VALUES ($1, $2, $3, $4);
+        "#,
+        token_hash,
+        user_id,
+        now,
+        now + Duration::hours(VERIFICATION_TOKEN_TTL_HOURS)
+    )
+    .execute(&mut **transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
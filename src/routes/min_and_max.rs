@@ -1,18 +1,22 @@
-use crate::routes::VALID_LAKES;
+use crate::routes::{analytes_exist, lake_exists, Substitution};
 use actix_web::{get, web, HttpResponse};
-use sqlx::{PgPool, Postgres};
+use sqlx::PgPool;
 
 #[derive(serde::Deserialize)]
 pub struct MinMaxQuery {
     lake: Option<String>,
     attr: String,
+    substitution: Option<Substitution>,
 }
 
-#[derive(serde::Serialize, sqlx::FromRow, Clone)]
+#[derive(serde::Serialize, Clone)]
 struct Fish {
     name: String,
     anishinaabe_name: Option<String>,
     value: Option<f32>,
+    /// For averages, how many of the samples were non-detects.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    censored: Option<i64>,
 }
 
 #[derive(serde::Serialize)]
@@ -21,12 +25,10 @@ struct Data {
     max: Option<Fish>,
 }
 
-// Whitelist options
-const VALID_ATTRS: [&str; 4] = ["protein", "pcb", "mercury", "omega_3_ratio"];
-
 /// Returns a json of the fish with the min and max value for a given lake and
 /// attribute. If no lake specified, will return min and max values for all fish
-/// averages.
+/// averages. Either way non-detects are counted as set by `substitution` (see
+/// `fish_avg`).
 ///
 /// # Example
 ///
@@ -53,18 +55,33 @@ pub async fn min_and_max(
     db_pool: web::Data<PgPool>,
 ) -> HttpResponse {
     let attr = query.attr.as_str();
-    if !VALID_ATTRS.contains(&attr) {
-        tracing::warn!("Invalid attr supplied.");
-        return HttpResponse::BadRequest().finish();
+    match analytes_exist(&db_pool, std::slice::from_ref(&query.attr)).await {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::warn!("Invalid attr supplied.");
+            return HttpResponse::BadRequest().finish();
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
     }
+    let substitution = query.substitution.unwrap_or_default();
     match &query.lake {
         Some(lake) => {
             let lake = lake.as_str();
-            if !VALID_LAKES.contains(&lake) {
-                tracing::warn!("Invalid lake supplied.");
-                return HttpResponse::BadRequest().finish();
+            match lake_exists(&db_pool, lake).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::warn!("Invalid lake supplied.");
+                    return HttpResponse::BadRequest().finish();
+                }
+                Err(e) => {
+                    tracing::error!("Failed to execute query: {:?}", e);
+                    return HttpResponse::InternalServerError().finish();
+                }
             }
-            match get_min_and_max_data(lake, attr, &db_pool).await {
+            match get_min_and_max_data(lake, attr, substitution, &db_pool).await {
                 Ok(data) => {
                     tracing::info!("Min and max data has been queried from the db.");
                     HttpResponse::Ok().json(data)
@@ -75,7 +92,7 @@ pub async fn min_and_max(
                 }
             }
         }
-        None => match get_min_and_max_of_avg_data(attr, &db_pool).await {
+        None => match get_min_and_max_of_avg_data(attr, substitution, &db_pool).await {
             Ok(data) => {
                 tracing::info!("Avg min and max data has been queried from the db.");
                 HttpResponse::Ok().json(data)
@@ -92,32 +109,38 @@ pub async fn min_and_max(
 async fn get_min_and_max_data(
     lake: &str,
     attr: &str,
+    substitution: Substitution,
     db_pool: &PgPool,
 ) -> Result<Data, sqlx::Error> {
-    let mut query: sqlx::QueryBuilder<Postgres> =
-        sqlx::QueryBuilder::new("SELECT fish_type.name, fish_type.anishinaabe_name, ");
-    query.push(attr);
-    query.push(" as value FROM fish JOIN fish_type ON fish.fish_type_id=fish_type.id WHERE (");
-    query.push(attr);
-    query.push("=(SELECT MAX(");
-    query.push(attr);
-    query.push(") FROM fish WHERE lake=");
-    query.push_bind(lake);
-    query.push(") AND fish.lake=");
-    query.push_bind(lake);
-    query.push(") OR (");
-    query.push(attr);
-    query.push("=(SELECT MIN(");
-    query.push(attr);
-    query.push(") FROM fish where lake=");
-    query.push_bind(lake);
-    query.push(") AND fish.lake=");
-    query.push_bind(lake);
-    query.push(") ORDER BY ");
-    query.push(attr);
-    query.push(";");
-    let stream = query.build_query_as::<Fish>();
-    let data = stream.fetch_all(db_pool).await.map_err(|e| {
+    let data = sqlx::query_as!(
+        Fish,
+        r#"
+        SELECT
+            fish_type.name,
+            fish_type.anishinaabe_name,
+            CAST(
+                substituted_value(
+                    measurement.detected,
+                    measurement.value,
+                    COALESCE(measurement.detection_limit, analyte.detection_limit),
+                    $3
+                ) AS FLOAT4
+            ) AS value,
+            NULL::INT8 as censored
+        FROM measurement
+        JOIN analyte ON measurement.analyte = analyte.name
+        JOIN fish ON measurement.fish_id = fish.id
+        JOIN fish_type ON fish.fish_type_id = fish_type.id
+        WHERE measurement.analyte = $1 AND fish.lake = $2
+        ORDER BY value;
+        "#,
+        attr,
+        lake,
+        substitution.factor()
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
@@ -131,16 +154,42 @@ async fn get_min_and_max_data(
 }
 
 #[tracing::instrument(name = "Querying the database", skip(db_pool))]
-async fn get_min_and_max_of_avg_data(attr: &str, db_pool: &PgPool) -> Result<Data, sqlx::Error> {
-    let mut query: sqlx::QueryBuilder<Postgres> =
-        sqlx::QueryBuilder::new("SELECT fish_type.name, fish_type.anishinaabe_name, CAST(AVG(");
-    query.push(attr);
-    query.push(
-        ") AS FLOAT4) AS value FROM fish JOIN fish_type ON fish.fish_type_id=fish_type.id
-        GROUP BY fish_type.name, fish_type.anishinaabe_name ORDER BY value;",
-    );
-    let stream = query.build_query_as::<Fish>();
-    let data = stream.fetch_all(db_pool).await.map_err(|e| {
+async fn get_min_and_max_of_avg_data(
+    attr: &str,
+    substitution: Substitution,
+    db_pool: &PgPool,
+) -> Result<Data, sqlx::Error> {
+    let data = sqlx::query_as!(
+        Fish,
+        r#"
+        SELECT
+            fish_type.name,
+            fish_type.anishinaabe_name,
+            CAST(
+                AVG(
+                    substituted_value(
+                        measurement.detected,
+                        measurement.value,
+                        COALESCE(measurement.detection_limit, analyte.detection_limit),
+                        $2
+                    )
+                ) AS FLOAT4
+            ) AS value,
+            count(*) FILTER (WHERE NOT measurement.detected) as censored
+        FROM measurement
+        JOIN analyte ON measurement.analyte = analyte.name
+        JOIN fish ON measurement.fish_id = fish.id
+        JOIN fish_type ON fish.fish_type_id = fish_type.id
+        WHERE measurement.analyte = $1
+        GROUP BY fish_type.name, fish_type.anishinaabe_name
+        ORDER BY value;
+        "#,
+        attr,
+        substitution.factor()
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
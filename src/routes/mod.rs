@@ -1,15 +1,26 @@
 use uuid::Uuid;
 
 mod admin;
+mod advice;
 pub mod everything;
 mod favorite;
+mod get_analytes;
+mod get_compare;
+mod get_export;
 mod get_fish;
 mod get_fish_avg;
 mod get_fish_avgs;
 mod get_fishs;
+mod get_lakes;
+mod get_length_classes;
+mod get_rankings;
+mod get_sites_near;
+mod get_stats;
+mod get_trend;
 mod health_check;
 mod login;
 pub mod min_and_max;
+mod password_reset;
 pub mod presign_s3;
 pub mod recipe;
 pub mod recipes;
@@ -18,23 +29,45 @@ mod unfavorite;
 mod user;
 
 pub use admin::{
-    create_fish_type, delete_fish, delete_recipe, get_analytics, new_fish, new_recipe,
-    read_all_fish_types, read_fish_type, update_fish, update_fish_type, update_fish_type_image,
-    update_recipe, update_recipe_image,
+    create_analyte, create_api_key, create_data_source, create_fish_type, create_lake,
+    create_sampling_site, delete_data_source, delete_fish, delete_lake, delete_recipe,
+    delete_sampling_site, disable_user, enable_user, get_analytics, import_fish,
+    import_fish_recipes, import_fish_types, import_recipes, new_fish, new_recipe,
+    read_all_api_keys, read_all_fish_types, read_all_login_attempts, read_all_users,
+    read_audit_log, read_fish_type, read_user, reset_user_password, revoke_api_key, update_analyte,
+    update_data_source, update_fish, update_fish_type, update_fish_type_image, update_lake,
+    update_length_classes, update_recipe, update_recipe_image, update_sampling_site,
+    update_user_roles, MAX_IMPORT_SIZE,
 };
+pub use advice::{fish_advice, fish_avg_advice, get_profile, get_reference_doses};
 pub use everything::*;
 pub use favorite::{favorite_fish, favorite_recipe, favorites};
+pub use get_analytes::{analytes, analytes_exist, get_analyte_data};
+pub use get_compare::{compare, AttrRanks, ComparedFish, ComparedMeasurement, Comparison};
+pub use get_export::{export_fish, ExportFormat};
 pub use get_fish::{fish, get_is_favorite, FishResponse};
 pub use get_fish_avg::fish_avg;
 pub use get_fish_avgs::fish_avgs;
 pub use get_fishs::fishs;
+pub use get_lakes::{lake_exists, lakes};
+pub use get_length_classes::{get_length_class_data, length_class_exists, length_classes};
+pub use get_rankings::{rankings, RankedFish, Rankings};
+pub use get_sites_near::{sites_near, NearbySite, SiteSpecies};
+pub use get_stats::{stats, AttrStats, Percentiles, StatsResult};
+pub use get_trend::{fish_type_trend, Trend, TrendPeriod, TrendResult};
 pub use health_check::*;
-pub use login::{login, register};
+pub use login::{
+    confirm_registration, login, logout, refresh, register, resend_verification,
+    send_verification_email, VerificationReason,
+};
 pub use min_and_max::*;
+pub use password_reset::{
+    confirm_password_reset, request_password_reset, send_password_reset_email,
+};
 pub use presign_s3::*;
 pub use recipe::*;
 pub use recipes::*;
-pub use search::{search, SearchResult};
+pub use search::{search, SearchHit, SearchResult};
 pub use unfavorite::{unfavorite_fish, unfavorite_recipe};
 pub use user::*;
 
@@ -55,6 +88,8 @@ pub struct Fish {
     pub protein: Option<f32>,
     pub lake: String,
     pub about: String,
+    pub length_cm: Option<f64>,
+    pub weight_g: Option<f64>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -78,4 +113,94 @@ pub struct FishType {
     pub about: String,
 }
 
-pub const VALID_LAKES: [&str; 5] = ["Store", "Superior", "Huron", "Michigan", "Inland"];
+/// A contaminant or nutrient that can be measured in a fish sample.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Analyte {
+    pub id: Uuid,
+    pub name: String,
+    pub display_name: String,
+    /// Either `contaminant` or `nutrient`.
+    pub kind: String,
+    pub unit: String,
+    /// µg/kg/day, for children and anyone who is or plans to become pregnant.
+    pub reference_dose: Option<f64>,
+    /// µg/kg/day, for everyone else when it's less strict.
+    pub general_reference_dose: Option<f64>,
+    pub detection_limit: Option<f64>,
+}
+
+/// The level of an analyte in a sample.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Measurement {
+    pub analyte: String,
+    pub display_name: String,
+    pub kind: String,
+    pub unit: String,
+    /// 0 when not detected.
+    pub value: f64,
+    pub detected: bool,
+    /// The sample's, or else the analyte's.
+    pub detection_limit: Option<f64>,
+}
+
+/// A length range of a fish type that advice can be given for.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct LengthClass {
+    pub name: String,
+    pub min_length_cm: f64,
+    /// Exclusive, None for no upper bound.
+    pub max_length_cm: Option<f64>,
+}
+
+/// Where a sample's figures come from, for citing them.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct DataSource {
+    pub id: Uuid,
+    pub agency: String,
+    pub study: Option<String>,
+    pub url: Option<String>,
+    pub lab_method: Option<String>,
+    pub collection_year: Option<i32>,
+    pub license: Option<String>,
+}
+
+/// How averages count non-detects, i.e. measurements below the detection
+/// limit. Non-detects without a known detection limit always count as 0.
+/// Endpoints without a `substitution` parameter use the default, through the
+/// `substituted_measurement` SQL function.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Substitution {
+    /// As 0, what they are stored as.
+    #[default]
+    #[serde(rename = "zero")]
+    Zero,
+    /// As half the detection limit.
+    #[serde(rename = "half_dl")]
+    HalfDetectionLimit,
+    /// As the detection limit.
+    #[serde(rename = "dl")]
+    DetectionLimit,
+}
+
+impl Substitution {
+    /// What the detection limit is multiplied by.
+    pub fn factor(&self) -> f64 {
+        match self {
+            Substitution::Zero => 0.0,
+            Substitution::HalfDetectionLimit => 0.5,
+            Substitution::DetectionLimit => 1.0,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Lake {
+    pub id: Uuid,
+    pub name: String,
+    pub anishinaabe_name: Option<String>,
+    pub region: Option<String>,
+    pub description: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub image_url: Option<String>,
+}
@@ -0,0 +1,67 @@
+use crate::authentication::{change_password, hash_token, revoke_user_sessions};
+use crate::utils::e500;
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use secrecy::{ExposeSecret, Secret};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct FormData {
+    token: String,
+    new_password: Secret<String>,
+    new_password_check: Secret<String>,
+}
+
+/// Sets a new password using the token from the password reset email. Returns
+/// a 400 if the passwords don't match, or the token is unknown, expired or has
+/// already been used. Every session of the user is revoked.
+#[tracing::instrument(name = "Confirming a password reset", skip(form, db_pool))]
+pub async fn confirm_password_reset(
+    form: web::Form<FormData>,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    if form.new_password.expose_secret() != form.new_password_check.expose_secret() {
+        return Ok(HttpResponse::BadRequest().finish());
+    }
+
+    let Some(user_id) = use_token(&db_pool, &hash_token(&form.token))
+        .await
+        .map_err(e500)?
+    else {
+        return Ok(HttpResponse::BadRequest().finish());
+    };
+
+    change_password(user_id, form.0.new_password, &db_pool)
+        .await
+        .map_err(e500)?;
+    revoke_user_sessions(&db_pool, user_id)
+        .await
+        .map_err(e500)?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Marks the token as used, returning who it belongs to if it was still valid.
+#[tracing::instrument(name = "Using a password reset token", skip(db_pool, token_hash))]
+async fn use_token(db_pool: &PgPool, token_hash: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    let now = Utc::now();
+    let row = sqlx::query!(
+        r#"
+        UPDATE password_reset_token
+        SET used_at = $2
+        WHERE token_hash = $1 AND used_at IS NULL AND expires_at > $2
+        RETURNING user_id;
+        "#,
+        token_hash,
+        now
+    )
+    .fetch_optional(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.map(|row| row.user_id))
+}
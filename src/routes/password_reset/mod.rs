@@ -0,0 +1,5 @@
+mod confirm;
+mod request;
+
+pub use confirm::confirm_password_reset;
+pub use request::{request_password_reset, send_password_reset_email};
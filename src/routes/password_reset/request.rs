@@ -0,0 +1,143 @@
+use crate::authentication::{generate_token, hash_token};
+use crate::email_client::{Email, EmailClient};
+use crate::utils::e500;
+use actix_web::{web, HttpResponse};
+use chrono::{Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+/// How long a password reset token can be used for.
+const RESET_TOKEN_TTL_MINUTES: i64 = 60;
+
+#[derive(serde::Deserialize)]
+pub struct FormData {
+    email: String,
+}
+
+/// Emails a single use password reset token to the user. Responds with a 200 OK
+/// whether or not the email belongs to a user, so it can't be used to find out
+/// who has an account.
+#[tracing::instrument(
+    name = "Requesting a password reset",
+    skip(form, db_pool, email_client),
+    fields(
+        subscriber_name = %form.email
+        )
+    )]
+pub async fn request_password_reset(
+    form: web::Form<FormData>,
+    db_pool: web::Data<PgPool>,
+    email_client: web::Data<dyn EmailClient>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let Some(user_id) = get_user_id(&db_pool, &form.email).await.map_err(e500)? else {
+        tracing::info!("Password reset requested for an unknown email.");
+        return Ok(HttpResponse::Ok().finish());
+    };
+
+    let mut transaction = db_pool.begin().await.map_err(e500)?;
+    send_password_reset_email(
+        &mut transaction,
+        email_client.as_ref(),
+        user_id,
+        form.0.email,
+    )
+    .await
+    .map_err(e500)?;
+    transaction.commit().await.map_err(e500)?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Creates a new reset token for the user and emails it to them.
+#[tracing::instrument(
+    name = "Sending a password reset email",
+    skip(transaction, email_client, email)
+)]
+pub async fn send_password_reset_email(
+    transaction: &mut Transaction<'_, Postgres>,
+    email_client: &dyn EmailClient,
+    user_id: Uuid,
+    email: String,
+) -> Result<(), anyhow::Error> {
+    let token = generate_token();
+    store_token(transaction, user_id, &hash_token(&token)).await?;
+
+    email_client
+        .send_email(Email {
+            to: email,
+            subject: "Reset your Fishy Edge password".to_string(),
+            text_body: format!(
+                "Someone asked to reset the password for your Fishy Edge account. \
+                If it was you, enter this code in the app to choose a new password:\n\n\
+                {token}\n\n\
+                The code expires in {RESET_TOKEN_TTL_MINUTES} minutes. \
+                If you didn't ask to reset your password you can ignore this email."
+            ),
+        })
+        .await
+}
+
+#[tracing::instrument(name = "Querying the database for a user id", skip(db_pool, email))]
+async fn get_user_id(db_pool: &PgPool, email: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT id
+        FROM users
+        WHERE email = $1;
+        "#,
+        email
+    )
+    .fetch_optional(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.map(|row| row.id))
+}
+
+/// Saves the new token, invalidating any the user hasn't used yet.
+#[tracing::instrument(name = "Saving a password reset token", skip(transaction, token_hash))]
+async fn store_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    token_hash: &str,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+
+    sqlx::query!(
+        r#"
+        UPDATE password_reset_token
+        SET used_at = $2
+        WHERE user_id = $1 AND used_at IS NULL;
+        "#,
+        user_id,
+        now
+    )
+    .execute(&mut **transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO password_reset_token (token_hash, user_id, created_at, expires_at)
+        VALUES ($1, $2, $3, $4);
+        "#,
+        token_hash,
+        user_id,
+        now,
+        now + Duration::minutes(RESET_TOKEN_TTL_MINUTES)
+    )
+    .execute(&mut **transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
-use crate::{routes::Recipe, utils::get_optional_user_id};
-use actix_web::{get, web, HttpRequest, HttpResponse};
+use crate::{authentication::OptionalSessionUser, routes::Recipe};
+use actix_web::{get, web, HttpResponse};
 use anyhow::Result;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -42,10 +42,9 @@ pub struct RecipeResponse {
 pub async fn recipe(
     uuid: web::Path<RecipeUuid>,
     db_pool: web::Data<PgPool>,
-    req: HttpRequest,
+    user: OptionalSessionUser,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_optional_user_id(req)?;
-    match get_recipe_data(&db_pool, uuid.uuid, user_id).await {
+    match get_recipe_data(&db_pool, uuid.uuid, user.user_id()).await {
         Ok(data) => {
             tracing::info!("Recipe data has been queried from the db.");
             Ok(HttpResponse::Ok().json(data))
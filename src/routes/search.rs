@@ -1,97 +1,174 @@
-use crate::routes::FishType;
 use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+/// Longest query accepted, anything longer is cut off.
+const MAX_QUERY_CHARS: usize = 200;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct SearchQuery {
+    q: Option<String>,
+    limit: Option<i64>,
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
-pub struct RecipeData {
-    recipe_id: Uuid,
-    recipe_name: String,
+pub struct SearchHit {
+    /// Either `fish_type` or `recipe`.
+    #[serde(rename = "type")]
+    pub result_type: String,
+    pub id: Uuid,
+    pub name: String,
+    pub anishinaabe_name: Option<String>,
+    /// A snippet of the description, ingredients or steps with the matched
+    /// words wrapped in `<mark>` tags. The text itself is HTML escaped, so the
+    /// snippet can be rendered as is.
+    pub highlight: Option<String>,
+    pub rank: f32,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct SearchResult {
-    pub fishs: Vec<FishType>,
-    pub recipes: Vec<RecipeData>,
+    pub query: String,
+    pub results: Vec<SearchHit>,
 }
 
-/// Returns a JSON of all store fish and recipes.
+/// Searches fish types and recipes, best matches first. Fish types are matched
+/// on their name, Anishinaabe name and description, recipes on their name,
+/// ingredients and steps. Names are also matched by similarity, so small typos
+/// still find the fish. Returns at most `limit` hits, 20 by default.
 ///
 /// # Example
 ///
-/// `.../search`
+/// `.../search?q=lake trout`
 ///
 ///```json
 /// {
-///   fish_result: [
-///     {
-///       "fish_id": "1fe5c906-d09d-11ed-afa1-0242ac120022",
-///       "name": "Herring",
-///       // ...
-///     },
-///     // ...
-///   ],
-///   recipe_result: [
-///     {
-///       "id": "1fe5c906-d09d-11ed-afa1-0242ac120002",
-///       "name": "Simple Salmon Recipe"
-///     },
-///     // ...
-///   ]
-///     ...
+///     "query": "lake trout",
+///     "results": [
+///         {
+///             "type": "fish_type",
+///             "id": "1fe5c906-d09d-11ed-afa1-0242ac120022",
+///             "name": "Lake Trout",
+///             "anishinaabe_name": "Namegos",
+///             "highlight": "<mark>Lake</mark> <mark>trout</mark> live in deep, cold water...",
+///             "rank": 1.61
+///         },
+///         {
+///             "type": "recipe",
+///             "id": "1fe5c906-d09d-11ed-afa1-0242ac120002",
+///             "name": "Smoked Trout Dip",
+///             "anishinaabe_name": null,
+///             "highlight": "1 lb smoked <mark>trout</mark>, flaked...",
+///             "rank": 0.52
+///         }
+///     ]
 /// }
 ///```
-#[tracing::instrument(name = "Retreving all fish data", skip(db_pool))]
-pub async fn search(db_pool: web::Data<PgPool>) -> HttpResponse {
-    match get_search_results(&db_pool).await {
-        Ok(result) => {
-            tracing::info!("All data has been queried from the db");
-            HttpResponse::Ok().json(result)
+#[tracing::instrument(name = "Searching fish types and recipes", skip(db_pool))]
+pub async fn search(query: web::Query<SearchQuery>, db_pool: web::Data<PgPool>) -> HttpResponse {
+    let q: String = query
+        .q
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .take(MAX_QUERY_CHARS)
+        .collect();
+    if q.is_empty() {
+        return HttpResponse::BadRequest().body("The search query `q` is required.");
+    }
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+
+    match get_search_results(&db_pool, &q, limit).await {
+        Ok(results) => {
+            tracing::info!("Search results have been queried from the db");
+            HttpResponse::Ok().json(SearchResult { query: q, results })
         }
         Err(e) => {
-            tracing::info!("Failed to execute query on recipes: {:?}", e);
+            tracing::error!("Failed to execute the search query: {:?}", e);
             HttpResponse::InternalServerError().finish()
         }
     }
 }
 
-async fn get_search_results(db_pool: &PgPool) -> Result<SearchResult, sqlx::Error> {
-    let fishs = get_fish_data(db_pool).await?;
-    let recipes = get_recipe_data(db_pool).await?;
-
-    Ok(SearchResult { fishs, recipes })
-}
-
-#[tracing::instrument(name = "Querying the database for fish", skip(db_pool))]
-async fn get_fish_data(db_pool: &PgPool) -> Result<Vec<FishType>, sqlx::Error> {
-    let data = sqlx::query_as!(
-        FishType,
-        r#"
-        SELECT 
-            *
-        FROM fish_type;
-        "#,
-    )
-    .fetch_all(db_pool)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to execute the query: {:?}", e);
-        e
-    })?;
-
-    Ok(data)
-}
-
-#[tracing::instrument(name = "Querying the database for recipes", skip(db_pool))]
-async fn get_recipe_data(db_pool: &PgPool) -> Result<Vec<RecipeData>, sqlx::Error> {
+/// Full text matches are ranked with `ts_rank`, names also get their trigram
+/// similarity to the query added so close spellings rank well.
+#[tracing::instrument(name = "Querying the database for search results", skip(db_pool))]
+async fn get_search_results(
+    db_pool: &PgPool,
+    q: &str,
+    limit: i64,
+) -> Result<Vec<SearchHit>, sqlx::Error> {
     let data = sqlx::query_as!(
-        RecipeData,
+        SearchHit,
         r#"
-        SELECT 
-            id as recipe_id,
-            name as recipe_name
-        FROM recipe
+        WITH query AS (
+            SELECT
+                websearch_to_tsquery('simple', $1) || websearch_to_tsquery('english', $1) AS ts,
+                $1::TEXT AS text
+        )
+        SELECT
+            result_type as "result_type!",
+            id as "id!",
+            name as "name!",
+            anishinaabe_name,
+            highlight,
+            rank as "rank!"
+        FROM (
+            SELECT
+                'fish_type' AS result_type,
+                fish_type.id,
+                fish_type.name,
+                fish_type.anishinaabe_name,
+                ts_headline(
+                    'english',
+                    html_escape(fish_type.about),
+                    query.ts,
+                    'StartSel=<mark>, StopSel=</mark>, MaxFragments=1, MaxWords=25, MinWords=10'
+                ) AS highlight,
+                (ts_rank(search_vector, query.ts) + GREATEST(
+                    similarity(fish_type.name, query.text),
+                    similarity(coalesce(fish_type.anishinaabe_name, ''), query.text)
+                ))::FLOAT4 AS rank
+            FROM fish_type,
+                query,
+                fish_type_search_vector(
+                    fish_type.name, fish_type.anishinaabe_name, fish_type.about
+                ) AS search_vector
+            WHERE fish_type_search_vector(
+                    fish_type.name, fish_type.anishinaabe_name, fish_type.about
+                ) @@ query.ts
+                OR fish_type.name % query.text
+                OR fish_type.anishinaabe_name % query.text
+            UNION ALL
+            SELECT
+                'recipe' AS result_type,
+                recipe.id,
+                recipe.name,
+                NULL AS anishinaabe_name,
+                ts_headline(
+                    'english',
+                    html_escape(concat_ws(
+                        ' ',
+                        array_to_string(recipe.ingredients, ' '),
+                        array_to_string(recipe.steps, ' ')
+                    )),
+                    query.ts,
+                    'StartSel=<mark>, StopSel=</mark>, MaxFragments=1, MaxWords=25, MinWords=10'
+                ) AS highlight,
+                (ts_rank(search_vector, query.ts)
+                    + similarity(recipe.name, query.text))::FLOAT4 AS rank
+            FROM recipe,
+                query,
+                recipe_search_vector(recipe.name, recipe.ingredients, recipe.steps) AS search_vector
+            WHERE recipe_search_vector(recipe.name, recipe.ingredients, recipe.steps) @@ query.ts
+                OR recipe.name % query.text
+        ) AS hits
+        ORDER BY rank DESC, name
+        LIMIT $2;
         "#,
+        q,
+        limit
     )
     .fetch_all(db_pool)
     .await
@@ -1,5 +1,5 @@
-use crate::utils::get_user_id;
-use actix_web::{post, web, HttpRequest, HttpResponse};
+use crate::authentication::SessionUser;
+use actix_web::{post, web, HttpResponse};
 use anyhow::Result;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -14,10 +14,9 @@ pub struct RecipeUuid {
 pub async fn unfavorite_recipe(
     uuid: web::Path<RecipeUuid>,
     db_pool: web::Data<PgPool>,
-    req: HttpRequest,
+    user: SessionUser,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = get_user_id(req)?;
-    match unfavorite_recipe_db(&db_pool, user_id, uuid.uuid).await {
+    match unfavorite_recipe_db(&db_pool, user.user_id, uuid.uuid).await {
         Ok(_) => {
             tracing::info!("Recipe has been unfavorited.");
             Ok(HttpResponse::Ok().finish())
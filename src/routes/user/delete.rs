@@ -1,36 +1,49 @@
+use crate::authentication::SessionUser;
 use crate::utils::e500;
 use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+/// Deletes the signed in user's profile from the db.
+#[tracing::instrument(name = "Deleting user's info", skip(db_pool))]
+pub async fn delete_user(
+    user: SessionUser,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    match delete_user_from_db(&db_pool, user.user_id).await {
+        Ok(user_data) => Ok(HttpResponse::Ok().json(user_data)),
+        Err(e) => Err(e500(e)),
+    }
+}
+
 #[derive(serde::Deserialize)]
 pub struct UserUuid {
     uuid: Uuid,
 }
 
-/// Deletes a user's profile from the db.
-#[tracing::instrument(name = "Deleting user's info", skip(uuid, db_pool))]
-pub async fn delete_user(
+/// Deprecated: use `DELETE /user`. Deletes the signed in user like it, but
+/// only if the uuid in the path is theirs; any other uuid gets a 403.
+#[tracing::instrument(name = "Deleting user's info by id", skip(uuid, db_pool))]
+pub async fn delete_user_by_id(
     uuid: web::Path<UserUuid>,
+    user: SessionUser,
     db_pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    match delete_user_from_db(&db_pool, uuid).await {
-        Ok(user_data) => Ok(HttpResponse::Ok().json(user_data)),
-        Err(e) => Err(e500(e)),
+    tracing::warn!("A user was deleted through the deprecated DELETE /user/{{uuid}} route.");
+    if uuid.uuid != user.user_id {
+        return Ok(HttpResponse::Forbidden().finish());
     }
+    delete_user(user, db_pool).await
 }
 
-#[tracing::instrument(name = "Deleting user details from the db.", skip(db_pool, uuid))]
-async fn delete_user_from_db(
-    db_pool: &PgPool,
-    uuid: web::Path<UserUuid>,
-) -> Result<(), sqlx::Error> {
+#[tracing::instrument(name = "Deleting user details from the db.", skip(db_pool))]
+async fn delete_user_from_db(db_pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
         DELETE FROM users
         WHERE id=$1
         "#,
-        uuid.uuid
+        user_id
     )
     .execute(db_pool)
     .await
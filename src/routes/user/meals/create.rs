@@ -0,0 +1,91 @@
+use super::read_all::get_meals_db;
+use super::{resolve_meal, MealData, ResolvedMeal};
+use crate::authentication::SessionUser;
+use crate::routes::get_profile;
+use crate::utils::e500;
+use actix_web::{web, HttpResponse};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Logs a meal the user ate. Either `fish_type_id` or `fish_id`, for a specific
+/// sample, is required. `portion_size` is in ounces and defaults to the one on
+/// the user's profile. Returns the saved meal.
+///
+/// # Example
+///
+///```json
+/// {
+///     "fish_type_id": "1fe5c906-d09d-11ed-afa1-0242ac120022",
+///     "lake": "Superior",
+///     "eaten_on": "2024-09-10",
+///     "portion_size": 6
+/// }
+///```
+#[tracing::instrument(name = "Logging a meal", skip(data, db_pool))]
+pub async fn log_meal(
+    data: web::Json<MealData>,
+    user: SessionUser,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let profile = get_profile(&db_pool, Some(user.user_id))
+        .await
+        .map_err(e500)?;
+    let meal = resolve_meal(&db_pool, &profile, data.into_inner()).await?;
+    let meal_id = Uuid::new_v4();
+
+    match log_meal_db(&db_pool, user.user_id, meal_id, &meal).await {
+        Ok(_) => {
+            tracing::info!("Meal has been logged.");
+            let meal = get_meals_db(&db_pool, user.user_id, Some(meal_id), None, None)
+                .await
+                .map_err(e500)?
+                .pop();
+            Ok(HttpResponse::Ok().json(meal))
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[tracing::instrument(name = "Saving a meal to the database", skip(db_pool))]
+async fn log_meal_db(
+    db_pool: &PgPool,
+    user_id: Uuid,
+    meal_id: Uuid,
+    meal: &ResolvedMeal,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO meal_log (
+            id,
+            user_id,
+            fish_type_id,
+            fish_id,
+            lake,
+            eaten_on,
+            portion_size,
+            created_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8);
+        "#,
+        meal_id,
+        user_id,
+        meal.fish_type_id,
+        meal.fish_id,
+        meal.lake,
+        meal.eaten_on,
+        meal.portion_size,
+        Utc::now()
+    )
+    .execute(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
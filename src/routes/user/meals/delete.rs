@@ -0,0 +1,49 @@
+use super::MealUuid;
+use crate::authentication::SessionUser;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[tracing::instrument(name = "Deleting a meal", skip(db_pool))]
+pub async fn delete_meal(
+    meal_id: web::Path<MealUuid>,
+    user: SessionUser,
+    db_pool: web::Data<PgPool>,
+) -> HttpResponse {
+    match delete_meal_db(&db_pool, user.user_id, meal_id.uuid).await {
+        Ok(true) => {
+            tracing::info!("Meal has been deleted.");
+            HttpResponse::Ok().finish()
+        }
+        Ok(false) => HttpResponse::NotFound().finish(),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Returns whether the user had a meal with the id.
+#[tracing::instrument(name = "Deleting a meal from the database", skip(db_pool))]
+async fn delete_meal_db(
+    db_pool: &PgPool,
+    user_id: Uuid,
+    meal_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM meal_log
+        WHERE id = $1 AND user_id = $2;
+        "#,
+        meal_id,
+        user_id
+    )
+    .execute(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(result.rows_affected() == 1)
+}
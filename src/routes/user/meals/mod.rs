@@ -0,0 +1,136 @@
+mod create;
+mod delete;
+mod read_all;
+mod summary;
+mod update;
+
+pub use create::log_meal;
+pub use delete::delete_meal;
+pub use read_all::meals;
+pub use summary::meal_summary;
+pub use update::update_meal;
+
+use crate::advisory::UserProfile;
+use crate::routes::lake_exists;
+use crate::utils::{e400, e500};
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct MealUuid {
+    uuid: Uuid,
+}
+
+/// A meal as sent by the app. Either the fish type or the exact sample has to
+/// be given.
+#[derive(serde::Deserialize, Debug)]
+pub struct MealData {
+    fish_type_id: Option<Uuid>,
+    fish_id: Option<Uuid>,
+    lake: Option<String>,
+    eaten_on: NaiveDate,
+    /// In ounces, defaults to the portion size on the user's profile.
+    portion_size: Option<i16>,
+}
+
+/// A meal with the blanks filled in, ready to be saved.
+#[derive(Debug)]
+struct ResolvedMeal {
+    fish_type_id: Uuid,
+    fish_id: Option<Uuid>,
+    lake: Option<String>,
+    eaten_on: NaiveDate,
+    portion_size: i16,
+}
+
+/// Checks the meal and fills in what the user left out: the fish type and lake
+/// of a sample, and the portion size from their profile.
+async fn resolve_meal(
+    db_pool: &PgPool,
+    profile: &UserProfile,
+    data: MealData,
+) -> Result<ResolvedMeal, actix_web::Error> {
+    if let Some(lake) = &data.lake {
+        if !lake_exists(db_pool, lake).await.map_err(e500)? {
+            return Err(e400("Invalid lake."));
+        }
+    }
+    let portion_size = data.portion_size.unwrap_or_else(|| profile.portion_oz());
+    if portion_size <= 0 {
+        return Err(e400("The portion size has to be positive."));
+    }
+
+    let (fish_type_id, lake) = match (data.fish_id, data.fish_type_id) {
+        (Some(fish_id), fish_type_id) => {
+            let sample = get_sample_db(db_pool, fish_id)
+                .await
+                .map_err(e500)?
+                .ok_or_else(|| e400("Unknown fish."))?;
+            if fish_type_id.is_some_and(|id| id != sample.fish_type_id) {
+                return Err(e400("The fish isn't of the given fish type."));
+            }
+            (sample.fish_type_id, data.lake.or(Some(sample.lake)))
+        }
+        (None, Some(fish_type_id)) => {
+            if !fish_type_exists_db(db_pool, fish_type_id)
+                .await
+                .map_err(e500)?
+            {
+                return Err(e400("Unknown fish type."));
+            }
+            (fish_type_id, data.lake)
+        }
+        (None, None) => return Err(e400("Either a fish type or a fish is required.")),
+    };
+
+    Ok(ResolvedMeal {
+        fish_type_id,
+        fish_id: data.fish_id,
+        lake,
+        eaten_on: data.eaten_on,
+        portion_size,
+    })
+}
+
+struct Sample {
+    fish_type_id: Uuid,
+    lake: String,
+}
+
+#[tracing::instrument(name = "Querying the database for a fish sample", skip(db_pool))]
+async fn get_sample_db(db_pool: &PgPool, fish_id: Uuid) -> Result<Option<Sample>, sqlx::Error> {
+    sqlx::query_as!(
+        Sample,
+        r#"
+        SELECT fish_type_id, lake
+        FROM fish
+        WHERE id = $1;
+        "#,
+        fish_id
+    )
+    .fetch_optional(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })
+}
+
+#[tracing::instrument(name = "Checking that a fish type exists", skip(db_pool))]
+async fn fish_type_exists_db(db_pool: &PgPool, fish_type_id: Uuid) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT EXISTS(SELECT 1 FROM fish_type WHERE id = $1) as "exists!";
+        "#,
+        fish_type_id
+    )
+    .fetch_one(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(row.exists)
+}
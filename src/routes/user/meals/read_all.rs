@@ -0,0 +1,159 @@
+use crate::advisory::portion_oz_to_g;
+use crate::authentication::SessionUser;
+use crate::routes::Substitution;
+use crate::utils::e500;
+use actix_web::{web, HttpResponse};
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct MealQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct Meal {
+    pub id: Uuid,
+    pub fish_type_id: Uuid,
+    pub fish_type_name: String,
+    pub fish_id: Option<Uuid>,
+    pub lake: Option<String>,
+    pub eaten_on: NaiveDate,
+    /// oz
+    pub portion_size: i16,
+    /// µg of mercury eaten, None if there's no data for the fish.
+    pub mercury: Option<f64>,
+    /// µg of PCBs eaten, None if there's no data for the fish.
+    pub pcb: Option<f64>,
+}
+
+struct MealRow {
+    id: Uuid,
+    fish_type_id: Uuid,
+    fish_type_name: String,
+    fish_id: Option<Uuid>,
+    lake: Option<String>,
+    eaten_on: NaiveDate,
+    portion_size: i16,
+    mercury_ppm: Option<f64>,
+    pcb_ppm: Option<f64>,
+}
+
+impl From<MealRow> for Meal {
+    fn from(row: MealRow) -> Self {
+        let portion_g = portion_oz_to_g(row.portion_size);
+        Self {
+            id: row.id,
+            fish_type_id: row.fish_type_id,
+            fish_type_name: row.fish_type_name,
+            fish_id: row.fish_id,
+            lake: row.lake,
+            eaten_on: row.eaten_on,
+            portion_size: row.portion_size,
+            mercury: row.mercury_ppm.map(|ppm| ppm * portion_g),
+            pcb: row.pcb_ppm.map(|ppm| ppm * portion_g),
+        }
+    }
+}
+
+/// Lists the meals the user has logged, most recent first, optionally between
+/// two dates (inclusive).
+///
+/// # Example
+///
+/// `.../user/meals?from=2024-09-01&to=2024-09-30`
+///
+///```json
+/// [
+///     {
+///         "id": "7d8c1f9e-5b0a-4d8e-9a61-1c3b2d4e5f60",
+///         "fish_type_id": "1fe5c906-d09d-11ed-afa1-0242ac120022",
+///         "fish_type_name": "Herring",
+///         "fish_id": null,
+///         "lake": "Superior",
+///         "eaten_on": "2024-09-10",
+///         "portion_size": 8,
+///         "mercury": 7.26,
+///         "pcb": 45.36
+///     }
+/// ]
+///```
+#[tracing::instrument(name = "Listing a user's meals", skip(db_pool))]
+pub async fn meals(
+    query: web::Query<MealQuery>,
+    user: SessionUser,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let meals = get_meals_db(&db_pool, user.user_id, None, query.from, query.to)
+        .await
+        .map_err(e500)?;
+
+    Ok(HttpResponse::Ok().json(meals))
+}
+
+/// The contaminant levels of a meal are the ones of the sample eaten, if known,
+/// otherwise the averages of the fish type in the lake, or in every lake if
+/// the lake isn't known either.
+#[tracing::instrument(name = "Querying the database for meals", skip(db_pool))]
+pub(super) async fn get_meals_db(
+    db_pool: &PgPool,
+    user_id: Uuid,
+    meal_id: Option<Uuid>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<Vec<Meal>, sqlx::Error> {
+    let rows = sqlx::query_as!(
+        MealRow,
+        r#"
+        SELECT
+            meal_log.id,
+            meal_log.fish_type_id,
+            fish_type.name as fish_type_name,
+            meal_log.fish_id,
+            meal_log.lake,
+            meal_log.eaten_on,
+            meal_log.portion_size,
+            COALESCE(sample.mercury, levels.mercury) as mercury_ppm,
+            COALESCE(sample.pcb, levels.pcb) as pcb_ppm
+        FROM meal_log
+        JOIN fish_type ON fish_type.id = meal_log.fish_type_id
+        LEFT JOIN LATERAL (
+            SELECT
+                AVG(measurement.value) FILTER (WHERE measurement.analyte = 'mercury') as mercury,
+                AVG(measurement.value) FILTER (WHERE measurement.analyte = 'pcb') as pcb
+            FROM substituted_measurement($5) AS measurement
+            WHERE measurement.fish_id = meal_log.fish_id
+        ) AS sample ON TRUE
+        LEFT JOIN LATERAL (
+            SELECT
+                AVG(measurement.value) FILTER (WHERE measurement.analyte = 'mercury') as mercury,
+                AVG(measurement.value) FILTER (WHERE measurement.analyte = 'pcb') as pcb
+            FROM fish AS samples
+            JOIN substituted_measurement($5) AS measurement
+                ON measurement.fish_id = samples.id
+            WHERE samples.fish_type_id = meal_log.fish_type_id
+                AND (meal_log.lake IS NULL OR samples.lake = meal_log.lake)
+        ) AS levels ON TRUE
+        WHERE meal_log.user_id = $1
+            AND ($2::DATE IS NULL OR meal_log.eaten_on >= $2)
+            AND ($3::DATE IS NULL OR meal_log.eaten_on <= $3)
+            AND ($4::UUID IS NULL OR meal_log.id = $4)
+        ORDER BY meal_log.eaten_on DESC, meal_log.created_at DESC;
+        "#,
+        user_id,
+        from,
+        to,
+        meal_id,
+        Substitution::default().factor()
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(rows.into_iter().map(Meal::from).collect())
+}
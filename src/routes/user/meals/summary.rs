@@ -0,0 +1,133 @@
+use super::read_all::{get_meals_db, Meal};
+use crate::advisory::{allowable_intake, ReferenceDoses};
+use crate::authentication::SessionUser;
+use crate::routes::{get_profile, get_reference_doses};
+use crate::utils::e500;
+use actix_web::{web, HttpResponse};
+use chrono::{Days, NaiveDate, Utc};
+use sqlx::PgPool;
+
+const WEEK_DAYS: u64 = 7;
+const MONTH_DAYS: u64 = 30;
+
+#[derive(serde::Serialize)]
+pub struct ContaminantIntake {
+    /// µg eaten in the window.
+    intake: f64,
+    /// µg the user can safely eat in the window.
+    limit: f64,
+    percent_of_limit: f64,
+    /// Meals of fish without data for the contaminant, they aren't counted in
+    /// its intake.
+    meals_without_data: usize,
+}
+
+impl ContaminantIntake {
+    /// `intakes` holds the µg of every meal, None when unknown.
+    fn new(intakes: impl Iterator<Item = Option<f64>>, limit: f64) -> Self {
+        let (known, unknown): (Vec<_>, Vec<_>) = intakes.partition(Option::is_some);
+        let intake = known.into_iter().flatten().sum();
+        Self {
+            intake,
+            limit,
+            percent_of_limit: intake / limit * 100.0,
+            meals_without_data: unknown.len(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct IntakeWindow {
+    from: NaiveDate,
+    to: NaiveDate,
+    meals: usize,
+    mercury: ContaminantIntake,
+    pcb: ContaminantIntake,
+}
+
+#[derive(serde::Serialize)]
+pub struct MealSummary {
+    weekly: IntakeWindow,
+    monthly: IntakeWindow,
+    weight_kg: f64,
+    reference_doses: ReferenceDoses,
+}
+
+/// Adds up the mercury and PCBs the user ate over the last 7 and 30 days
+/// (including today) and compares it with how much they can safely eat, based
+/// on their weight and the reference doses in the analyte catalog that apply
+/// to them.
+///
+/// # Example
+///
+///```json
+/// {
+///     "weekly": {
+///         "from": "2024-09-04",
+///         "to": "2024-09-10",
+///         "meals": 2,
+///         "mercury": {
+///             "intake": 14.5,
+///             "limit": 171.4,
+///             "percent_of_limit": 8.5,
+///             "meals_without_data": 0
+///         },
+///         "pcb": {
+///             "intake": 90.7,
+///             "limit": 11.4,
+///             "percent_of_limit": 795.6,
+///             "meals_without_data": 1
+///         }
+///     },
+///     "monthly": {
+///         // ...
+///     },
+///     "weight_kg": 81.6,
+///     "reference_doses": { "mercury": 0.3, "pcb": 0.02 }
+/// }
+///```
+#[tracing::instrument(name = "Summarizing a user's meals", skip(db_pool))]
+pub async fn meal_summary(
+    user: SessionUser,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let profile = get_profile(&db_pool, Some(user.user_id))
+        .await
+        .map_err(e500)?;
+    let today = Utc::now().date_naive();
+    let month_start = today - Days::new(MONTH_DAYS - 1);
+    let meals = get_meals_db(&db_pool, user.user_id, None, Some(month_start), Some(today))
+        .await
+        .map_err(e500)?;
+
+    let weight_kg = profile.weight_kg();
+    let reference_doses = get_reference_doses(&db_pool, &profile)
+        .await
+        .map_err(e500)?;
+    let window = |days: u64| {
+        let from = today - Days::new(days - 1);
+        let meals: Vec<&Meal> = meals.iter().filter(|meal| meal.eaten_on >= from).collect();
+        let days = days as f64;
+
+        IntakeWindow {
+            from,
+            to: today,
+            meals: meals.len(),
+            mercury: ContaminantIntake::new(
+                meals.iter().map(|meal| meal.mercury),
+                allowable_intake(reference_doses.mercury, weight_kg, days),
+            ),
+            pcb: ContaminantIntake::new(
+                meals.iter().map(|meal| meal.pcb),
+                allowable_intake(reference_doses.pcb, weight_kg, days),
+            ),
+        }
+    };
+
+    Ok(HttpResponse::Ok().json(MealSummary {
+        weekly: window(WEEK_DAYS),
+        monthly: window(MONTH_DAYS),
+        weight_kg,
+        reference_doses,
+    }))
+}
@@ -0,0 +1,75 @@
+use super::read_all::get_meals_db;
+use super::{resolve_meal, MealData, MealUuid, ResolvedMeal};
+use crate::authentication::SessionUser;
+use crate::routes::get_profile;
+use crate::utils::e500;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Replaces a logged meal, takes the same body as logging a new one.
+#[tracing::instrument(name = "Updating a meal", skip(data, db_pool))]
+pub async fn update_meal(
+    meal_id: web::Path<MealUuid>,
+    data: web::Json<MealData>,
+    user: SessionUser,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let profile = get_profile(&db_pool, Some(user.user_id))
+        .await
+        .map_err(e500)?;
+    let meal = resolve_meal(&db_pool, &profile, data.into_inner()).await?;
+
+    match update_meal_db(&db_pool, user.user_id, meal_id.uuid, &meal).await {
+        Ok(true) => {
+            tracing::info!("Meal has been updated.");
+            let meal = get_meals_db(&db_pool, user.user_id, Some(meal_id.uuid), None, None)
+                .await
+                .map_err(e500)?
+                .pop();
+            Ok(HttpResponse::Ok().json(meal))
+        }
+        Ok(false) => Ok(HttpResponse::NotFound().finish()),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Returns whether the user has a meal with the id.
+#[tracing::instrument(name = "Updating a meal in the database", skip(db_pool))]
+async fn update_meal_db(
+    db_pool: &PgPool,
+    user_id: Uuid,
+    meal_id: Uuid,
+    meal: &ResolvedMeal,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE meal_log
+        SET
+            fish_type_id = $3,
+            fish_id = $4,
+            lake = $5,
+            eaten_on = $6,
+            portion_size = $7
+        WHERE id = $1 AND user_id = $2;
+        "#,
+        meal_id,
+        user_id,
+        meal.fish_type_id,
+        meal.fish_id,
+        meal.lake,
+        meal.eaten_on,
+        meal.portion_size
+    )
+    .execute(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?;
+
+    Ok(result.rows_affected() == 1)
+}
@@ -1,5 +1,9 @@
 mod delete;
+mod meals;
+mod sessions;
 mod update;
 
-pub use delete::delete_user;
+pub use delete::{delete_user, delete_user_by_id};
+pub use meals::{delete_meal, log_meal, meal_summary, meals, update_meal};
+pub use sessions::{revoke_all_sessions, sessions};
 pub use update::{change_password, update_account, update_image, update_profile};
@@ -0,0 +1,97 @@
+use crate::authentication::{revoke_user_sessions, SessionUser};
+use crate::utils::e500;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Serialize)]
+pub struct Session {
+    id: Uuid,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    created_at: DateTime<Utc>,
+    last_seen_at: Option<DateTime<Utc>>,
+    /// True for the session the request was made with.
+    current: bool,
+}
+
+/// Lists the user's active sessions, most recently used first.
+///
+/// # Example
+///
+///```json
+/// [
+///     {
+///         "id": "1fe5c906-d09d-11ed-afa1-0242ac120002",
+///         "user_agent": "okhttp/4.9.2",
+///         "ip_address": "203.0.113.7",
+///         "created_at": "2024-09-01T12:00:00Z",
+///         "last_seen_at": "2024-09-02T08:30:00Z",
+///         "current": true
+///     }
+/// ]
+///```
+#[tracing::instrument(name = "Listing a user's sessions", skip(db_pool))]
+pub async fn sessions(
+    user: SessionUser,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    match get_sessions_db(&db_pool, user).await {
+        Ok(sessions) => Ok(HttpResponse::Ok().json(sessions)),
+        Err(e) => Err(e500(e)),
+    }
+}
+
+/// Logs the user out everywhere, including the session the request was made
+/// with.
+#[tracing::instrument(name = "Revoking all of a user's sessions", skip(db_pool))]
+pub async fn revoke_all_sessions(
+    user: SessionUser,
+    db_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    revoke_user_sessions(&db_pool, user.user_id)
+        .await
+        .map_err(e500)?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(name = "Querying the database for sessions", skip(db_pool))]
+async fn get_sessions_db(db_pool: &PgPool, user: SessionUser) -> Result<Vec<Session>, sqlx::Error> {
+    let sessions = sqlx::query!(
+        r#"
+        SELECT
+            id,
+            user_agent,
+            ip_address,
+            created_at,
+            last_seen_at
+        FROM session
+        WHERE user_id = $1
+            AND revoked_at IS NULL
+            AND (expires_at > $2 OR refresh_expires_at > $2)
+        ORDER BY last_seen_at DESC NULLS LAST;
+        "#,
+        user.user_id,
+        Utc::now()
+    )
+    .fetch_all(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute the query: {:?}", e);
+        e
+    })?
+    .into_iter()
+    .map(|row| Session {
+        current: Some(row.id) == user.session_id,
+        id: row.id,
+        user_agent: row.user_agent,
+        ip_address: row.ip_address,
+        created_at: row.created_at,
+        last_seen_at: row.last_seen_at,
+    })
+    .collect();
+
+    Ok(sessions)
+}
@@ -1,47 +1,98 @@
+use crate::authentication::SessionUser;
+use crate::email_client::EmailClient;
+use crate::routes::{send_verification_email, VerificationReason};
+use crate::startup::ApplicationBaseUrl;
 use crate::utils::e500;
 use actix_web::{web, HttpResponse};
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
 #[derive(serde::Deserialize)]
 pub struct FormData {
-    user_id: Uuid,
     email: String,
     first_name: Option<String>,
     last_name: Option<String>,
 }
 
-/// An endpoint to update a user's account information.
-/// It requires the `user_id` and `email`.
+/// An endpoint to update the signed in user's account information.
+/// It requires the `email`. A new email has to be verified again, a link to
+/// verify it is sent to it. Returns a 400 if the email isn't a valid address
+/// and a 409 if another user has it.
 pub async fn update_account(
+    user: SessionUser,
     form: web::Form<FormData>,
     pool: web::Data<PgPool>,
+    email_client: web::Data<dyn EmailClient>,
+    base_url: web::Data<ApplicationBaseUrl>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    match update_account_db(form, &pool).await {
-        Ok(_) => Ok(HttpResponse::Ok().finish()),
-        Err(e) => Ok(e500(e).into()),
+    if form.email.parse::<lettre::Address>().is_err() {
+        return Ok(HttpResponse::BadRequest().finish());
+    }
+
+    let mut transaction = pool.begin().await.map_err(e500)?;
+    let email_changed = match update_account_db(&mut transaction, user.user_id, &form).await {
+        Ok(email_changed) => email_changed,
+        Err(e)
+            if e.as_database_error()
+                .is_some_and(|e| e.is_unique_violation()) =>
+        {
+            return Ok(HttpResponse::Conflict().finish());
+        }
+        Err(e) => return Ok(e500(e).into()),
+    };
+    if email_changed {
+        send_verification_email(
+            &mut transaction,
+            email_client.as_ref(),
+            &base_url.0,
+            user.user_id,
+            &form.email,
+            VerificationReason::EmailChanged,
+        )
+        .await
+        .map_err(e500)?;
     }
+    transaction.commit().await.map_err(e500)?;
+
+    Ok(HttpResponse::Ok().finish())
 }
 
-#[tracing::instrument(name = "Saving user account details to the db.", skip(pool, form))]
-async fn update_account_db(form: web::Form<FormData>, pool: &PgPool) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+/// Returns whether the email was changed, which clears its verification.
+#[tracing::instrument(
+    name = "Saving user account details to the db.",
+    skip(transaction, form)
+)]
+async fn update_account_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    form: &FormData,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
         r#"
         UPDATE users
-        SET email=$1, first_name=$2, last_name=$3 
-        WHERE id = $4
+        SET
+            email = $1,
+            first_name = $2,
+            last_name = $3,
+            email_verified_at = CASE
+                WHEN users.email = $1 THEN users.email_verified_at
+                ELSE NULL
+            END
+        FROM (SELECT email FROM users WHERE id = $4) AS previous
+        WHERE users.id = $4
+        RETURNING previous.email <> $1 as "email_changed!"
         "#,
         form.email,
         form.first_name,
         form.last_name,
-        form.user_id
+        user_id
     )
-    .execute(pool)
+    .fetch_one(&mut **transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute the query: {:?}", e);
         e
     })?;
 
-    Ok(())
+    Ok(row.email_changed)
 }
@@ -1,3 +1,4 @@
+use crate::authentication::SessionUser;
 use crate::utils::e500;
 use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
@@ -5,31 +6,35 @@ use uuid::Uuid;
 
 #[derive(serde::Deserialize)]
 pub struct FormData {
-    user_id: Uuid,
     image_url: String,
 }
 
-/// Saves the users image url to the user object.
-/// Requires the user_id and image_url to be in the body's request.
+/// Saves the signed in user's image url to the user object.
+/// Requires the image_url to be in the body's request.
 #[tracing::instrument(
     name="Saving user's image",
     skip(form, db_pool),
     fields(
-        subscriber_name = %form.user_id
+        subscriber_name = %user.user_id
         )
     )]
 pub async fn update_image(
+    user: SessionUser,
     form: web::Form<FormData>,
     db_pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    match update_image_db(&db_pool, form).await {
+    match update_image_db(&db_pool, user.user_id, form).await {
         Ok(_) => Ok(HttpResponse::Ok().finish()),
         Err(e) => Err(e500(e)),
     }
 }
 
 #[tracing::instrument(name = "Saving user details to the db.", skip(db_pool, form))]
-async fn update_image_db(db_pool: &PgPool, form: web::Form<FormData>) -> Result<(), sqlx::Error> {
+async fn update_image_db(
+    db_pool: &PgPool,
+    user_id: Uuid,
+    form: web::Form<FormData>,
+) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
         UPDATE users
@@ -37,7 +42,7 @@ async fn update_image_db(db_pool: &PgPool, form: web::Form<FormData>) -> Result<
         WHERE users.id=$2
         "#,
         form.image_url,
-        form.user_id,
+        user_id,
     )
     .execute(db_pool)
     .await
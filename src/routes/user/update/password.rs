@@ -1,4 +1,6 @@
-use crate::authentication::{validate_credentials, AuthError, Credentials};
+use crate::authentication::{
+    revoke_user_sessions, validate_credentials, AuthError, Credentials, SessionUser,
+};
 use crate::utils::e500;
 use actix_web::{web, HttpResponse};
 use secrecy::{ExposeSecret, Secret};
@@ -7,20 +9,21 @@ use uuid::Uuid;
 
 #[derive(serde::Deserialize)]
 pub struct FormData {
-    user_id: Uuid,
     current_password: Secret<String>,
     new_password: Secret<String>,
     new_password_check: Secret<String>,
 }
 
-/// An endpoint to update a user's password.
-/// It expects the `user_id`, `current_password`, `new_password`, and
-/// `new_password_check` to be included as form data.
+/// An endpoint to update the signed in user's password.
+/// It expects the `current_password`, `new_password`, and
+/// `new_password_check` to be included as form data. Every session of the user
+/// is revoked once the password has changed.
 pub async fn change_password(
+    user: SessionUser,
     form: web::Form<FormData>,
     pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let user_id = form.user_id;
+    let user_id = user.user_id;
     if form.new_password.expose_secret() != form.new_password_check.expose_secret() {
         return Ok(HttpResponse::BadRequest().finish());
     }
@@ -32,12 +35,14 @@ pub async fn change_password(
     if let Err(e) = validate_credentials(credentials, &pool).await {
         return match e {
             AuthError::InvalidCredentials(_) => Ok(HttpResponse::BadRequest().finish()),
+            AuthError::AccountDisabled => Ok(HttpResponse::Forbidden().finish()),
             AuthError::UnexpectedError(_) => Err(e500(e)),
         };
     }
     crate::authentication::change_password(user_id, form.0.new_password, &pool)
         .await
         .map_err(e500)?;
+    revoke_user_sessions(&pool, user_id).await.map_err(e500)?;
 
     Ok(HttpResponse::Ok().finish())
 }
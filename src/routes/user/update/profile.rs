@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use crate::authentication::SessionUser;
 use crate::utils::e500;
 use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
@@ -8,6 +9,7 @@ use uuid::Uuid;
 #[derive(serde::Deserialize, serde::Serialize, sqlx::Type)]
 #[sqlx(type_name = "sex")]
 #[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum Sex {
     Male,
     Female,
@@ -24,45 +26,52 @@ impl Display for Sex {
 
 #[derive(serde::Deserialize)]
 pub struct FormData {
-    user_id: Uuid,
     weight: i16,
     age: i16,
+    sex: Option<Sex>,
     plan_to_get_pregnant: Option<bool>,
     portion_size: i16,
 }
 
-/// Updates a user's profile information.
+/// Updates the signed in user's profile information. `sex` is kept as it was
+/// when it isn't given.
 #[tracing::instrument(
     name="Saving user's info",
     skip(form, db_pool),
     fields(
-        subscriber_name = %form.user_id
+        subscriber_name = %user.user_id
         )
     )]
 pub async fn update_profile(
+    user: SessionUser,
     form: web::Form<FormData>,
     db_pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    match update_profile_db(&db_pool, form).await {
+    match update_profile_db(&db_pool, user.user_id, form).await {
         Ok(_) => Ok(HttpResponse::Ok().finish()),
         Err(e) => Err(e500(e)),
     }
 }
 
 #[tracing::instrument(name = "Saving user details to the db.", skip(db_pool, form))]
-async fn update_profile_db(db_pool: &PgPool, form: web::Form<FormData>) -> Result<(), sqlx::Error> {
+async fn update_profile_db(
+    db_pool: &PgPool,
+    user_id: Uuid,
+    form: web::Form<FormData>,
+) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
         UPDATE users
-        SET (weight, age, sex, plan_to_get_pregnant, portion_size) = ($1, $2, $3, $4, $5)
+        SET (weight, age, sex, plan_to_get_pregnant, portion_size) =
+            ($1, $2, COALESCE($3, sex), $4, $5)
         WHERE users.id = $6
         "#,
         form.weight,
         form.age,
-        "male",
+        form.sex.as_ref().map(|sex| sex.to_string().to_lowercase()),
         form.plan_to_get_pregnant,
         form.portion_size,
-        form.user_id,
+        user_id,
     )
     .execute(db_pool)
     .await
@@ -1,33 +1,79 @@
-use crate::middleware::{api_auth, reject_non_admin_users};
+use crate::authentication::Permission;
+use crate::configuration::Settings;
+use crate::email_client::EmailClient;
+use crate::middleware::{api_auth, reject_non_admin_users, require_permission, ConfiguredApiKeys};
 use crate::routes;
 use actix_web::dev::Server;
 use actix_web::{middleware, web, App, HttpServer};
 use actix_web_httpauth::middleware::HttpAuthentication;
 use actix_web_lab::middleware::from_fn;
+use ipnet::IpNet;
+use secrecy::Secret;
 use sqlx::PgPool;
 use std::net::TcpListener;
+use std::sync::Arc;
 use tracing_actix_web::TracingLogger;
 
-pub fn run(listener: TcpListener, db_pool: PgPool) -> Result<Server, std::io::Error> {
+/// The public URL of the API, used to build links in emails.
+pub struct ApplicationBaseUrl(pub String);
+
+/// The proxies whose `X-Forwarded-For` header is used to find the client's
+/// address, see `authentication::client_ip`.
+pub struct TrustedProxies(pub Vec<IpNet>);
+
+pub fn run(
+    listener: TcpListener,
+    db_pool: PgPool,
+    email_client: Arc<dyn EmailClient>,
+    settings: &Settings,
+) -> Result<Server, std::io::Error> {
     let db_pool = web::Data::new(db_pool);
+    let email_client: web::Data<dyn EmailClient> = web::Data::from(email_client);
+    let session_settings = web::Data::new(settings.session.clone());
+    let login_throttle = web::Data::new(settings.login_throttle.clone());
+    let scoring = web::Data::new(settings.scoring);
+    let base_url = web::Data::new(ApplicationBaseUrl(settings.application.base_url.clone()));
+    let unverified_users = web::Data::new(settings.application.unverified_users);
+    let trusted_proxies =
+        web::Data::new(TrustedProxies(settings.application.trusted_proxies.clone()));
+    let configured_api_keys = web::Data::new(ConfiguredApiKeys {
+        admin: Secret::new(settings.application.api_key.clone()),
+        public: Secret::new(settings.application.public_key.clone()),
+    });
     let server = HttpServer::new(move || {
         let auth = HttpAuthentication::bearer(api_auth);
 
         App::new()
             .wrap(middleware::Compress::default())
             .route("/health_check", web::get().to(routes::health_check))
+            // Opened from the verification email, so it can't require the API key.
+            .route(
+                "/v1/register/confirm",
+                web::get().to(routes::confirm_registration),
+            )
             .service(
                 web::scope("/v1")
                     .wrap(auth)
                     .wrap(TracingLogger::default())
                     .service(routes::fish)
+                    .service(routes::fish_advice)
                     .service(routes::fishs)
                     .service(routes::fish_avg)
+                    .service(routes::fish_avg_advice)
                     .service(routes::fish_avgs)
                     .service(routes::recipe)
                     .service(routes::recipes)
                     .service(routes::min_and_max)
+                    .service(routes::stats)
+                    .service(routes::compare)
+                    .service(routes::rankings)
+                    .service(routes::lakes)
+                    .service(routes::analytes)
+                    .service(routes::sites_near)
+                    .service(routes::fish_type_trend)
+                    .service(routes::length_classes)
                     .service(routes::everything)
+                    .service(routes::export_fish)
                     .service(routes::presign_s3)
                     .service(
                         web::scope("/favorite")
@@ -42,20 +88,48 @@ pub fn run(listener: TcpListener, db_pool: PgPool) -> Result<Server, std::io::Er
                     )
                     .route("/search", web::get().to(routes::search))
                     .route("/register", web::post().to(routes::register))
+                    .route(
+                        "/register/resend",
+                        web::post().to(routes::resend_verification),
+                    )
                     .route("/login", web::post().to(routes::login))
+                    .route("/logout", web::post().to(routes::logout))
+                    .route("/refresh", web::post().to(routes::refresh))
+                    .service(
+                        web::scope("/password_reset")
+                            .route("/request", web::post().to(routes::request_password_reset))
+                            .route("/confirm", web::post().to(routes::confirm_password_reset)),
+                    )
                     .service(
                         web::scope("/user")
                             .route("/profile", web::post().to(routes::update_profile))
                             .route("/account", web::post().to(routes::update_account))
                             .route("/image", web::post().to(routes::update_image))
                             .route("/change_password", web::post().to(routes::change_password))
-                            .route("/{uuid}", web::delete().to(routes::delete_user)),
+                            .route("/sessions", web::get().to(routes::sessions))
+                            .route(
+                                "/sessions/revoke_all",
+                                web::post().to(routes::revoke_all_sessions),
+                            )
+                            .service(
+                                web::scope("/meals")
+                                    .route("", web::get().to(routes::meals))
+                                    .route("", web::post().to(routes::log_meal))
+                                    .route("/summary", web::get().to(routes::meal_summary))
+                                    .route("/{uuid}", web::put().to(routes::update_meal))
+                                    .route("/{uuid}", web::delete().to(routes::delete_meal)),
+                            )
+                            .route("", web::delete().to(routes::delete_user))
+                            .route("/{uuid}", web::delete().to(routes::delete_user_by_id)),
                     )
                     .service(
                         web::scope("/admin")
                             .wrap(from_fn(reject_non_admin_users))
                             .service(
                                 web::scope("/recipe")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ManageRecipes, req, next)
+                                    }))
                                     .service(routes::new_recipe)
                                     .service(routes::update_recipe)
                                     .service(routes::delete_recipe)
@@ -63,22 +137,161 @@ pub fn run(listener: TcpListener, db_pool: PgPool) -> Result<Server, std::io::Er
                             )
                             .service(
                                 web::scope("/fish")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ManageFish, req, next)
+                                    }))
                                     .service(routes::new_fish)
                                     .service(routes::update_fish)
                                     .service(routes::delete_fish),
                             )
+                            .service(
+                                web::scope("/import")
+                                    .app_data(web::PayloadConfig::new(routes::MAX_IMPORT_SIZE))
+                                    .service(
+                                        web::scope("/fish")
+                                            .wrap(from_fn(|req, next| {
+                                                require_permission(
+                                                    Permission::ManageFish,
+                                                    req,
+                                                    next,
+                                                )
+                                            }))
+                                            .service(routes::import_fish),
+                                    )
+                                    .service(
+                                        web::scope("/fish_type")
+                                            .wrap(from_fn(|req, next| {
+                                                require_permission(
+                                                    Permission::ManageFishTypes,
+                                                    req,
+                                                    next,
+                                                )
+                                            }))
+                                            .service(routes::import_fish_types),
+                                    )
+                                    .service(
+                                        web::scope("/recipe")
+                                            .wrap(from_fn(|req, next| {
+                                                require_permission(
+                                                    Permission::ManageRecipes,
+                                                    req,
+                                                    next,
+                                                )
+                                            }))
+                                            .service(routes::import_recipes),
+                                    )
+                                    .service(
+                                        web::scope("/fish_recipe")
+                                            .wrap(from_fn(|req, next| {
+                                                require_permission(
+                                                    Permission::ManageFishTypes,
+                                                    req,
+                                                    next,
+                                                )
+                                            }))
+                                            .service(routes::import_fish_recipes),
+                                    ),
+                            )
+                            .service(
+                                web::scope("/lake")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ManageLakes, req, next)
+                                    }))
+                                    .service(routes::create_lake)
+                                    .service(routes::update_lake)
+                                    .service(routes::delete_lake),
+                            )
+                            .service(
+                                web::scope("/analyte")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ManageFish, req, next)
+                                    }))
+                                    .service(routes::create_analyte)
+                                    .service(routes::update_analyte),
+                            )
+                            .service(
+                                web::scope("/data_source")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ManageFish, req, next)
+                                    }))
+                                    .service(routes::create_data_source)
+                                    .service(routes::update_data_source)
+                                    .service(routes::delete_data_source),
+                            )
+                            .service(
+                                web::scope("/sampling_site")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ManageFish, req, next)
+                                    }))
+                                    .service(routes::create_sampling_site)
+                                    .service(routes::update_sampling_site)
+                                    .service(routes::delete_sampling_site),
+                            )
                             .service(
                                 web::scope("/fish_type")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ManageFishTypes, req, next)
+                                    }))
                                     .service(routes::create_fish_type)
                                     .service(routes::update_fish_type)
                                     .service(routes::read_fish_type)
                                     .service(routes::read_all_fish_types)
-                                    .service(routes::update_fish_type_image),
+                                    .service(routes::update_fish_type_image)
+                                    .service(routes::update_length_classes),
+                            )
+                            .service(
+                                web::scope("/analytics")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ViewAnalytics, req, next)
+                                    }))
+                                    .service(routes::get_analytics),
                             )
-                            .service(web::scope("/analytics").service(routes::get_analytics)),
+                            .service(
+                                web::scope("/login_attempt")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ManageUsers, req, next)
+                                    }))
+                                    .service(routes::read_all_login_attempts),
+                            )
+                            .service(
+                                web::scope("/audit")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ViewAuditLog, req, next)
+                                    }))
+                                    .service(routes::read_audit_log),
+                            )
+                            .service(
+                                web::scope("/users")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ManageUsers, req, next)
+                                    }))
+                                    .service(routes::read_all_users)
+                                    .service(routes::read_user)
+                                    .service(routes::update_user_roles)
+                                    .service(routes::disable_user)
+                                    .service(routes::enable_user)
+                                    .service(routes::reset_user_password),
+                            )
+                            .service(
+                                web::scope("/api_key")
+                                    .wrap(from_fn(|req, next| {
+                                        require_permission(Permission::ManageApiKeys, req, next)
+                                    }))
+                                    .service(routes::create_api_key)
+                                    .service(routes::read_all_api_keys)
+                                    .service(routes::revoke_api_key),
+                            ),
                     ),
             )
             .app_data(db_pool.clone())
+            .app_data(session_settings.clone())
+            .app_data(login_throttle.clone())
+            .app_data(scoring.clone())
+            .app_data(email_client.clone())
+            .app_data(base_url.clone())
+            .app_data(unverified_users.clone())
+            .app_data(trusted_proxies.clone())
+            .app_data(configured_api_keys.clone())
     })
     .listen(listener)?
     .run();
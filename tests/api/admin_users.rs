@@ -0,0 +1,133 @@
+use crate::helpers::spawn_app;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn admins_can_list_and_filter_users() {
+    let app = spawn_app().await;
+
+    let response = app.get_users("").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["page"], 1);
+
+    let response = app
+        .get_users(&format!("email={}", &app.test_user.email[..8]))
+        .await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["users"][0]["id"], app.test_user.id.to_string());
+
+    let response = app.get_users("role=super_admin").await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["users"][0]["roles"][0], "super_admin");
+
+    let response = app.get_users("created_after=2999-01-01T00:00:00Z").await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["total"], 0);
+}
+
+#[tokio::test]
+async fn users_are_paginated() {
+    let app = spawn_app().await;
+
+    let response = app.get_users("per_page=1&page=2").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["users"].as_array().unwrap().len(), 1);
+
+    let response = app.get_users("per_page=1&page=3").await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["users"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn admins_can_change_a_users_roles() {
+    let app = spawn_app().await;
+    let body = serde_json::json!({ "roles": ["data_steward"] });
+
+    let response = app.update_user_roles(&app.test_user.id, &body).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["roles"], serde_json::json!(["data_steward"]));
+
+    let response = app
+        .update_user_roles(&app.test_user.id, &serde_json::json!({ "roles": [] }))
+        .await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["roles"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn unknown_roles_and_users_are_rejected() {
+    let app = spawn_app().await;
+
+    let body = serde_json::json!({ "roles": ["owner"] });
+    let response = app.update_user_roles(&app.test_user.id, &body).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let body = serde_json::json!({ "roles": ["data_steward"] });
+    let response = app.update_user_roles(&Uuid::new_v4(), &body).await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn admins_cant_change_their_own_account() {
+    let app = spawn_app().await;
+
+    let body = serde_json::json!({ "roles": [] });
+    let response = app.update_user_roles(&app.admin_user.user_id, &body).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.manage_user(&app.admin_user.user_id, "disable").await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn disabled_users_cant_login_until_enabled() {
+    let app = spawn_app().await;
+    let login_body = format!(
+        "email={}&password={}",
+        &app.test_user.email, &app.test_user.password_hash
+    );
+
+    let response = app.manage_user(&app.test_user.id, "disable").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Existing sessions are revoked.
+    let response = app.get_sessions().await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.login(login_body.clone()).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.manage_user(&app.test_user.id, "enable").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.login(login_body).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn admins_can_send_a_password_reset_email() {
+    let app = spawn_app().await;
+
+    let response = app.manage_user(&app.test_user.id, "password_reset").await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let emails = app.email_client.sent_emails();
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].to, app.test_user.email);
+
+    let response = app
+        .get_audit_log(&format!("entity_id={}", app.test_user.id))
+        .await;
+    let entries: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["entity"], "user");
+    assert_eq!(entries[0]["action"], "update");
+    assert_eq!(entries[0]["actor_id"], app.admin_user.user_id.to_string());
+}
@@ -0,0 +1,115 @@
+use crate::helpers::{spawn_app, FishType};
+
+#[tokio::test]
+async fn you_can_get_advice_for_a_fish_with_the_default_profile() {
+    let app = spawn_app().await;
+
+    let response = app.get_fish_advice(&app.fish.id).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let advice: serde_json::Value = response.json().await.unwrap();
+
+    assert_eq!(advice["profile"]["is_default"], true);
+    assert_eq!(advice["profile"]["sensitive"], false);
+    assert_eq!(advice["reference_doses"]["mercury"], 0.3);
+    assert_eq!(advice["reference_doses"]["pcb"], 0.02);
+    assert_eq!(advice["limiting_contaminant"], "pcb");
+    assert_eq!(advice["category"], "do_not_eat");
+    assert_eq!(advice["meals_per_month"], 0.0);
+}
+
+#[tokio::test]
+async fn advice_uses_the_users_profile() {
+    let app = spawn_app().await;
+
+    let body = "weight=130&age=28&sex=female&plan_to_get_pregnant=true&portion_size=4".to_string();
+    let response = app.update_profile(body).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_fish_advice(&app.fish.id).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let advice: serde_json::Value = response.json().await.unwrap();
+
+    assert_eq!(advice["profile"]["is_default"], false);
+    assert_eq!(advice["profile"]["sensitive"], true);
+    assert_eq!(advice["reference_doses"]["mercury"], 0.1);
+    assert_eq!(advice["mercury"]["reference_dose"], 0.1);
+}
+
+#[tokio::test]
+async fn advice_requires_an_existing_fish() {
+    let app = spawn_app().await;
+
+    let response = app.get_fish_advice(&uuid::Uuid::new_v4()).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn you_can_get_advice_for_a_fish_type_average() {
+    let app = spawn_app().await;
+
+    let response = app.get_fish_type_avg_advice(&app.fish_type.id).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let advice: serde_json::Value = response.json().await.unwrap();
+
+    assert_eq!(advice["fish_id"], app.fish_type.id.to_string());
+
+    let response = app.get_fish_type_avg_advice(&uuid::Uuid::new_v4()).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn a_fish_type_without_samples_gets_no_advice() {
+    let app = spawn_app().await;
+    let fish_type = FishType::new(uuid::Uuid::new_v4());
+    fish_type.store(&app.db_pool).await;
+
+    let response = app.get_fish_type_avg_advice(&fish_type.id).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let advice: serde_json::Value = response.json().await.unwrap();
+
+    assert!(advice["category"].is_null());
+    assert!(advice["meals_per_month"].is_null());
+    assert!(advice["limiting_contaminant"].is_null());
+    assert!(advice["mercury"]["concentration"].is_null());
+    assert!(advice["mercury"]["category"].is_null());
+}
+
+#[tokio::test]
+async fn advice_uses_the_reference_doses_of_the_analyte_catalog() {
+    let app = spawn_app().await;
+    let analytes: Vec<serde_json::Value> = app.get_analytes().await.json().await.unwrap();
+    let mercury = analytes
+        .iter()
+        .find(|analyte| analyte["name"] == "mercury")
+        .expect("Mercury is missing.");
+    assert_eq!(mercury["reference_dose"], 0.1);
+    assert_eq!(mercury["general_reference_dose"], 0.3);
+
+    let mut body = mercury.clone();
+    body["general_reference_dose"] = serde_json::json!(0.2);
+    let response = app
+        .update_analyte(&body, mercury["id"].as_str().unwrap().parse().unwrap())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let advice: serde_json::Value = app
+        .get_fish_advice(&app.fish.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(advice["reference_doses"]["mercury"], 0.2);
+    assert_eq!(advice["mercury"]["reference_dose"], 0.2);
+}
@@ -0,0 +1,180 @@
+use crate::helpers::{spawn_app, TestApp};
+use uuid::Uuid;
+
+fn fish_body(app: &TestApp, measurements: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "fish_type_id": app.fish_type.id,
+        "lake": app.fish.lake,
+        "mercury": 1.1,
+        "omega_3": 1.1,
+        "omega_3_ratio": 1.1,
+        "pcb": 1.1,
+        "protein": 1.1,
+        "measurements": measurements
+    })
+}
+
+fn analyte_body(kind: &str) -> serde_json::Value {
+    serde_json::json!({
+        "name": "cadmium",
+        "display_name": "Cadmium",
+        "kind": kind,
+        "unit": "ppm",
+        "reference_dose": 1.0,
+        "detection_limit": 0.005
+    })
+}
+
+async fn fish_id_with(app: &TestApp, analyte: &str) -> Uuid {
+    sqlx::query_scalar!(
+        "SELECT fish_id FROM measurement WHERE analyte = $1",
+        analyte
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .expect("Failed to get fish.")
+}
+
+fn measurement(measurements: &serde_json::Value, analyte: &str) -> Option<f64> {
+    measurements
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["analyte"] == analyte)
+        .map(|m| m["value"].as_f64().unwrap())
+}
+
+#[tokio::test]
+async fn get_analytes_returns_the_catalog() {
+    let app = spawn_app().await;
+
+    let response = app.get_analytes().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let analytes: Vec<serde_json::Value> = response.json().await.unwrap();
+    let pfas = analytes
+        .iter()
+        .find(|analyte| analyte["name"] == "pfas")
+        .expect("PFAS is missing.");
+    assert_eq!(pfas["kind"], "contaminant");
+    assert_eq!(pfas["unit"], "ppb");
+    let selenium = analytes
+        .iter()
+        .find(|analyte| analyte["name"] == "selenium")
+        .expect("Selenium is missing.");
+    assert_eq!(selenium["kind"], "contaminant");
+    for name in ["mercury", "pcb", "omega_3", "selenium", "vitamin_d"] {
+        assert!(analytes.iter().any(|analyte| analyte["name"] == name));
+    }
+}
+
+#[tokio::test]
+async fn new_analytes_are_returned_with_the_fish() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_new_fish(&fish_body(
+            &app,
+            serde_json::json!({ "selenium": 0.41, "pfas": 2.5 }),
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let fish_id = fish_id_with(&app, "pfas").await;
+    let fish: serde_json::Value = app.get_fish_by_id(fish_id).await.json().await.unwrap();
+    let measurements = &fish["measurements"];
+    assert_eq!(measurement(measurements, "selenium"), Some(0.41));
+    assert_eq!(measurement(measurements, "pfas"), Some(2.5));
+    assert_eq!(measurement(measurements, "mercury"), Some(1.1));
+    assert_eq!(measurement(measurements, "lead"), None);
+}
+
+#[tokio::test]
+async fn a_measured_legacy_analyte_updates_the_legacy_field() {
+    let app = spawn_app().await;
+    let fish_id = app.fish.id;
+
+    let mut body = fish_body(&app, serde_json::json!({ "mercury": 0.5 }));
+    body["mercury"] = serde_json::json!(1.0);
+    let response = app.update_fish(&body, &fish_id.to_string()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let fish: serde_json::Value = app.get_fish_by_id(fish_id).await.json().await.unwrap();
+    assert_eq!(fish["fish_data"]["mercury"], 0.5);
+    assert_eq!(measurement(&fish["measurements"], "mercury"), Some(0.5));
+}
+
+#[tokio::test]
+async fn fish_avg_averages_every_analyte() {
+    let app = spawn_app().await;
+
+    for selenium in [0.2, 0.4] {
+        let response = app
+            .post_new_fish(&fish_body(
+                &app,
+                serde_json::json!({ "selenium": selenium }),
+            ))
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let avg: serde_json::Value = app
+        .get_fish_type_avg(&app.fish_type.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let selenium = measurement(&avg["measurements"], "selenium").unwrap();
+    assert!((selenium - 0.3).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn fish_with_unknown_or_negative_measurements_are_rejected() {
+    let app = spawn_app().await;
+
+    for measurements in [
+        serde_json::json!({ "kryptonite": 1.0 }),
+        serde_json::json!({ "selenium": -1.0 }),
+    ] {
+        let response = app.post_new_fish(&fish_body(&app, measurements)).await;
+        assert_eq!(response.status().as_u16(), 400);
+    }
+}
+
+#[tokio::test]
+async fn admins_can_add_and_update_analytes() {
+    let app = spawn_app().await;
+
+    let response = app.post_new_analyte(&analyte_body("metal")).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let response = app.post_new_analyte(&analyte_body("contaminant")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.post_new_analyte(&analyte_body("contaminant")).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let analytes: Vec<serde_json::Value> = app.get_analytes().await.json().await.unwrap();
+    let cadmium = analytes
+        .iter()
+        .find(|analyte| analyte["name"] == "cadmium")
+        .expect("Cadmium is missing.");
+    let cadmium_id: Uuid = cadmium["id"].as_str().unwrap().parse().unwrap();
+
+    let mut body = analyte_body("contaminant");
+    body["unit"] = serde_json::json!("ppb");
+    let response = app.update_analyte(&body, cadmium_id).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_new_fish(&fish_body(&app, serde_json::json!({ "cadmium": 12.0 })))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let fish_id = fish_id_with(&app, "cadmium").await;
+    let fish: serde_json::Value = app.get_fish_by_id(fish_id).await.json().await.unwrap();
+    let cadmium = fish["measurements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["analyte"] == "cadmium")
+        .expect("Cadmium is missing.");
+    assert_eq!(cadmium["unit"], "ppb");
+}
@@ -41,10 +41,7 @@ async fn only_admin_users_can_post_admin_routes() {
         .api_client
         .post(format!("{}/v1/admin/recipe/", &app.address))
         .json(&body)
-        .header(
-            "Cookie",
-            &format!("user_id={}", &app.test_user.id.to_string()),
-        )
+        .header("Cookie", &format!("session={}", &app.user_session))
         .header("Authorization", &format!("Bearer {}", &app.api_key))
         .send()
         .await
@@ -63,10 +60,7 @@ async fn only_admin_users_can_get_admin_routes() {
             "{}/v1/admin/fish_type/{}",
             app.address, app.fish_type.id
         ))
-        .header(
-            "Cookie",
-            &format!("user_id={}", &app.test_user.id.to_string()),
-        )
+        .header("Cookie", &format!("session={}", &app.user_session))
         .header("Authorization", &format!("Bearer {}", &app.api_key))
         .send()
         .await
@@ -76,7 +70,7 @@ async fn only_admin_users_can_get_admin_routes() {
 }
 
 #[tokio::test]
-async fn a_user_id_cookie_is_required_for_admin_routes() {
+async fn a_session_is_required_for_admin_routes() {
     let app = spawn_app().await;
 
     let response = app
@@ -90,5 +84,5 @@ async fn a_user_id_cookie_is_required_for_admin_routes() {
         .await
         .expect("Failed to post new recipe with test user.");
 
-    assert_eq!(response.status().as_u16(), 500);
+    assert_eq!(response.status().as_u16(), 401);
 }
@@ -0,0 +1,143 @@
+use crate::helpers::spawn_app;
+
+#[derive(serde::Deserialize)]
+struct CreatedApiKey {
+    id: uuid::Uuid,
+    key: String,
+}
+
+#[derive(serde::Deserialize)]
+struct ApiKeyData {
+    id: uuid::Uuid,
+    last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[tokio::test]
+async fn an_admin_can_create_and_revoke_api_keys() {
+    let app = spawn_app().await;
+
+    let response = app
+        .create_api_key(&serde_json::json!({
+            "label": "Test app",
+            "scope": "user"
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let created: CreatedApiKey = response.json().await.unwrap();
+
+    let response = app
+        .api_client
+        .get(format!("{}/v1/fishs", &app.address))
+        .header("Authorization", &format!("Bearer {}", &created.key))
+        .send()
+        .await
+        .expect("Failed to get fishs.");
+    assert_eq!(response.status().as_u16(), 200);
+
+    let keys: Vec<ApiKeyData> = app.get_api_keys().await.json().await.unwrap();
+    let key = keys.iter().find(|key| key.id == created.id).unwrap();
+    assert!(key.last_used_at.is_some());
+
+    let response = app.revoke_api_key(&created.id.to_string()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .api_client
+        .get(format!("{}/v1/fishs", &app.address))
+        .header("Authorization", &format!("Bearer {}", &created.key))
+        .send()
+        .await
+        .expect("Failed to get fishs.");
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn an_expired_api_key_is_rejected() {
+    let app = spawn_app().await;
+
+    let created: CreatedApiKey = app
+        .create_api_key(&serde_json::json!({
+            "label": "Expired app",
+            "scope": "public",
+            "expires_at": "2020-01-01T00:00:00Z"
+        }))
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    let response = app
+        .api_client
+        .get(format!("{}/v1/fishs", &app.address))
+        .header("Authorization", &format!("Bearer {}", &created.key))
+        .send()
+        .await
+        .expect("Failed to get fishs.");
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn a_public_api_key_is_read_only_and_anonymous() {
+    let app = spawn_app().await;
+    let public_key = "987654321";
+
+    let response = app
+        .api_client
+        .get(format!("{}/v1/fishs", &app.address))
+        .header("Authorization", &format!("Bearer {}", public_key))
+        .send()
+        .await
+        .expect("Failed to get fishs.");
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .api_client
+        .post(format!("{}/v1/login", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Authorization", &format!("Bearer {}", public_key))
+        .body(format!(
+            "email={}&password={}",
+            &app.test_user.email, &app.test_user.password_hash
+        ))
+        .send()
+        .await
+        .expect("Failed to login.");
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app
+        .api_client
+        .get(format!("{}/v1/favorite/", &app.address))
+        .header("Cookie", &format!("session={}", &app.user_session))
+        .header("Authorization", &format!("Bearer {}", public_key))
+        .send()
+        .await
+        .expect("Failed to get favorites.");
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn only_admin_api_keys_can_use_admin_routes() {
+    let app = spawn_app().await;
+
+    let created: CreatedApiKey = app
+        .create_api_key(&serde_json::json!({
+            "label": "User app",
+            "scope": "user"
+        }))
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    let response = app
+        .api_client
+        .get(format!("{}/v1/admin/fish_type/", &app.address))
+        .header("Cookie", &format!("session={}", &app.admin_session))
+        .header("Authorization", &format!("Bearer {}", &created.key))
+        .send()
+        .await
+        .expect("Failed to get all fish types.");
+
+    assert_eq!(response.status().as_u16(), 403);
+}
@@ -0,0 +1,104 @@
+use crate::helpers::spawn_app;
+use uuid::Uuid;
+
+fn fish_body(fish_type_id: &Uuid, mercury: f32) -> serde_json::Value {
+    serde_json::json!({
+        "fish_type_id": fish_type_id,
+        "lake": "Huron",
+        "mercury": mercury,
+        "omega_3": 1.1,
+        "omega_3_ratio": 1.1,
+        "pcb": 1.1,
+        "protein": 1.1
+    })
+}
+
+#[tokio::test]
+async fn admin_changes_are_recorded_in_the_audit_log() {
+    let app = spawn_app().await;
+
+    let response = app.post_new_fish(&fish_body(&app.fish_type.id, 1.5)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let fish_id = sqlx::query!(
+        "SELECT id FROM fish WHERE fish_type_id = $1 AND lake = 'Huron'",
+        &app.fish_type.id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .expect("Failed to get the created fish.")
+    .id;
+
+    let response = app
+        .update_fish(&fish_body(&app.fish_type.id, 2.5), &fish_id.to_string())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.delete_fish(&fish_id.to_string()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_audit_log(&format!("entity_id={}", fish_id)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let entries: Vec<serde_json::Value> = response.json().await.unwrap();
+
+    // Newest first.
+    let actions: Vec<&str> = entries
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, vec!["delete", "update", "create"]);
+    for entry in &entries {
+        assert_eq!(entry["entity"], "fish");
+        assert_eq!(entry["actor_id"], app.admin_user.user_id.to_string());
+        assert_eq!(entry["actor_email"], app.admin_user.email);
+    }
+    assert!(entries[2]["before"].is_null());
+    assert_eq!(entries[1]["before"]["mercury"], 1.5);
+    assert_eq!(entries[1]["after"]["mercury"], 2.5);
+    assert!(entries[0]["after"].is_null());
+}
+
+#[tokio::test]
+async fn the_audit_log_can_be_filtered_by_entity_and_actor() {
+    let app = spawn_app().await;
+    let recipe = serde_json::json!({
+        "name": Uuid::new_v4(),
+        "image_url": "https://fake_url.com",
+        "steps": ["step"],
+        "ingredients": ["ingredient"]
+    });
+    app.post_new_recipe(&recipe).await;
+    app.post_new_fish(&fish_body(&app.fish_type.id, 1.5)).await;
+
+    let response = app.get_audit_log("entity=recipe").await;
+    let entries: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["after"]["name"], recipe["name"]);
+
+    let response = app
+        .get_audit_log(&format!("actor_id={}", app.admin_user.user_id))
+        .await;
+    let entries: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert_eq!(entries.len(), 2);
+
+    let response = app
+        .get_audit_log(&format!("actor_id={}", Uuid::new_v4()))
+        .await;
+    let entries: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert!(entries.is_empty());
+}
+
+#[tokio::test]
+async fn audit_log_entries_cant_be_changed() {
+    let app = spawn_app().await;
+    app.post_new_fish(&fish_body(&app.fish_type.id, 1.5)).await;
+
+    let update = sqlx::query!("UPDATE audit_log SET entity = 'recipe'")
+        .execute(&app.db_pool)
+        .await;
+    let delete = sqlx::query!("DELETE FROM audit_log")
+        .execute(&app.db_pool)
+        .await;
+
+    assert!(update.is_err());
+    assert!(delete.is_err());
+}
@@ -0,0 +1,169 @@
+use crate::helpers::{spawn_app, FishType, TestApp};
+use uuid::Uuid;
+
+async fn store_sample(app: &TestApp, fish_type_id: Uuid, lake: &str, mercury: f32, omega_3: f32) {
+    let response = app
+        .post_new_fish(&serde_json::json!({
+            "fish_type_id": fish_type_id,
+            "lake": lake,
+            "mercury": mercury,
+            "omega_3": omega_3,
+            "omega_3_ratio": 1.0,
+            "pcb": 1.0,
+            "protein": 1.0
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn compare_returns_aligned_averages_and_ranks() {
+    let app = spawn_app().await;
+    let other = FishType::new(Uuid::new_v4());
+    other.store(&app.db_pool).await;
+    store_sample(&app, app.fish_type.id, "Huron", 0.5, 1.0).await;
+    store_sample(&app, other.id, "Huron", 0.1, 0.5).await;
+    store_sample(&app, other.id, "Huron", 0.3, 0.5).await;
+
+    let response = app
+        .get_compare(&format!(
+            "fish_type={}&fish_type={}&lake=Huron",
+            other.id, app.fish_type.id
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let fish = body["fish"].as_array().unwrap();
+    assert_eq!(fish[0]["fish_type_id"], other.id.to_string());
+    assert_eq!(fish[1]["fish_type_id"], app.fish_type.id.to_string());
+    assert_eq!(fish[0]["samples"], 2);
+    assert!((fish[0]["mercury"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+    assert_eq!(fish[0]["ranks"]["mercury"], 1);
+    assert_eq!(fish[1]["ranks"]["mercury"], 2);
+    assert_eq!(fish[1]["ranks"]["omega_3"], 1);
+    assert_eq!(fish[0]["ranks"]["protein"], 1);
+    assert_eq!(fish[1]["ranks"]["protein"], 1);
+    assert!((fish[0]["benefit_risk_ratio"].as_f64().unwrap() - 2.5).abs() < 1e-3);
+    assert!((fish[1]["benefit_risk_ratio"].as_f64().unwrap() - 2.0).abs() < 1e-3);
+    assert_eq!(fish[0]["ranks"]["benefit_risk_ratio"], 1);
+}
+
+#[tokio::test]
+async fn compare_includes_fish_without_samples_in_the_lake() {
+    let app = spawn_app().await;
+    store_sample(&app, app.fish_type.id, "Huron", 0.5, 1.0).await;
+
+    let response = app
+        .get_compare(&format!("fish_type={}&lake=Superior", app.fish_type.id))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["fish"][0]["samples"], 0);
+    assert!(body["fish"][0]["mercury"].is_null());
+    assert!(body["fish"][0]["ranks"]["mercury"].is_null());
+}
+
+#[tokio::test]
+async fn compare_floors_mercury_in_the_benefit_risk_ratio_like_rankings() {
+    let app = spawn_app().await;
+    let other = FishType::new(Uuid::new_v4());
+    other.store(&app.db_pool).await;
+    store_sample(&app, other.id, "Huron", 0.0, 0.5).await;
+
+    let response = app
+        .get_compare(&format!("fish_type={}&lake=Huron", other.id))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let compared = body["fish"][0]["benefit_risk_ratio"].as_f64().unwrap();
+    assert!((compared - 0.5 / 0.01).abs() < 1e-6);
+
+    let response = app.get_rankings("lake=Huron").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let rankings: serde_json::Value = response.json().await.unwrap();
+    let ranked = rankings["fish"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|fish| fish["fish_type_id"] == other.id.to_string())
+        .expect("The fish type isn't ranked.");
+    assert_eq!(ranked["benefit_risk_ratio"].as_f64().unwrap(), compared);
+}
+
+#[tokio::test]
+async fn compare_ranks_the_requested_analytes() {
+    let app = spawn_app().await;
+    let other = FishType::new(Uuid::new_v4());
+    other.store(&app.db_pool).await;
+    for (fish_type_id, selenium) in [(app.fish_type.id, 0.6), (other.id, 0.2)] {
+        let response = app
+            .post_new_fish(&serde_json::json!({
+                "fish_type_id": fish_type_id,
+                "lake": "Huron",
+                "mercury": 0.1,
+                "omega_3": 1.0,
+                "omega_3_ratio": 1.0,
+                "pcb": 1.0,
+                "protein": 1.0,
+                "measurements": { "selenium": selenium }
+            }))
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app
+        .get_compare(&format!(
+            "fish_type={}&fish_type={}&lake=Huron&attr=selenium",
+            app.fish_type.id, other.id
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let fish = body["fish"].as_array().unwrap();
+    let measurements = fish[0]["measurements"].as_array().unwrap();
+    assert_eq!(measurements.len(), 1);
+    assert_eq!(measurements[0]["analyte"], "selenium");
+    assert!((measurements[0]["value"].as_f64().unwrap() - 0.6).abs() < 1e-6);
+    // Selenium is a contaminant, the lower level ranks first.
+    assert_eq!(measurements[0]["rank"], 2);
+    assert_eq!(fish[1]["measurements"][0]["rank"], 1);
+}
+
+#[tokio::test]
+async fn compare_rejects_invalid_queries() {
+    let app = spawn_app().await;
+    let too_many = (0..11)
+        .map(|_| format!("fish_type={}", Uuid::new_v4()))
+        .collect::<Vec<_>>()
+        .join("&");
+    let test_cases = [
+        (String::new(), "no fish types"),
+        ("fish_type=herring".to_string(), "an invalid fish type"),
+        (
+            format!("fish_type={}", Uuid::new_v4()),
+            "an unknown fish type",
+        ),
+        (
+            format!("fish_type={}&lake=Atlantis", app.fish_type.id),
+            "an unknown lake",
+        ),
+        (
+            format!("fish_type={}&attr=weight", app.fish_type.id),
+            "an unknown attr",
+        ),
+        (too_many, "too many fish types"),
+    ];
+
+    for (query, description) in test_cases {
+        let response = app.get_compare(&query).await;
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "The API did not reject a comparison with {}.",
+            description
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::helpers::{spawn_app, TestApp};
+use uuid::Uuid;
+
+fn source_body(url: &str) -> serde_json::Value {
+    serde_json::json!({
+        "agency": "GLIFWC",
+        "study": "Mercury in Ceded Territory Walleye",
+        "url": url,
+        "lab_method": "EPA Method 7473",
+        "collection_year": 2019,
+        "license": "CC BY 4.0"
+    })
+}
+
+fn fish_body(app: &TestApp, data_source_id: Uuid) -> serde_json::Value {
+    serde_json::json!({
+        "fish_type_id": app.fish_type.id,
+        "lake": app.fish.lake,
+        "mercury": 1.1,
+        "omega_3": 1.1,
+        "omega_3_ratio": 1.1,
+        "pcb": 1.1,
+        "protein": 1.1,
+        "data_source_id": data_source_id
+    })
+}
+
+async fn new_source(app: &TestApp) -> Uuid {
+    let response = app
+        .post_new_data_source(&source_body("https://glifwc.org/mercury"))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    body["id"].as_str().unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn a_fish_cites_its_data_source() {
+    let app = spawn_app().await;
+    let source_id = new_source(&app).await;
+
+    let response = app.post_new_fish(&fish_body(&app, source_id)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let fish_id = sqlx::query_scalar!("SELECT id FROM fish WHERE data_source_id = $1", source_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to get fish.");
+
+    let fish: serde_json::Value = app.get_fish_by_id(fish_id).await.json().await.unwrap();
+    assert_eq!(fish["data_source"]["agency"], "GLIFWC");
+    assert_eq!(fish["data_source"]["collection_year"], 2019);
+
+    let fish: serde_json::Value = app.get_fish_by_id(app.fish.id).await.json().await.unwrap();
+    assert!(fish["data_source"].is_null());
+
+    let everything: serde_json::Value = app.get_everything().await.json().await.unwrap();
+    assert!(everything["data_sources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|source| source["id"] == source_id.to_string()));
+    assert!(everything["fishs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|fish| fish["data_source_id"] == source_id.to_string()));
+}
+
+#[tokio::test]
+async fn fish_avg_lists_the_sources_of_its_samples() {
+    let app = spawn_app().await;
+    let source_id = new_source(&app).await;
+
+    let response = app
+        .update_fish(&fish_body(&app, source_id), &app.fish.id.to_string())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let avg: serde_json::Value = app
+        .get_fish_type_avg(&app.fish_type.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let sources = avg["data_sources"].as_array().unwrap();
+    assert_eq!(sources.len(), 1);
+    assert_eq!(sources[0]["license"], "CC BY 4.0");
+}
+
+#[tokio::test]
+async fn a_cited_data_source_cannot_be_deleted() {
+    let app = spawn_app().await;
+    let source_id = new_source(&app).await;
+    let response = app.post_new_fish(&fish_body(&app, source_id)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.delete_data_source(source_id).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let uncited_id = new_source(&app).await;
+    let response = app.delete_data_source(uncited_id).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn invalid_data_sources_are_rejected() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_new_data_source(&source_body("javascript:alert(1)"))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let source_id = new_source(&app).await;
+    let mut body = source_body("https://glifwc.org/mercury");
+    body["collection_year"] = serde_json::json!(1066);
+    let response = app.update_data_source(&body, source_id).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_new_fish(&fish_body(&app, Uuid::new_v4())).await;
+    assert_eq!(response.status().as_u16(), 400);
+}
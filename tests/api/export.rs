@@ -0,0 +1,101 @@
+use crate::helpers::{spawn_app, TestApp};
+use std::collections::HashMap;
+
+/// Adds a fish sampled in 2020 without detectable mercury, and one sampled in
+/// 2023 with selenium, to the app's fish.
+async fn import_samples(app: &TestApp) {
+    let csv = format!(
+        "fish_type_id,lake,sample_date,mercury,pcb,selenium\n\
+         {id},{lake},2020-05-01,0,0.01,\n\
+         {id},{lake},2023-07-15,0.2,0.02,0.41\n",
+        id = app.fish_type.id,
+        lake = app.fish.lake
+    );
+    let response = app.import("fish", csv, false).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn fish_are_exported_as_csv_with_a_column_per_analyte() {
+    let app = spawn_app().await;
+    import_samples(&app).await;
+
+    let response = app.get_export("").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/csv"));
+    let body = response.text().await.unwrap();
+
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    let headers = reader.headers().unwrap().clone();
+    assert_eq!(&headers[0], "id");
+    assert!(headers.iter().any(|header| header == "selenium"));
+    let rows: Vec<HashMap<String, String>> = reader
+        .records()
+        .map(|record| {
+            headers
+                .iter()
+                .map(str::to_string)
+                .zip(record.unwrap().iter().map(str::to_string))
+                .collect()
+        })
+        .collect();
+    assert_eq!(rows.len(), 3);
+
+    let old = rows
+        .iter()
+        .find(|row| row["sample_date"] == "2020-05-01")
+        .expect("The 2020 sample is missing.");
+    assert_eq!(old["fish_type"], app.fish_type.name);
+    assert_eq!(old["mercury"], "ND");
+    assert_eq!(old["selenium"], "");
+    let new = rows
+        .iter()
+        .find(|row| row["sample_date"] == "2023-07-15")
+        .expect("The 2023 sample is missing.");
+    assert_eq!(new["selenium"], "0.41");
+}
+
+#[tokio::test]
+async fn fish_are_exported_as_json_lines_between_dates() {
+    let app = spawn_app().await;
+    import_samples(&app).await;
+
+    let query = format!(
+        "format=jsonl&lake={}&fish_type={}&from=2021-01-01&to=2024-12-31",
+        app.fish.lake, app.fish_type.id
+    );
+    let response = app.get_export(&query).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response.text().await.unwrap();
+
+    let lines: Vec<serde_json::Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["sample_date"], "2023-07-15");
+    assert_eq!(lines[0]["measurements"]["selenium"]["value"], 0.41);
+    assert_eq!(lines[0]["measurements"]["selenium"]["detected"], true);
+}
+
+#[tokio::test]
+async fn invalid_exports_are_rejected() {
+    let app = spawn_app().await;
+
+    for query in [
+        "lake=Atlantis",
+        "format=xml",
+        "from=2024-01-01&to=2023-01-01",
+    ] {
+        let response = app.get_export(query).await;
+        assert_eq!(response.status().as_u16(), 400, "{}", query);
+    }
+
+    let response = app.get_export("format=excel").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response.bytes().await.unwrap();
+    assert!(body.starts_with(b"\xEF\xBB\xBFid,"));
+}
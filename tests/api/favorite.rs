@@ -10,7 +10,7 @@ async fn you_shoud_be_able_to_see_your_favorite_fish_and_recipes() {
 }
 
 #[tokio::test]
-async fn a_user_id_is_required_to_see_favorites() {
+async fn a_session_is_required_to_see_favorites() {
     let app = spawn_app().await;
 
     let response = app
@@ -21,23 +21,23 @@ async fn a_user_id_is_required_to_see_favorites() {
         .await
         .expect("Failed to get favorites.");
 
-    assert_eq!(response.status().as_u16(), 500);
+    assert_eq!(response.status().as_u16(), 401);
 }
 
 #[tokio::test]
-async fn a_valid_uuid_user_id_is_required_to_see_favorites() {
+async fn a_valid_session_token_is_required_to_see_favorites() {
     let app = spawn_app().await;
 
     let response = app
         .api_client
         .get(format!("{}/v1/favorite/", &app.address))
-        .header("Cookie", &format!("user_id={}", "not-a-uuid"))
+        .header("Cookie", &format!("session={}", "not-a-token"))
         .header("Authorization", &format!("Bearer {}", &app.api_key))
         .send()
         .await
         .expect("Failed to get favorites.");
 
-    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(response.status().as_u16(), 401);
 }
 
 #[tokio::test]
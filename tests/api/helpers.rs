@@ -1,12 +1,15 @@
 use argon2::password_hash::SaltString;
 use argon2::{Algorithm, Argon2, Params, PasswordHasher, Version};
 use chrono::Utc;
-use fishy_edge::configuration::{get_configuration, DataBaseSettings};
+use fishy_edge::authentication::{create_session, SessionDevice};
+use fishy_edge::configuration::{get_configuration, DataBaseSettings, SessionSettings, Settings};
+use fishy_edge::email_client::InMemoryEmailClient;
 use fishy_edge::startup::run;
 use fishy_edge::telemetry;
 use once_cell::sync::Lazy;
 use sqlx::{Connection, Executor, PgConnection, PgPool};
 use std::net::TcpListener;
+use std::sync::Arc;
 use uuid::Uuid;
 
 pub struct TestApp {
@@ -19,6 +22,10 @@ pub struct TestApp {
     pub recipe: Recipe,
     pub api_client: reqwest::Client,
     pub api_key: &'static str,
+    pub session_settings: SessionSettings,
+    pub user_session: String,
+    pub admin_session: String,
+    pub email_client: Arc<InMemoryEmailClient>,
 }
 
 impl TestApp {
@@ -32,6 +39,18 @@ impl TestApp {
         .await
     }
 
+    pub async fn create_session(&self, user_id: Uuid) -> String {
+        create_session(
+            &self.db_pool,
+            user_id,
+            &SessionDevice::default(),
+            &self.session_settings,
+        )
+        .await
+        .expect("Failed to create session.")
+        .token
+    }
+
     pub async fn post_new_recipe<Body>(&self, body: &Body) -> reqwest::Response
     where
         Body: serde::Serialize,
@@ -39,10 +58,7 @@ impl TestApp {
         self.api_client
             .post(&format!("{}/v1/admin/recipe/", &self.address))
             .json(body)
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -56,10 +72,7 @@ impl TestApp {
         self.api_client
             .put(format!("{}/v1/admin/recipe/{}", &self.address, recipe_id))
             .json(body)
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -76,10 +89,7 @@ impl TestApp {
                 &self.address, &self.recipe.id
             ))
             .json(body)
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -89,10 +99,7 @@ impl TestApp {
     pub async fn delete_recipe(&self, recipe_id: &str) -> reqwest::Response {
         self.api_client
             .delete(format!("{}/v1/admin/recipe/{}", &self.address, recipe_id))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -106,10 +113,7 @@ impl TestApp {
         self.api_client
             .post(&format!("{}/v1/admin/fish_type/", &self.address))
             .json(body)
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -119,10 +123,7 @@ impl TestApp {
     pub async fn get_all_fish_types(&self) -> reqwest::Response {
         self.api_client
             .get(format!("{}/v1/admin/fish_type/", &self.address))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -135,10 +136,7 @@ impl TestApp {
                 "{}/v1/admin/fish_type/{}",
                 &self.address, fish_type_id
             ))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -155,10 +153,7 @@ impl TestApp {
                 &self.address, fish_type_id
             ))
             .json(body)
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -172,10 +167,7 @@ impl TestApp {
         self.api_client
             .post(format!("{}/v1/admin/fish/", &self.address))
             .json(body)
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -189,33 +181,351 @@ impl TestApp {
         self.api_client
             .put(format!("{}/v1/admin/fish/{}", &self.address, fish_id))
             .json(body)
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
             .expect("Failed to update fish.")
     }
 
+    pub async fn post_new_lake<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!("{}/v1/admin/lake/", &self.address))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to post new lake.")
+    }
+
+    pub async fn update_lake<Body>(&self, body: &Body, lake_id: Uuid) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .put(format!("{}/v1/admin/lake/{}", &self.address, lake_id))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to update lake.")
+    }
+
+    pub async fn post_new_analyte<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!("{}/v1/admin/analyte/", &self.address))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to post new analyte.")
+    }
+
+    pub async fn update_analyte<Body>(&self, body: &Body, analyte_id: Uuid) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .put(format!("{}/v1/admin/analyte/{}", &self.address, analyte_id))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to update analyte.")
+    }
+
+    pub async fn get_analytes(&self) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/analytes", &self.address))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get analytes.")
+    }
+
+    pub async fn post_new_data_source<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!("{}/v1/admin/data_source/", &self.address))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to post new data source.")
+    }
+
+    pub async fn update_data_source<Body>(&self, body: &Body, source_id: Uuid) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .put(format!(
+                "{}/v1/admin/data_source/{}",
+                &self.address, source_id
+            ))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to update data source.")
+    }
+
+    pub async fn delete_data_source(&self, source_id: Uuid) -> reqwest::Response {
+        self.api_client
+            .delete(format!(
+                "{}/v1/admin/data_source/{}",
+                &self.address, source_id
+            ))
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to delete data source.")
+    }
+
+    pub async fn update_length_classes<Body>(
+        &self,
+        body: &Body,
+        fish_type_id: Uuid,
+    ) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .put(format!(
+                "{}/v1/admin/fish_type/{}/length_classes",
+                &self.address, fish_type_id
+            ))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to update length classes.")
+    }
+
+    pub async fn import(&self, entity: &str, csv: String, dry_run: bool) -> reqwest::Response {
+        self.api_client
+            .post(format!(
+                "{}/v1/admin/import/{}?dry_run={}",
+                &self.address, entity, dry_run
+            ))
+            .body(csv)
+            .header("Content-Type", "text/csv")
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to import.")
+    }
+
+    pub async fn get_length_classes(&self, fish_type_id: Uuid) -> reqwest::Response {
+        self.api_client
+            .get(format!(
+                "{}/v1/fish_type/{}/length_classes",
+                &self.address, fish_type_id
+            ))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get length classes.")
+    }
+
+    pub async fn post_new_sampling_site<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!("{}/v1/admin/sampling_site/", &self.address))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to post new sampling site.")
+    }
+
+    pub async fn get_compare(&self, query: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/compare?{}", &self.address, query))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get comparison.")
+    }
+
+    pub async fn get_rankings(&self, query: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/rankings?{}", &self.address, query))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get rankings.")
+    }
+
+    pub async fn get_stats(&self, query: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/stats?{}", &self.address, query))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get stats.")
+    }
+
+    pub async fn get_trend(&self, fish_type_id: Uuid, query: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!(
+                "{}/v1/fish_type/{}/trend?{}",
+                &self.address, fish_type_id, query
+            ))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get trend.")
+    }
+
+    pub async fn get_sites_near(&self, query: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/sites/near?{}", &self.address, query))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get nearby sites.")
+    }
+
+    pub async fn delete_lake(&self, lake_id: Uuid) -> reqwest::Response {
+        self.api_client
+            .delete(format!("{}/v1/admin/lake/{}", &self.address, lake_id))
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to delete lake.")
+    }
+
+    pub async fn get_lakes(&self) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/lakes", &self.address))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get lakes.")
+    }
+
     pub async fn delete_fish(&self, fish_id: &str) -> reqwest::Response {
         self.api_client
             .delete(format!("{}/v1/admin/fish/{}", &self.address, fish_id))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
             .expect("Failed to update fish.")
     }
 
+    pub async fn create_api_key<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!("{}/v1/admin/api_key/", &self.address))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to create API key.")
+    }
+
+    pub async fn get_api_keys(&self) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/admin/api_key/", &self.address))
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get API keys.")
+    }
+
+    pub async fn revoke_api_key(&self, api_key_id: &str) -> reqwest::Response {
+        self.api_client
+            .delete(format!("{}/v1/admin/api_key/{}", &self.address, api_key_id))
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to revoke API key.")
+    }
+
+    pub async fn get_audit_log(&self, query: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/admin/audit/?{}", &self.address, query))
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get the audit log.")
+    }
+
+    pub async fn get_users(&self, query: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/admin/users/?{}", &self.address, query))
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get users.")
+    }
+
+    pub async fn update_user_roles<Body>(&self, user_id: &Uuid, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .put(format!(
+                "{}/v1/admin/users/{}/roles",
+                &self.address, user_id
+            ))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to update user roles.")
+    }
+
+    /// `action` is one of `disable`, `enable` or `password_reset`.
+    pub async fn manage_user(&self, user_id: &Uuid, action: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!(
+                "{}/v1/admin/users/{}/{}",
+                &self.address, user_id, action
+            ))
+            .header("Cookie", &format!("session={}", &self.admin_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to manage user.")
+    }
+
     pub async fn update_profile(&self, body: String) -> reqwest::Response {
         self.api_client
             .post(format!("{}/v1/user/profile", &self.address))
             .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .body(body)
             .send()
@@ -227,6 +537,7 @@ impl TestApp {
         self.api_client
             .post(format!("{}/v1/user/account", &self.address))
             .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .body(body)
             .send()
@@ -238,6 +549,7 @@ impl TestApp {
         self.api_client
             .post(format!("{}/v1/user/image", &self.address))
             .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .body(body)
             .send()
@@ -249,6 +561,7 @@ impl TestApp {
         self.api_client
             .post(format!("{}/v1/user/change_password", &self.address))
             .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .body(body)
             .send()
@@ -267,18 +580,199 @@ impl TestApp {
             .expect("Failed to login.")
     }
 
+    pub async fn refresh(&self, body: String) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/v1/refresh", &self.address))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to refresh session.")
+    }
+
+    pub async fn logout(&self) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/v1/logout", &self.address))
+            .header("Cookie", &format!("session={}", &self.user_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to logout.")
+    }
+
+    pub async fn get_sessions(&self) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/user/sessions", &self.address))
+            .header("Cookie", &format!("session={}", &self.user_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get sessions.")
+    }
+
+    pub async fn log_meal<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!("{}/v1/user/meals", &self.address))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.user_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to log meal.")
+    }
+
+    pub async fn get_meals(&self) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/user/meals", &self.address))
+            .header("Cookie", &format!("session={}", &self.user_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get meals.")
+    }
+
+    pub async fn update_meal<Body>(&self, meal_id: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .put(format!("{}/v1/user/meals/{}", &self.address, meal_id))
+            .json(body)
+            .header("Cookie", &format!("session={}", &self.user_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to update meal.")
+    }
+
+    pub async fn delete_meal(&self, meal_id: &str) -> reqwest::Response {
+        self.api_client
+            .delete(format!("{}/v1/user/meals/{}", &self.address, meal_id))
+            .header("Cookie", &format!("session={}", &self.user_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to delete meal.")
+    }
+
+    pub async fn get_meal_summary(&self) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/user/meals/summary", &self.address))
+            .header("Cookie", &format!("session={}", &self.user_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get meal summary.")
+    }
+
+    pub async fn revoke_all_sessions(&self) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/v1/user/sessions/revoke_all", &self.address))
+            .header("Cookie", &format!("session={}", &self.user_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to revoke all sessions.")
+    }
+
+    pub async fn request_password_reset(&self, body: String) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/v1/password_reset/request", &self.address))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to request password reset.")
+    }
+
+    pub async fn confirm_password_reset(&self, body: String) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/v1/password_reset/confirm", &self.address))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to confirm password reset.")
+    }
+
+    pub async fn register(&self, body: String) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/v1/register", &self.address))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to register.")
+    }
+
+    pub async fn resend_verification(&self, email: &str) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/v1/register/resend", &self.address))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .body(format!("email={email}"))
+            .send()
+            .await
+            .expect("Failed to resend the verification email.")
+    }
+
+    /// The verification link from the most recent email sent to `email`.
+    pub fn link_from_email(&self, email: &str) -> String {
+        let email = self
+            .email_client
+            .sent_emails()
+            .into_iter()
+            .rev()
+            .find(|sent| sent.to == email)
+            .expect("No email was sent.");
+
+        email
+            .text_body
+            .split_whitespace()
+            .find(|word| word.starts_with(&self.address))
+            .expect("The email doesn't contain a link.")
+            .to_string()
+    }
+
+    /// The token from the most recent email sent to `email`.
+    pub fn token_from_email(&self, email: &str) -> String {
+        let email = self
+            .email_client
+            .sent_emails()
+            .into_iter()
+            .rev()
+            .find(|sent| sent.to == email)
+            .expect("No email was sent.");
+
+        email
+            .text_body
+            .split_whitespace()
+            .find(|word| word.len() == 64 && word.chars().all(|c| c.is_ascii_hexdigit()))
+            .expect("The email doesn't contain a token.")
+            .to_string()
+    }
+
     pub async fn delete_account(&self) -> reqwest::Response {
         self.api_client
-            .delete(format!("{}/v1/user/{}", &self.address, &self.test_user.id))
+            .delete(format!("{}/v1/user", &self.address))
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
             .expect("Failed to delete account.")
     }
 
-    pub async fn get_search(&self) -> reqwest::Response {
+    pub async fn get_search(&self, query: &str) -> reqwest::Response {
         self.api_client
             .get(format!("{}/v1/search", &self.address))
+            .query(&[("q", query)])
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -288,10 +782,7 @@ impl TestApp {
     pub async fn get_fish_by_id(&self, fish_id: Uuid) -> reqwest::Response {
         self.api_client
             .get(format!("{}/v1/fish/{}", &self.address, fish_id))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -304,16 +795,36 @@ impl TestApp {
                 "{}/v1/fish_avg?fishtype_id={}",
                 &self.address, fish_type_id
             ))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
             .expect("Failed to get fish type avg.")
     }
 
+    pub async fn get_fish_advice(&self, fish_id: &Uuid) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/fish/{}/advice", &self.address, fish_id))
+            .header("Cookie", &format!("session={}", &self.user_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get fish advice.")
+    }
+
+    pub async fn get_fish_type_avg_advice(&self, fish_type_id: &Uuid) -> reqwest::Response {
+        self.api_client
+            .get(format!(
+                "{}/v1/fish_avg/advice?fishtype_id={}",
+                &self.address, fish_type_id
+            ))
+            .header("Cookie", &format!("session={}", &self.user_session))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to get fish type avg advice.")
+    }
+
     pub async fn get_fish_type_avgs(&self) -> reqwest::Response {
         self.api_client
             .get(format!("{}/v1/fish_avgs", &self.address))
@@ -326,10 +837,7 @@ impl TestApp {
     pub async fn get_favorites(&self) -> reqwest::Response {
         self.api_client
             .get(format!("{}/v1/favorite/", &self.address))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.admin_user.user_id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.admin_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -339,10 +847,7 @@ impl TestApp {
     pub async fn favorite_fish(&self, fish_id: &Uuid) -> reqwest::Response {
         self.api_client
             .post(format!("{}/v1/favorite/fish/{}", &self.address, fish_id))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.test_user.id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -352,10 +857,7 @@ impl TestApp {
     pub async fn unfavorite_fish(&self, fish_id: &Uuid) -> reqwest::Response {
         self.api_client
             .post(format!("{}/v1/unfavorite/fish/{}", &self.address, fish_id))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.test_user.id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -368,10 +870,7 @@ impl TestApp {
                 "{}/v1/favorite/recipe/{}",
                 &self.address, recipe_id
             ))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.test_user.id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -384,10 +883,7 @@ impl TestApp {
                 "{}/v1/unfavorite/recipe/{}",
                 &self.address, recipe_id
             ))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.test_user.id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -401,10 +897,7 @@ impl TestApp {
         self.api_client
             .post(format!("{}/v1/presign_s3", &self.address))
             .json(&body)
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.test_user.id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -414,10 +907,7 @@ impl TestApp {
     pub async fn get_recipes(&self) -> reqwest::Response {
         self.api_client
             .get(format!("{}/v1/recipe/", &self.address))
-            .header(
-                "Cookie",
-                &format!("user_id={}", &self.test_user.id.to_string()),
-            )
+            .header("Cookie", &format!("session={}", &self.user_session))
             .header("Authorization", &format!("Bearer {}", &self.api_key))
             .send()
             .await
@@ -433,6 +923,15 @@ impl TestApp {
             .expect("Failed to get everything.")
     }
 
+    pub async fn get_export(&self, query: &str) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/v1/export/fish?{}", &self.address, query))
+            .header("Authorization", &format!("Bearer {}", &self.api_key))
+            .send()
+            .await
+            .expect("Failed to export fish.")
+    }
+
     pub async fn get_min_and_max(&self, lake: &str, attr: &str) -> reqwest::Response {
         self.api_client
             .get(format!(
@@ -456,7 +955,7 @@ async fn configure_database(config: &DataBaseSettings) -> PgPool {
         .await
         .expect("Failed to create database.");
 
-    let connection_pool = PgPool::connect_with(config.without_db())
+    let connection_pool = PgPool::connect_with(config.with_db())
         .await
         .expect("Failed to connect to Postgres.");
 
@@ -484,18 +983,35 @@ static TRACING: Lazy<()> = Lazy::new(|| {
 });
 
 pub async fn spawn_app() -> TestApp {
+    spawn_app_with(|_| {}).await
+}
+
+/// Same as `spawn_app`, but lets a test change the configuration first.
+pub async fn spawn_app_with(customise: impl FnOnce(&mut Settings)) -> TestApp {
     // ensure that trancing is only initialized on the first call
     Lazy::force(&TRACING);
 
     let mut configuration = get_configuration().expect("Failed to read configuration.");
     configuration.database.database_name = Uuid::new_v4().to_string();
+    // Only signed session tokens identify a user in the tests.
+    configuration.session.allow_user_id_cookie = false;
     let connection_pool = configure_database(&configuration.database).await;
 
     let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind to random port.");
     let port = listener.local_addr().unwrap().port();
     let address = format!("http://127.0.0.1:{}", port);
+    configuration.application.base_url = address.clone();
+    customise(&mut configuration);
 
-    let server = run(listener, connection_pool.clone()).expect("Failed to bind address.");
+    let email_client = Arc::new(InMemoryEmailClient::default());
+
+    let server = run(
+        listener,
+        connection_pool.clone(),
+        email_client.clone(),
+        &configuration,
+    )
+    .expect("Failed to bind address.");
 
     let client = reqwest::Client::builder()
         .redirect(reqwest::redirect::Policy::none())
@@ -506,7 +1022,7 @@ pub async fn spawn_app() -> TestApp {
 
     let fish_type_id = Uuid::new_v4();
 
-    let test_app = TestApp {
+    let mut test_app = TestApp {
         address,
         db_pool: connection_pool,
         api_client: client,
@@ -516,6 +1032,10 @@ pub async fn spawn_app() -> TestApp {
         fish: Fish::new(fish_type_id),
         recipe: Recipe::new(),
         api_key: "1234567890",
+        session_settings: configuration.session,
+        user_session: String::new(),
+        admin_session: String::new(),
+        email_client,
     };
 
     test_app.test_user.store(&test_app.db_pool).await;
@@ -524,6 +1044,9 @@ pub async fn spawn_app() -> TestApp {
     test_app.fish.store(&test_app.db_pool).await;
     test_app.recipe.store(&test_app.db_pool).await;
 
+    test_app.user_session = test_app.create_session(test_app.test_user.id).await;
+    test_app.admin_session = test_app.create_session(test_app.admin_user.user_id).await;
+
     test_app
 }
 
@@ -547,6 +1070,8 @@ pub struct TestUser {
     pub image_url: Option<String>,
     pub created_at: Option<chrono::DateTime<Utc>>,
     pub latest_login: Option<chrono::DateTime<Utc>>,
+    pub email_verified_at: Option<chrono::DateTime<Utc>>,
+    pub disabled_at: Option<chrono::DateTime<Utc>>,
 }
 
 impl TestUser {
@@ -567,6 +1092,8 @@ impl TestUser {
             image_url: None,
             created_at: Some(Utc::now()),
             latest_login: Some(Utc::now()),
+            email_verified_at: None,
+            disabled_at: None,
         }
     }
 
@@ -595,7 +1122,7 @@ impl TestUser {
 }
 
 pub struct AdminUser {
-    user_id: Uuid,
+    pub user_id: Uuid,
     pub email: String,
     pub password: String,
 }
@@ -631,9 +1158,22 @@ impl AdminUser {
         .execute(pool)
         .await
         .expect("Failed to store admin user.");
+
+        grant_role(pool, self.user_id, "super_admin").await;
     }
 }
 
+pub async fn grant_role(pool: &PgPool, user_id: Uuid, role: &str) {
+    sqlx::query!(
+        "INSERT INTO user_roles (user_id, role) VALUES ($1, $2);",
+        user_id,
+        role
+    )
+    .execute(pool)
+    .await
+    .expect("Failed to grant role.");
+}
+
 pub struct FishType {
     pub id: Uuid,
     pub name: &'static str,
@@ -695,6 +1235,18 @@ impl Fish {
     }
 
     pub async fn store(&self, db_pool: &PgPool) {
+        sqlx::query!(
+            r#"
+            INSERT INTO lake (id, name)
+            VALUES ($1, $2)
+            ON CONFLICT (name) DO NOTHING
+            "#,
+            Uuid::new_v4(),
+            &self.lake,
+        )
+        .execute(db_pool)
+        .await
+        .expect("Failed to store lake.");
         sqlx::query!(
             r#"
             INSERT INTO fish (id, fish_type_id, lake, mercury, omega_3, omega_3_ratio, pcb, protein)
@@ -0,0 +1,337 @@
+use crate::helpers::{spawn_app, TestApp};
+
+const HEADER: &str = "fish_type_id,lake,date_sampled,mercury,omega_3,omega_3_ratio,pcb,protein";
+
+fn row(app: &TestApp, lake: &str, mercury: &str) -> String {
+    format!(
+        "{},{},2024-05-01 10:30:00,{},1.1,1.1,1.1,1.1",
+        app.fish_type.id, lake, mercury
+    )
+}
+
+async fn fish_count(app: &TestApp) -> i64 {
+    sqlx::query_scalar!(r#"SELECT count(*) as "count!" FROM fish"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count fish.")
+}
+
+#[tokio::test]
+async fn valid_rows_are_imported() {
+    let app = spawn_app().await;
+    let before = fish_count(&app).await;
+    let csv = format!(
+        "{}\n{}\n{}\n",
+        HEADER,
+        row(&app, &app.fish.lake, "0.2"),
+        row(&app, &app.fish.lake, "0.4")
+    );
+
+    let response = app.import("fish", csv, false).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["rows"], 2);
+    assert_eq!(report["imported"], 2);
+    assert_eq!(report["errors"].as_array().unwrap().len(), 0);
+    assert_eq!(fish_count(&app).await, before + 2);
+
+    let audited = sqlx::query_scalar!(
+        r#"SELECT count(*) as "count!" FROM audit_log WHERE entity = 'fish' AND action = 'create'"#
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(audited, 2);
+}
+
+#[tokio::test]
+async fn a_dry_run_reports_errors_per_row_and_saves_nothing() {
+    let app = spawn_app().await;
+    let before = fish_count(&app).await;
+    let csv = format!(
+        "{}\n{}\n{}\n{},{},,1,1,1,1,1\n",
+        HEADER,
+        row(&app, &app.fish.lake, "0.2"),
+        row(&app, "Atlantis", "lots"),
+        uuid::Uuid::new_v4(),
+        app.fish.lake
+    );
+
+    let response = app.import("fish", csv, true).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["rows"], 3);
+    assert_eq!(report["imported"], 0);
+    let errors: Vec<(u64, String)> = report["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e["row"].as_u64().unwrap(),
+                e["column"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            (3, "lake".to_string()),
+            (3, "mercury".to_string()),
+            (4, "fish_type_id".to_string())
+        ]
+    );
+    assert_eq!(fish_count(&app).await, before);
+}
+
+#[tokio::test]
+async fn nothing_is_imported_when_a_row_is_invalid() {
+    let app = spawn_app().await;
+    let before = fish_count(&app).await;
+    let csv = format!(
+        "{}\n{}\n{}\n",
+        HEADER,
+        row(&app, &app.fish.lake, "0.2"),
+        row(&app, &app.fish.lake, "-1")
+    );
+
+    let response = app.import("fish", csv, false).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["imported"], 0);
+    assert_eq!(report["errors"][0]["row"], 3);
+    assert_eq!(fish_count(&app).await, before);
+}
+
+#[tokio::test]
+async fn concurrent_imports_of_the_same_id_report_a_row_error() {
+    let app = spawn_app().await;
+    let before = fish_count(&app).await;
+    let csv = format!(
+        "id,{}\n{},{}\n",
+        HEADER,
+        uuid::Uuid::new_v4(),
+        row(&app, &app.fish.lake, "0.2")
+    );
+
+    let (first, second) = tokio::join!(
+        app.import("fish", csv.clone(), false),
+        app.import("fish", csv, false)
+    );
+    let mut statuses = [first.status().as_u16(), second.status().as_u16()];
+    statuses.sort();
+    assert_eq!(statuses, [200, 400]);
+    let rejected = if first.status().as_u16() == 400 {
+        first
+    } else {
+        second
+    };
+    let report: serde_json::Value = rejected.json().await.unwrap();
+    assert_eq!(report["errors"][0]["row"], 2);
+    assert_eq!(report["errors"][0]["column"], "id");
+    assert_eq!(fish_count(&app).await, before + 1);
+}
+
+#[tokio::test]
+async fn analyte_columns_are_imported_as_measurements() {
+    let app = spawn_app().await;
+    let id = uuid::Uuid::new_v4();
+    let csv = format!(
+        "id,fish_type_id,lake,length_cm,selenium\n{},{},{},42.5,0.41\n",
+        id, app.fish_type.id, app.fish.lake
+    );
+
+    let response = app.import("fish", csv, false).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let fish: serde_json::Value = app.get_fish_by_id(id).await.json().await.unwrap();
+    let selenium = fish["measurements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["analyte"] == "selenium")
+        .expect("Selenium is missing.");
+    assert_eq!(selenium["value"].as_f64(), Some(0.41));
+
+    let csv = format!(
+        "fish_type_id,lake,kryptonite\n{},{},1\n",
+        app.fish_type.id, app.fish.lake
+    );
+    let response = app.import("fish", csv, false).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["errors"][0]["row"], 1);
+    assert_eq!(report["errors"][0]["column"], "kryptonite");
+}
+
+#[tokio::test]
+async fn non_detects_are_imported_with_their_detection_limit() {
+    let app = spawn_app().await;
+    let (below, unknown) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+    let csv = format!(
+        "id,fish_type_id,lake,mercury,pcb,selenium,selenium_detection_limit\n\
+        {},{},{},0.2,<0.005,ND,0.1\n\
+        {},{},{},0.3,0.01,nd,\n",
+        below, app.fish_type.id, app.fish.lake, unknown, app.fish_type.id, app.fish.lake
+    );
+
+    let response = app.import("fish", csv, false).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let measurement = |fish: &serde_json::Value, analyte: &str| {
+        fish["measurements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|m| m["analyte"] == analyte)
+            .cloned()
+            .expect("The measurement is missing.")
+    };
+    let fish: serde_json::Value = app.get_fish_by_id(below).await.json().await.unwrap();
+    let pcb = measurement(&fish, "pcb");
+    assert_eq!(pcb["value"], 0.0);
+    assert_eq!(pcb["detected"], false);
+    assert_eq!(pcb["detection_limit"], 0.005);
+    assert_eq!(fish["fish_data"]["pcb"], 0.0);
+    let selenium = measurement(&fish, "selenium");
+    assert_eq!(selenium["detected"], false);
+    assert_eq!(selenium["detection_limit"], 0.1);
+    assert_eq!(measurement(&fish, "mercury")["detected"], true);
+
+    let fish: serde_json::Value = app.get_fish_by_id(unknown).await.json().await.unwrap();
+    let selenium = measurement(&fish, "selenium");
+    assert_eq!(selenium["value"], 0.0);
+    assert_eq!(selenium["detected"], false);
+    assert!(selenium["detection_limit"].is_null());
+}
+
+#[tokio::test]
+async fn invalid_detection_limits_are_rejected() {
+    let app = spawn_app().await;
+    let csv = format!(
+        "fish_type_id,lake,pcb,selenium,selenium_detection_limit\n\
+        {id},{lake},<0,0.4,\n\
+        {id},{lake},<lots,,\n\
+        {id},{lake},0.1,<0.2,0.3\n\
+        {id},{lake},0.1,,0.3\n",
+        id = app.fish_type.id,
+        lake = app.fish.lake
+    );
+
+    let response = app.import("fish", csv, true).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let report: serde_json::Value = response.json().await.unwrap();
+    let errors: Vec<(u64, &str)> = report["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| (e["row"].as_u64().unwrap(), e["column"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (2, "pcb"),
+            (3, "pcb"),
+            (4, "selenium_detection_limit"),
+            (5, "selenium_detection_limit")
+        ]
+    );
+}
+
+fn counts(report: &serde_json::Value) -> (u64, u64, u64) {
+    (
+        report["created"].as_u64().unwrap(),
+        report["updated"].as_u64().unwrap(),
+        report["unchanged"].as_u64().unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn fish_types_are_upserted_by_name() {
+    let app = spawn_app().await;
+    let csv = format!(
+        "name,anishinaabe_name,about\n{},{},A new about.\nTest Sturgeon,Name,About sturgeon.\n",
+        app.fish_type.name, app.fish_type.anishinaabe_name
+    );
+
+    let response = app.import("fish_type", csv.clone(), false).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(counts(&report), (1, 1, 0));
+    let about = sqlx::query_scalar!(
+        "SELECT about FROM fish_type WHERE id = $1",
+        app.fish_type.id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(about, "A new about.");
+
+    let response = app.import("fish_type", csv, false).await;
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(counts(&report), (0, 0, 2));
+}
+
+#[tokio::test]
+async fn recipes_are_imported_from_array_literals() {
+    let app = spawn_app().await;
+    let csv = r#"name,ingredients,steps,image_url
+Smoked Whitefish,"{""1 whitefish"",""Salt, to taste""}","{""Brine the fish"",Smoke}",
+Fried Perch,"{Perch}",Fry the fish,
+"#;
+
+    let response = app.import("recipe", csv.to_string(), true).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["errors"][0]["row"], 3);
+    assert_eq!(report["errors"][0]["column"], "steps");
+
+    let response = app.import("recipe", csv.to_string(), false).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let saved = sqlx::query_scalar!("SELECT count(*) FROM recipe WHERE name = 'Smoked Whitefish'")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved, Some(0));
+
+    let csv = csv.replace("Fry the fish", r#""{""Fry the fish""}""#);
+    let response = app.import("recipe", csv, false).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(counts(&report), (2, 0, 0));
+    let ingredients =
+        sqlx::query_scalar!("SELECT ingredients FROM recipe WHERE name = 'Smoked Whitefish'")
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        ingredients,
+        Some(vec![
+            "1 whitefish".to_string(),
+            "Salt, to taste".to_string()
+        ])
+    );
+}
+
+#[tokio::test]
+async fn fish_types_and_recipes_are_linked_by_name() {
+    let app = spawn_app().await;
+    let csv = format!(
+        "fish_type,recipe\n{},{}\n",
+        app.fish_type.name, app.recipe.name
+    );
+
+    let response = app.import("fish_recipe", csv.clone(), false).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(counts(&report), (1, 0, 0));
+    let response = app.import("fish_recipe", csv, false).await;
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(counts(&report), (0, 0, 1));
+
+    let csv = format!("fishtype_id,recipe\n{},Unknown Recipe\n", app.fish_type.id);
+    let response = app.import("fish_recipe", csv, false).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["errors"][0]["column"], "recipe");
+}
@@ -0,0 +1,185 @@
+use crate::helpers::spawn_app;
+use uuid::Uuid;
+
+fn lake_body(name: &str) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "anishinaabe_name": "Waabishkiigoo-gichigami",
+        "region": "Great Lakes",
+        "description": null,
+        "latitude": 42.2,
+        "longitude": -81.2,
+        "image_url": null
+    })
+}
+
+fn fish_body(fish_type_id: &Uuid, lake: &str) -> serde_json::Value {
+    serde_json::json!({
+        "fish_type_id": fish_type_id,
+        "lake": lake,
+        "mercury": 1.1,
+        "omega_3": 1.1,
+        "omega_3_ratio": 1.1,
+        "pcb": 1.1,
+        "protein": 1.1
+    })
+}
+
+async fn lake_id(app: &crate::helpers::TestApp, name: &str) -> Option<Uuid> {
+    sqlx::query!("SELECT id FROM lake WHERE name = $1", name)
+        .fetch_optional(&app.db_pool)
+        .await
+        .expect("Failed to get lake.")
+        .map(|row| row.id)
+}
+
+#[tokio::test]
+async fn get_lakes_returns_the_seeded_lakes() {
+    let app = spawn_app().await;
+
+    let response = app.get_lakes().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let lakes: Vec<serde_json::Value> = response.json().await.unwrap();
+    let superior = lakes
+        .iter()
+        .find(|lake| lake["name"] == "Superior")
+        .expect("Superior is missing.");
+    assert_eq!(superior["anishinaabe_name"], "Gichigami");
+    assert!(superior["latitude"].is_f64());
+    for name in ["Store", "Huron", "Michigan", "Inland"] {
+        assert!(lakes.iter().any(|lake| lake["name"] == name));
+    }
+}
+
+#[tokio::test]
+async fn a_new_lake_can_be_used_for_fish_without_a_release() {
+    let app = spawn_app().await;
+
+    let response = app.post_new_lake(&lake_body("Erie")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let lakes: Vec<serde_json::Value> = app.get_lakes().await.json().await.unwrap();
+    assert!(lakes.iter().any(|lake| lake["name"] == "Erie"));
+
+    let response = app
+        .post_new_fish(&fish_body(&app.fish_type.id, "Erie"))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_min_and_max("Erie", "mercury").await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn new_fish_rejects_an_unknown_lake() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_new_fish(&fish_body(&app.fish_type.id, "Atlantis"))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let count = sqlx::query!(r#"SELECT count(*) as "count!" FROM fish WHERE lake = 'Atlantis'"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .count;
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn renaming_a_lake_renames_it_on_its_fish() {
+    let app = spawn_app().await;
+    app.post_new_lake(&lake_body("Erie")).await;
+    app.post_new_fish(&fish_body(&app.fish_type.id, "Erie"))
+        .await;
+    let erie = lake_id(&app, "Erie").await.unwrap();
+
+    let response = app.update_lake(&lake_body("Lake Erie"), erie).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let lake = sqlx::query!(
+        "SELECT lake FROM fish WHERE fish_type_id = $1 AND lake LIKE '%Erie'",
+        &app.fish_type.id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+    .lake;
+    assert_eq!(lake, "Lake Erie");
+}
+
+#[tokio::test]
+async fn invalid_lakes_are_rejected() {
+    let app = spawn_app().await;
+
+    let mut no_longitude = lake_body("Erie");
+    no_longitude["longitude"] = serde_json::Value::Null;
+    let mut out_of_range = lake_body("Erie");
+    out_of_range["latitude"] = serde_json::json!(120.0);
+    let test_cases = [
+        (lake_body("Superior"), "a duplicate name"),
+        (lake_body(" "), "an empty name"),
+        (no_longitude, "half a centroid"),
+        (out_of_range, "an out of range centroid"),
+    ];
+
+    for (body, description) in test_cases {
+        let response = app.post_new_lake(&body).await;
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "The API did not reject a lake with {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn lakes_with_fish_cannot_be_deleted() {
+    let app = spawn_app().await;
+    app.post_new_lake(&lake_body("Erie")).await;
+    app.post_new_fish(&fish_body(&app.fish_type.id, "Erie"))
+        .await;
+    let erie = lake_id(&app, "Erie").await.unwrap();
+
+    let response = app.delete_lake(erie).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "The lake still has fish samples."
+    );
+
+    sqlx::query!("DELETE FROM fish WHERE lake = 'Erie'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    let response = app.delete_lake(erie).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(lake_id(&app, "Erie").await.is_none());
+}
+
+#[tokio::test]
+async fn lakes_with_sampling_sites_cannot_be_deleted() {
+    let app = spawn_app().await;
+    app.post_new_lake(&lake_body("Erie")).await;
+    let response = app
+        .post_new_sampling_site(&serde_json::json!({
+            "name": "Presque Isle",
+            "water_body": "Erie",
+            "lake": "Erie",
+            "agency": "GLIFWC",
+            "latitude": 42.16,
+            "longitude": -80.1
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let erie = lake_id(&app, "Erie").await.unwrap();
+
+    let response = app.delete_lake(erie).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "The lake still has sampling sites."
+    );
+}
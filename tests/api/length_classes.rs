@@ -0,0 +1,150 @@
+use crate::helpers::{spawn_app, FishType, TestApp};
+use uuid::Uuid;
+
+fn classes_body() -> serde_json::Value {
+    serde_json::json!([
+        { "name": "large", "min_length_cm": 38.1, "max_length_cm": null },
+        { "name": "small", "min_length_cm": 0.0, "max_length_cm": 38.1 }
+    ])
+}
+
+fn fish_body(fish_type_id: Uuid, length_cm: Option<f64>, mercury: f64) -> serde_json::Value {
+    serde_json::json!({
+        "fish_type_id": fish_type_id,
+        "lake": "Huron",
+        "mercury": mercury,
+        "omega_3": 1.1,
+        "omega_3_ratio": 1.1,
+        "pcb": 1.1,
+        "protein": 1.1,
+        "length_cm": length_cm,
+        "weight_g": length_cm.map(|_| 500.0)
+    })
+}
+
+/// A fish type with a small fish, a large fish and a fish without a length,
+/// with 0.1, 0.5 and 0.9 mercury.
+async fn sized_fish_type(app: &TestApp) -> Uuid {
+    let fish_type = FishType {
+        name: "Sized Fish",
+        ..FishType::new(Uuid::new_v4())
+    };
+    fish_type.store(&app.db_pool).await;
+    let response = app
+        .update_length_classes(&classes_body(), fish_type.id)
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    for (length_cm, mercury) in [(Some(30.0), 0.1), (Some(50.0), 0.5), (None, 0.9)] {
+        let response = app
+            .post_new_fish(&fish_body(fish_type.id, length_cm, mercury))
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+    fish_type.id
+}
+
+async fn get(app: &TestApp, path: &str) -> reqwest::Response {
+    app.api_client
+        .get(format!("{}/v1/{}", &app.address, path))
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn length_classes_are_listed_shortest_first() {
+    let app = spawn_app().await;
+
+    let response = app
+        .update_length_classes(&classes_body(), app.fish_type.id)
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let classes: Vec<serde_json::Value> = app
+        .get_length_classes(app.fish_type.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(classes.len(), 2);
+    assert_eq!(classes[0]["name"], "small");
+    assert_eq!(classes[1]["name"], "large");
+    assert!(classes[1]["max_length_cm"].is_null());
+}
+
+#[tokio::test]
+async fn invalid_length_classes_are_rejected() {
+    let app = spawn_app().await;
+
+    for body in [
+        serde_json::json!([{ "name": "small", "min_length_cm": 40.0, "max_length_cm": 30.0 }]),
+        serde_json::json!([{ "name": "Under 15\"", "max_length_cm": 38.1 }]),
+        serde_json::json!([{ "name": "small" }, { "name": "small" }]),
+    ] {
+        let response = app.update_length_classes(&body, app.fish_type.id).await;
+        assert_eq!(response.status().as_u16(), 400);
+    }
+
+    let response = app
+        .post_new_fish(&fish_body(app.fish_type.id, Some(-1.0), 0.1))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn fish_avg_only_averages_the_length_class() {
+    let app = spawn_app().await;
+    let fish_type_id = sized_fish_type(&app).await;
+
+    for (length_class, expected) in [("small", 0.1), ("large", 0.5)] {
+        let response = get(
+            &app,
+            &format!("fish_avg?fishtype_id={fish_type_id}&length_class={length_class}"),
+        )
+        .await;
+        assert_eq!(response.status().as_u16(), 200);
+
+        let avg: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(avg["length_class"], length_class);
+        assert_eq!(avg["length_classes"].as_array().unwrap().len(), 2);
+        assert!((avg["fish_data"]["mercury"].as_f64().unwrap() - expected).abs() < 1e-9);
+    }
+
+    let avg: serde_json::Value = get(&app, &format!("fish_avg?fishtype_id={fish_type_id}"))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert!((avg["fish_data"]["mercury"].as_f64().unwrap() - 0.5).abs() < 1e-9);
+
+    let response = get(
+        &app,
+        &format!("fish_avg?fishtype_id={fish_type_id}&length_class=huge"),
+    )
+    .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = get(
+        &app,
+        &format!("fish_avg/advice?fishtype_id={fish_type_id}&length_class=small"),
+    )
+    .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn fishs_can_be_narrowed_down_to_a_length_class() {
+    let app = spawn_app().await;
+    sized_fish_type(&app).await;
+
+    let response = get(&app, "fishs?lake=Huron&length_class=large").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let fish: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert_eq!(fish.len(), 1);
+    assert_eq!(fish[0]["length_cm"], 50.0);
+    assert_eq!(fish[0]["weight_g"], 500.0);
+
+    let response = get(&app, "fishs?lake=Huron&length_class=huge").await;
+    assert_eq!(response.status().as_u16(), 400);
+}
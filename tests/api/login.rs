@@ -1,4 +1,4 @@
-use crate::helpers::spawn_app;
+use crate::helpers::{spawn_app, spawn_app_with, TestApp};
 
 #[tokio::test]
 async fn a_user_should_be_able_to_login() {
@@ -35,3 +35,115 @@ async fn an_email_needs_to_be_included_in_the_request() {
 
     assert_eq!(response.status().as_u16(), 400);
 }
+
+/// Logs in through a proxy that forwards `ip`. The app only believes it when
+/// the test trusts the loopback address as a proxy.
+async fn login_from(app: &TestApp, email: &str, password: &str, ip: &str) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/v1/login", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("X-Forwarded-For", ip)
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .body(format!("email={email}&password={password}"))
+        .send()
+        .await
+        .expect("Failed to login.")
+}
+
+#[tokio::test]
+async fn repeated_failures_lock_the_account_out() {
+    let app = spawn_app().await;
+    let email = &app.test_user.email;
+
+    for _ in 0..5 {
+        let response = login_from(&app, email, "wrong-password", "203.0.113.1").await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+
+    // Even the right password is rejected during the lockout, from any address.
+    let response = login_from(&app, email, &app.test_user.password_hash, "203.0.113.2").await;
+    assert_eq!(response.status().as_u16(), 429);
+    let retry_after: i64 = response.headers()["Retry-After"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after > 0 && retry_after <= 30);
+
+    let response = app
+        .api_client
+        .get(format!(
+            "{}/v1/admin/login_attempt/?email={}",
+            &app.address, email
+        ))
+        .header("Cookie", &format!("session={}", &app.admin_session))
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .send()
+        .await
+        .expect("Failed to get login attempts.");
+    assert_eq!(response.status().as_u16(), 200);
+    let attempts: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert_eq!(attempts.len(), 6);
+    assert_eq!(attempts[0]["outcome"], "locked_out");
+    assert_eq!(attempts[1]["outcome"], "failure");
+}
+
+#[tokio::test]
+async fn a_successful_login_resets_the_failed_attempts() {
+    let app = spawn_app().await;
+    let email = &app.test_user.email;
+
+    for _ in 0..4 {
+        login_from(&app, email, "wrong-password", "203.0.113.1").await;
+    }
+    let response = login_from(&app, email, &app.test_user.password_hash, "203.0.113.1").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    for _ in 0..4 {
+        let response = login_from(&app, email, "wrong-password", "203.0.113.1").await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+}
+
+#[tokio::test]
+async fn repeated_failures_lock_the_ip_address_out() {
+    let app = spawn_app_with(|config| {
+        config.login_throttle.ip_free_attempts = 3;
+        config.application.trusted_proxies = vec!["127.0.0.1/32".parse().unwrap()];
+    })
+    .await;
+
+    for attempt in 0..3 {
+        let email = format!("unknown-{attempt}@example.com");
+        let response = login_from(&app, &email, "wrong-password", "203.0.113.1").await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+
+    let password = &app.test_user.password_hash;
+    let response = login_from(&app, &app.test_user.email, password, "203.0.113.1").await;
+    assert_eq!(response.status().as_u16(), 429);
+
+    // Addresses the client added in front of the proxy's are ignored.
+    let spoofed = "203.0.113.2, 203.0.113.1";
+    let response = login_from(&app, &app.test_user.email, password, spoofed).await;
+    assert_eq!(response.status().as_u16(), 429);
+
+    let response = login_from(&app, &app.test_user.email, password, "203.0.113.2").await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn the_forwarded_address_is_ignored_without_a_trusted_proxy() {
+    let app = spawn_app_with(|config| config.login_throttle.ip_free_attempts = 3).await;
+
+    for attempt in 0..3 {
+        let email = format!("unknown-{attempt}@example.com");
+        let ip = format!("203.0.113.{attempt}");
+        let response = login_from(&app, &email, "wrong-password", &ip).await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+
+    let password = &app.test_user.password_hash;
+    let response = login_from(&app, &app.test_user.email, password, "203.0.113.9").await;
+    assert_eq!(response.status().as_u16(), 429);
+}
@@ -1,7 +1,15 @@
 mod admin;
+mod admin_users;
+mod advice;
+mod analytes;
 mod api;
+mod api_key;
+mod audit;
 mod change_password;
+mod compare;
+mod data_sources;
 mod everything;
+mod export;
 mod favorite;
 mod fish;
 mod fish_avg;
@@ -9,10 +17,22 @@ mod fish_avgs;
 mod fishs;
 mod health_check;
 mod helpers;
+mod import;
+mod lakes;
+mod length_classes;
 mod login;
+mod meals;
 mod min_and_max;
+mod non_detects;
+mod password_reset;
 mod presign_s3;
+mod rankings;
 mod recipe;
 mod register;
+mod roles;
 mod search;
+mod session;
+mod sites;
+mod stats;
+mod trend;
 mod user;
@@ -0,0 +1,188 @@
+use crate::helpers::{spawn_app, FishType};
+use chrono::{Days, Utc};
+use uuid::Uuid;
+
+const GRAMS_PER_OZ: f64 = 28.349_523_125;
+const DEFAULT_WEIGHT_KG: f64 = 180.0 * 0.453_592_37;
+
+fn assert_close(actual: &serde_json::Value, expected: f64) {
+    let actual = actual.as_f64().unwrap();
+    assert!(
+        (actual - expected).abs() < 0.01,
+        "{actual} is not {expected}"
+    );
+}
+
+#[tokio::test]
+async fn meals_default_to_the_profile_portion_size() {
+    let app = spawn_app().await;
+    let body = serde_json::json!({
+        "fish_type_id": app.fish_type.id,
+        "eaten_on": Utc::now().date_naive(),
+    });
+
+    let response = app.log_meal(&body).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let meal: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(meal["portion_size"], 8);
+    assert_eq!(meal["fish_type_name"], app.fish_type.name);
+    assert_close(&meal["mercury"], 1.12 * 8.0 * GRAMS_PER_OZ);
+
+    let response = app.get_meals().await;
+    let meals: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert_eq!(meals.len(), 1);
+    assert_eq!(meals[0]["id"], meal["id"]);
+}
+
+#[tokio::test]
+async fn meals_of_a_sample_use_its_fish_type_and_lake() {
+    let app = spawn_app().await;
+    let body = serde_json::json!({
+        "fish_id": app.fish.id,
+        "eaten_on": Utc::now().date_naive(),
+        "portion_size": 4
+    });
+
+    let response = app.log_meal(&body).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let meal: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(meal["fish_type_id"], app.fish_type.id.to_string());
+    assert_eq!(meal["lake"], app.fish.lake);
+    assert_close(&meal["pcb"], 1.12 * 4.0 * GRAMS_PER_OZ);
+}
+
+#[tokio::test]
+async fn invalid_meals_are_rejected() {
+    let app = spawn_app().await;
+    let today = Utc::now().date_naive();
+    let test_cases = vec![
+        (serde_json::json!({ "eaten_on": today }), "no fish"),
+        (
+            serde_json::json!({ "fish_type_id": Uuid::new_v4(), "eaten_on": today }),
+            "an unknown fish type",
+        ),
+        (
+            serde_json::json!({ "fish_id": Uuid::new_v4(), "eaten_on": today }),
+            "an unknown fish",
+        ),
+        (
+            serde_json::json!({ "fish_type_id": app.fish_type.id, "lake": "Erie", "eaten_on": today }),
+            "an invalid lake",
+        ),
+        (
+            serde_json::json!({ "fish_type_id": app.fish_type.id, "portion_size": 0, "eaten_on": today }),
+            "an empty portion",
+        ),
+    ];
+
+    for (body, error) in test_cases {
+        let response = app.log_meal(&body).await;
+
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "The API did not fail with 400 Bad Request when the meal had {error}."
+        );
+    }
+}
+
+#[tokio::test]
+async fn users_can_update_and_delete_only_their_meals() {
+    let mut app = spawn_app().await;
+    let body = serde_json::json!({
+        "fish_type_id": app.fish_type.id,
+        "eaten_on": Utc::now().date_naive(),
+    });
+    let meal: serde_json::Value = app.log_meal(&body).await.json().await.unwrap();
+    let meal_id = meal["id"].as_str().unwrap().to_string();
+
+    let body = serde_json::json!({
+        "fish_type_id": app.fish_type.id,
+        "eaten_on": Utc::now().date_naive(),
+        "portion_size": 12
+    });
+    let response = app.update_meal(&meal_id, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let meal: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(meal["portion_size"], 12);
+
+    let user_session = app.user_session.clone();
+    app.user_session = app.admin_session.clone();
+    let response = app.delete_meal(&meal_id).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.user_session = user_session;
+    let response = app.delete_meal(&meal_id).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let meals: Vec<serde_json::Value> = app.get_meals().await.json().await.unwrap();
+    assert!(meals.is_empty());
+}
+
+#[tokio::test]
+async fn the_summary_adds_up_weekly_and_monthly_intake() {
+    let app = spawn_app().await;
+    let today = Utc::now().date_naive();
+    for eaten_on in [today, today - Days::new(10), today - Days::new(40)] {
+        let body = serde_json::json!({
+            "fish_type_id": app.fish_type.id,
+            "eaten_on": eaten_on,
+        });
+        app.log_meal(&body).await;
+    }
+
+    let response = app.get_meal_summary().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let summary: serde_json::Value = response.json().await.unwrap();
+    let meal_intake = 1.12 * 8.0 * GRAMS_PER_OZ;
+    assert_eq!(summary["weekly"]["meals"], 1);
+    assert_eq!(summary["monthly"]["meals"], 2);
+    assert_close(&summary["weekly"]["mercury"]["intake"], meal_intake);
+    assert_close(&summary["monthly"]["pcb"]["intake"], 2.0 * meal_intake);
+    assert_close(
+        &summary["weekly"]["mercury"]["limit"],
+        0.3 * DEFAULT_WEIGHT_KG * 7.0,
+    );
+    assert_close(
+        &summary["monthly"]["pcb"]["limit"],
+        0.02 * DEFAULT_WEIGHT_KG * 30.0,
+    );
+}
+
+#[tokio::test]
+async fn the_summary_counts_meals_without_data_per_contaminant() {
+    let app = spawn_app().await;
+    let fish_type = FishType::new(Uuid::new_v4());
+    fish_type.store(&app.db_pool).await;
+    let csv = format!(
+        "fish_type_id,lake,mercury\n{},{},0.5\n",
+        fish_type.id, app.fish.lake
+    );
+    let response = app.import("fish", csv, false).await;
+    assert_eq!(response.status().as_u16(), 200);
+    for fish_type_id in [app.fish_type.id, fish_type.id] {
+        let body = serde_json::json!({
+            "fish_type_id": fish_type_id,
+            "eaten_on": Utc::now().date_naive(),
+        });
+        app.log_meal(&body).await;
+    }
+
+    let response = app.get_meal_summary().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let summary: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(summary["weekly"]["meals"], 2);
+    assert_eq!(summary["weekly"]["mercury"]["meals_without_data"], 0);
+    assert_close(
+        &summary["weekly"]["mercury"]["intake"],
+        (1.12 + 0.5) * 8.0 * GRAMS_PER_OZ,
+    );
+    assert_eq!(summary["weekly"]["pcb"]["meals_without_data"], 1);
+    assert_close(
+        &summary["weekly"]["pcb"]["intake"],
+        1.12 * 8.0 * GRAMS_PER_OZ,
+    );
+}
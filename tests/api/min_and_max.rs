@@ -26,7 +26,15 @@ async fn a_user_should_be_able_to_use_all_valid_lakes() {
 async fn a_user_should_be_able_to_use_all_valid_attrs() {
     let app = spawn_app().await;
 
-    const ATTRS: [&str; 4] = ["protein", "pcb", "mercury", "omega_3_ratio"];
+    const ATTRS: [&str; 7] = [
+        "protein",
+        "pcb",
+        "mercury",
+        "omega_3",
+        "omega_3_ratio",
+        "selenium",
+        "pfas",
+    ];
 
     for attr in ATTRS {
         let response = app.get_min_and_max("Michigan", attr).await;
@@ -0,0 +1,273 @@
+use crate::helpers::{spawn_app, FishType, TestApp};
+use uuid::Uuid;
+
+fn fish_body(fish_type_id: Uuid, measurements: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "fish_type_id": fish_type_id,
+        "lake": "Huron",
+        "mercury": 0.0,
+        "omega_3": 1.1,
+        "omega_3_ratio": 1.1,
+        "pcb": 1.1,
+        "protein": 1.1,
+        "measurements": measurements
+    })
+}
+
+/// A fish type with a detected selenium level of 0.4 and a non-detect below
+/// 0.2.
+async fn fish_type_with_a_non_detect(app: &TestApp) -> Uuid {
+    let fish_type = FishType {
+        name: "Non-detect Fish",
+        ..FishType::new(Uuid::new_v4())
+    };
+    fish_type.store(&app.db_pool).await;
+    for selenium in [
+        serde_json::json!(0.4),
+        serde_json::json!({ "detected": false, "detection_limit": 0.2 }),
+    ] {
+        let response = app
+            .post_new_fish(&fish_body(
+                fish_type.id,
+                serde_json::json!({ "selenium": selenium }),
+            ))
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+    fish_type.id
+}
+
+async fn set_detection_limit(app: &TestApp, analyte: &str, detection_limit: f64) {
+    let analytes: Vec<serde_json::Value> = app.get_analytes().await.json().await.unwrap();
+    let analyte = analytes
+        .iter()
+        .find(|a| a["name"] == analyte)
+        .expect("The analyte is missing.");
+    let mut body = analyte.clone();
+    body["detection_limit"] = serde_json::json!(detection_limit);
+    let response = app
+        .update_analyte(&body, analyte["id"].as_str().unwrap().parse().unwrap())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+async fn fish_avg(app: &TestApp, fish_type_id: Uuid, substitution: &str) -> reqwest::Response {
+    app.api_client
+        .get(format!(
+            "{}/v1/fish_avg?fishtype_id={}&substitution={}",
+            &app.address, fish_type_id, substitution
+        ))
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .send()
+        .await
+        .expect("Failed to get fish type avg.")
+}
+
+#[tokio::test]
+async fn a_non_detect_is_returned_with_its_detection_limit() {
+    let app = spawn_app().await;
+    fish_type_with_a_non_detect(&app).await;
+
+    let fish_id = sqlx::query_scalar!(
+        "SELECT fish_id FROM measurement WHERE analyte = 'selenium' AND NOT detected"
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .expect("Failed to get fish.");
+    let fish: serde_json::Value = app.get_fish_by_id(fish_id).await.json().await.unwrap();
+    let selenium = fish["measurements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["analyte"] == "selenium")
+        .expect("Selenium is missing.");
+    assert_eq!(selenium["value"], 0.0);
+    assert_eq!(selenium["detected"], false);
+    assert_eq!(selenium["detection_limit"], 0.2);
+}
+
+#[tokio::test]
+async fn fish_avg_substitutes_non_detects_as_asked() {
+    let app = spawn_app().await;
+    let fish_type_id = fish_type_with_a_non_detect(&app).await;
+
+    for (substitution, expected) in [("zero", 0.2), ("half_dl", 0.25), ("dl", 0.3)] {
+        let response = fish_avg(&app, fish_type_id, substitution).await;
+        assert_eq!(response.status().as_u16(), 200);
+
+        let avg: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(avg["substitution"], substitution);
+        let selenium = avg["measurements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|m| m["analyte"] == "selenium")
+            .expect("Selenium is missing.");
+        assert!((selenium["value"].as_f64().unwrap() - expected).abs() < 1e-9);
+        assert_eq!(selenium["samples"], 2);
+        assert_eq!(selenium["censored"], 1);
+    }
+}
+
+#[tokio::test]
+async fn legacy_zeros_fall_back_on_the_analyte_detection_limit() {
+    let app = spawn_app().await;
+    let fish_type_id = fish_type_with_a_non_detect(&app).await;
+
+    set_detection_limit(&app, "mercury", 0.05).await;
+
+    let avg: serde_json::Value = fish_avg(&app, fish_type_id, "dl")
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(avg["fish_data"]["mercury"], 0.05);
+
+    let min_and_max: serde_json::Value = app
+        .api_client
+        .get(format!(
+            "{}/v1/min_and_max?attr=mercury&substitution=half_dl",
+            &app.address
+        ))
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .send()
+        .await
+        .expect("Failed to get min and max.")
+        .json()
+        .await
+        .unwrap();
+    assert!((min_and_max["min"]["value"].as_f64().unwrap() - 0.025).abs() < 1e-6);
+    assert_eq!(min_and_max["min"]["censored"], 2);
+}
+
+#[tokio::test]
+async fn stats_and_min_and_max_in_a_lake_substitute_non_detects_as_asked() {
+    let app = spawn_app().await;
+    // Two mercury non-detects below 0.2 and a detected 0.4, all in Huron.
+    let fish_type_id = fish_type_with_a_non_detect(&app).await;
+    set_detection_limit(&app, "mercury", 0.2).await;
+    let mut body = fish_body(fish_type_id, serde_json::json!({}));
+    body["mercury"] = serde_json::json!(0.4);
+    let response = app.post_new_fish(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    for (substitution, mean, min) in [
+        ("zero", 0.4 / 3.0, 0.0),
+        ("half_dl", 0.6 / 3.0, 0.1),
+        ("dl", 0.8 / 3.0, 0.2),
+    ] {
+        let response = app
+            .get_stats(&format!(
+                "lake=Huron&fish_type={fish_type_id}&attr=mercury&substitution={substitution}"
+            ))
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["substitution"], substitution);
+        let stats = &body["stats"][0];
+        assert_eq!(stats["below_detection"], 2);
+        assert!((stats["mean"].as_f64().unwrap() - mean).abs() < 1e-6);
+        assert!((stats["min"].as_f64().unwrap() - min).abs() < 1e-6);
+
+        let min_and_max: serde_json::Value = app
+            .api_client
+            .get(format!(
+                "{}/v1/min_and_max?lake=Huron&attr=mercury&substitution={}",
+                &app.address, substitution
+            ))
+            .header("Authorization", &format!("Bearer {}", &app.api_key))
+            .send()
+            .await
+            .expect("Failed to get min and max.")
+            .json()
+            .await
+            .unwrap();
+        assert!((min_and_max["min"]["value"].as_f64().unwrap() - min).abs() < 1e-6);
+        assert!((min_and_max["max"]["value"].as_f64().unwrap() - 0.4).abs() < 1e-6);
+    }
+}
+
+#[tokio::test]
+async fn zeros_of_nutrients_are_not_substituted() {
+    let app = spawn_app().await;
+    let fish_type = FishType {
+        name: "Lean Fish",
+        ..FishType::new(Uuid::new_v4())
+    };
+    fish_type.store(&app.db_pool).await;
+    set_detection_limit(&app, "omega_3", 0.5).await;
+    set_detection_limit(&app, "mercury", 0.2).await;
+    let mut body = fish_body(fish_type.id, serde_json::json!({}));
+    body["omega_3"] = serde_json::json!(0.0);
+    let response = app.post_new_fish(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .get_stats(&format!("fish_type={}&substitution=dl", fish_type.id))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let stats = |attr: &str| {
+        body["stats"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|stats| stats["attr"] == attr)
+            .cloned()
+            .unwrap()
+    };
+    assert_eq!(stats("omega_3")["below_detection"], 0);
+    assert_eq!(stats("omega_3")["mean"], 0.0);
+    // A zero of a contaminant is still a non-detect.
+    assert_eq!(stats("mercury")["below_detection"], 1);
+    assert!((stats("mercury")["mean"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn inconsistent_non_detects_and_unknown_substitutions_are_rejected() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_new_fish(&fish_body(
+            app.fish_type.id,
+            serde_json::json!({ "selenium": { "value": 0.3, "detected": false } }),
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = fish_avg(&app, app.fish_type.id, "half").await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn endpoints_without_a_substitution_average_like_the_default() {
+    let app = spawn_app().await;
+    let fish_type_id = fish_type_with_a_non_detect(&app).await;
+
+    let avg: serde_json::Value = fish_avg(&app, fish_type_id, "zero")
+        .await
+        .json()
+        .await
+        .unwrap();
+    let expected = avg["measurements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["analyte"] == "selenium")
+        .expect("Selenium is missing.")["value"]
+        .as_f64()
+        .unwrap();
+
+    let response = app
+        .get_compare(&format!(
+            "fish_type={}&lake=Huron&attr=selenium",
+            fish_type_id
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let compared = body["fish"][0]["measurements"][0]["value"]
+        .as_f64()
+        .unwrap();
+    assert!((compared - expected).abs() < 1e-9);
+}
@@ -0,0 +1,117 @@
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn a_user_can_reset_their_password() {
+    let app = spawn_app().await;
+
+    let response = app
+        .request_password_reset(format!("email={}", &app.test_user.email))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = app.token_from_email(&app.test_user.email);
+    let new_password = uuid::Uuid::new_v4().to_string();
+    let response = app
+        .confirm_password_reset(format!(
+            "token={}&new_password={}&new_password_check={}",
+            token, new_password, new_password
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .login(format!(
+            "email={}&password={}",
+            &app.test_user.email, &app.test_user.password_hash
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .login(format!(
+            "email={}&password={}",
+            &app.test_user.email, new_password
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Existing sessions were logged out.
+    let response = app.favorite_fish(&app.fish_type.id).await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn a_reset_token_can_only_be_used_once() {
+    let app = spawn_app().await;
+
+    app.request_password_reset(format!("email={}", &app.test_user.email))
+        .await;
+    let token = app.token_from_email(&app.test_user.email);
+    let body = format!(
+        "token={}&new_password=new-password&new_password_check=new-password",
+        token
+    );
+
+    let response = app.confirm_password_reset(body.clone()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.confirm_password_reset(body).await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn requesting_a_new_token_invalidates_the_previous_one() {
+    let app = spawn_app().await;
+
+    app.request_password_reset(format!("email={}", &app.test_user.email))
+        .await;
+    let first_token = app.token_from_email(&app.test_user.email);
+    app.request_password_reset(format!("email={}", &app.test_user.email))
+        .await;
+
+    let response = app
+        .confirm_password_reset(format!(
+            "token={}&new_password=new-password&new_password_check=new-password",
+            first_token
+        ))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn an_unknown_email_does_not_send_a_reset_email() {
+    let app = spawn_app().await;
+
+    let response = app
+        .request_password_reset("email=nobody@example.com".to_string())
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(app.email_client.sent_emails().is_empty());
+}
+
+#[tokio::test]
+async fn the_new_passwords_must_match_and_the_token_must_be_valid() {
+    let app = spawn_app().await;
+
+    app.request_password_reset(format!("email={}", &app.test_user.email))
+        .await;
+    let token = app.token_from_email(&app.test_user.email);
+
+    let response = app
+        .confirm_password_reset(format!(
+            "token={}&new_password=new-password&new_password_check=other-password",
+            token
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app
+        .confirm_password_reset(
+            "token=not-a-token&new_password=new-password&new_password_check=new-password"
+                .to_string(),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+}
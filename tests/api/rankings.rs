@@ -0,0 +1,201 @@
+use crate::helpers::{spawn_app, spawn_app_with, FishType, TestApp};
+use uuid::Uuid;
+
+struct Sample {
+    mercury: f32,
+    omega_3: f32,
+    pcb: f32,
+}
+
+async fn store_fish_type(app: &TestApp, lake: &str, sample: Sample) -> Uuid {
+    let fish_type = FishType::new(Uuid::new_v4());
+    fish_type.store(&app.db_pool).await;
+    let response = app
+        .post_new_fish(&serde_json::json!({
+            "fish_type_id": fish_type.id,
+            "lake": lake,
+            "mercury": sample.mercury,
+            "omega_3": sample.omega_3,
+            "omega_3_ratio": 1.0,
+            "pcb": sample.pcb,
+            "protein": 1.0
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    fish_type.id
+}
+
+fn ranked_ids(body: &serde_json::Value) -> Vec<String> {
+    body["fish"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|fish| fish["fish_type_id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn rankings_sort_the_fish_in_a_lake_best_first() {
+    let app = spawn_app().await;
+    let oily = store_fish_type(
+        &app,
+        "Huron",
+        Sample {
+            mercury: 0.1,
+            omega_3: 2.0,
+            pcb: 0.0,
+        },
+    )
+    .await;
+    let lean = store_fish_type(
+        &app,
+        "Huron",
+        Sample {
+            mercury: 0.05,
+            omega_3: 0.2,
+            pcb: 0.0,
+        },
+    )
+    .await;
+    let risky = store_fish_type(
+        &app,
+        "Huron",
+        Sample {
+            mercury: 0.5,
+            omega_3: 1.0,
+            pcb: 0.0,
+        },
+    )
+    .await;
+    store_fish_type(
+        &app,
+        "Superior",
+        Sample {
+            mercury: 0.01,
+            omega_3: 9.0,
+            pcb: 0.0,
+        },
+    )
+    .await;
+
+    let test_cases = [
+        ("benefit_risk", [&oily, &lean, &risky]),
+        ("mercury", [&lean, &oily, &risky]),
+        ("omega_3", [&oily, &risky, &lean]),
+    ];
+    for (sort, expected) in test_cases {
+        let response = app.get_rankings(&format!("lake=Huron&sort={}", sort)).await;
+        assert_eq!(response.status().as_u16(), 200);
+
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(
+            ranked_ids(&body),
+            expected.map(|id| id.to_string()),
+            "{}",
+            sort
+        );
+        assert_eq!(body["fish"][0]["rank"], 1);
+    }
+}
+
+#[tokio::test]
+async fn rankings_respect_the_limit() {
+    let app = spawn_app().await;
+    for _ in 0..3 {
+        store_fish_type(
+            &app,
+            "Huron",
+            Sample {
+                mercury: 0.1,
+                omega_3: 1.0,
+                pcb: 0.0,
+            },
+        )
+        .await;
+    }
+
+    let response = app.get_rankings("lake=Huron&limit=2").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["fish"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn the_pcb_penalty_is_configurable() {
+    let app = spawn_app_with(|config| config.scoring.pcb_penalty = 100.0).await;
+    let clean = store_fish_type(
+        &app,
+        "Huron",
+        Sample {
+            mercury: 0.1,
+            omega_3: 1.0,
+            pcb: 0.0,
+        },
+    )
+    .await;
+    let oily_with_pcb = store_fish_type(
+        &app,
+        "Huron",
+        Sample {
+            mercury: 0.1,
+            omega_3: 2.0,
+            pcb: 0.2,
+        },
+    )
+    .await;
+
+    let response = app.get_rankings("lake=Huron").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        ranked_ids(&body),
+        [clean.to_string(), oily_with_pcb.to_string()]
+    );
+}
+
+#[tokio::test]
+async fn rankings_sort_by_any_analyte_contaminants_lowest_first() {
+    let app = spawn_app().await;
+    let mut ids = vec![];
+    for selenium in [0.6, 0.2, 0.4] {
+        let fish_type = FishType::new(Uuid::new_v4());
+        fish_type.store(&app.db_pool).await;
+        let response = app
+            .post_new_fish(&serde_json::json!({
+                "fish_type_id": fish_type.id,
+                "lake": "Huron",
+                "mercury": 0.1,
+                "omega_3": 1.0,
+                "omega_3_ratio": 1.0,
+                "pcb": 0.0,
+                "protein": 1.0,
+                "measurements": { "selenium": selenium }
+            }))
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+        ids.push(fish_type.id.to_string());
+    }
+
+    let response = app.get_rankings("lake=Huron&sort=selenium").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["sort"], "selenium");
+    assert_eq!(
+        ranked_ids(&body),
+        [ids[1].clone(), ids[2].clone(), ids[0].clone()]
+    );
+    assert!((body["fish"][0]["sort_value"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn rankings_reject_an_invalid_sort_or_lake() {
+    let app = spawn_app().await;
+
+    for query in ["sort=weight", "lake=Atlantis"] {
+        let response = app.get_rankings(query).await;
+        assert_eq!(response.status().as_u16(), 400, "{}", query);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::helpers::spawn_app;
+use crate::helpers::{spawn_app, spawn_app_with};
 use fake::faker::internet::en::SafeEmail;
 use fake::Fake;
 
@@ -48,3 +48,123 @@ async fn subscribe_returns_a_400_with_incomplete_data() {
 
     assert_eq!(response.status().as_u16(), 400);
 }
+
+#[tokio::test]
+async fn registering_sends_a_link_that_verifies_the_email() {
+    let app = spawn_app().await;
+    let email: String = SafeEmail().fake();
+
+    let response = app.register(format!("email={email}&password=test")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let user_id = response.json::<uuid::Uuid>().await.unwrap();
+
+    let link = app.link_from_email(&email);
+    // The link is opened from an email client, so it works without the API key.
+    let response = reqwest::get(&link).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let saved = sqlx::query!("SELECT email_verified_at FROM users WHERE id = $1", user_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved user.");
+    assert!(saved.email_verified_at.is_some());
+
+    let response = reqwest::get(&link).await.unwrap();
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn registering_an_email_twice_returns_a_409() {
+    let app = spawn_app().await;
+    let email: String = SafeEmail().fake();
+
+    let response = app.register(format!("email={email}&password=test")).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.register(format!("email={email}&password=other")).await;
+    assert_eq!(response.status().as_u16(), 409);
+}
+
+#[tokio::test]
+async fn a_new_verification_link_replaces_the_old_one() {
+    let app = spawn_app().await;
+    let email: String = SafeEmail().fake();
+
+    app.register(format!("email={email}&password=test")).await;
+    let old_link = app.link_from_email(&email);
+
+    let response = app.resend_verification(&email).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let new_link = app.link_from_email(&email);
+    assert_ne!(old_link, new_link);
+
+    let response = reqwest::get(&old_link).await.unwrap();
+    assert_eq!(response.status().as_u16(), 400);
+    let response = reqwest::get(&new_link).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Verified and unknown emails get the same response, without an email.
+    let sent = app.email_client.sent_emails().len();
+    let response = app.resend_verification(&email).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let unknown: String = SafeEmail().fake();
+    let response = app.resend_verification(&unknown).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.email_client.sent_emails().len(), sent);
+}
+
+#[tokio::test]
+async fn register_returns_a_400_for_an_invalid_email() {
+    let app = spawn_app().await;
+
+    let response = app
+        .register("email=not-an-email&password=test".to_string())
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert!(app.email_client.sent_emails().is_empty());
+}
+
+#[tokio::test]
+async fn unverified_users_cannot_login_when_the_policy_forbids_it() {
+    let app = spawn_app_with(|config| config.application.unverified_users.can_login = false).await;
+    let email: String = SafeEmail().fake();
+
+    app.register(format!("email={email}&password=test")).await;
+
+    let response = app.login(format!("email={email}&password=test")).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    reqwest::get(&app.link_from_email(&email)).await.unwrap();
+
+    let response = app.login(format!("email={email}&password=test")).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let attempts: Vec<serde_json::Value> = app
+        .api_client
+        .get(format!(
+            "{}/v1/admin/login_attempt/?email={}",
+            &app.address, email
+        ))
+        .header("Cookie", &format!("session={}", &app.admin_session))
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .send()
+        .await
+        .expect("Failed to get login attempts.")
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[0]["outcome"], "success");
+    assert_eq!(attempts[1]["outcome"], "email_not_verified");
+}
+
+#[tokio::test]
+async fn unverified_users_cannot_favorite_when_the_policy_forbids_it() {
+    let app =
+        spawn_app_with(|config| config.application.unverified_users.can_favorite = false).await;
+
+    let response = app.favorite_fish(&app.fish_type.id).await;
+
+    assert_eq!(response.status().as_u16(), 403);
+}
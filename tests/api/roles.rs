@@ -0,0 +1,75 @@
+use crate::helpers::{grant_role, spawn_app};
+use uuid::Uuid;
+
+fn fish_body(fish_type_id: &Uuid) -> serde_json::Value {
+    serde_json::json!({
+        "fish_type_id": fish_type_id,
+        "lake": "Superior",
+        "mercury": 1.1,
+        "omega_3": 1.1,
+        "omega_3_ratio": 1.1,
+        "pcb": 1.1,
+        "protein": 1.1
+    })
+}
+
+fn recipe_body() -> serde_json::Value {
+    serde_json::json!({
+        "name": Uuid::new_v4(),
+        "image_url": "https://fake_url.com",
+        "steps": ["step"],
+        "ingredients": ["ingredient"]
+    })
+}
+
+#[tokio::test]
+async fn users_without_a_role_are_rejected_from_admin_routes() {
+    let mut app = spawn_app().await;
+    app.admin_session = app.user_session.clone();
+
+    let response = app.post_new_fish(&fish_body(&app.fish_type.id)).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn data_stewards_can_manage_fish_but_not_recipes() {
+    let mut app = spawn_app().await;
+    grant_role(&app.db_pool, app.test_user.id, "data_steward").await;
+    app.admin_session = app.user_session.clone();
+
+    let response = app.post_new_fish(&fish_body(&app.fish_type.id)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_new_recipe(&recipe_body()).await;
+    assert_eq!(response.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn content_editors_can_manage_recipes_but_not_fish() {
+    let mut app = spawn_app().await;
+    grant_role(&app.db_pool, app.test_user.id, "content_editor").await;
+    app.admin_session = app.user_session.clone();
+
+    let response = app.post_new_recipe(&recipe_body()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_new_fish(&fish_body(&app.fish_type.id)).await;
+    assert_eq!(response.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn login_reports_users_with_a_role_as_admins() {
+    let app = spawn_app().await;
+    grant_role(&app.db_pool, app.test_user.id, "content_editor").await;
+
+    let body = format!(
+        "email={}&password={}",
+        &app.test_user.email, &app.test_user.password_hash
+    );
+    let response = app.login(body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["is_admin"], true);
+}
@@ -1,24 +1,102 @@
 use crate::helpers::spawn_app;
 use fishy_edge::routes::SearchResult;
+use uuid::Uuid;
 
 #[tokio::test]
-async fn search_should_return_everything_for_the_client() {
+async fn search_requires_a_query() {
     let app = spawn_app().await;
 
-    let response = app.get_search().await;
+    let response = app.get_search("  ").await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn search_finds_fish_types_by_name() {
+    let app = spawn_app().await;
+
+    let response = app.get_search("test fish").await;
 
     assert_eq!(response.status().as_u16(), 200);
+    let body: SearchResult = response.json().await.expect("Failed to parse response.");
+    assert_eq!(body.query, "test fish");
+    assert_eq!(body.results[0].result_type, "fish_type");
+    assert_eq!(body.results[0].id, app.fish_type.id);
+}
+
+#[tokio::test]
+async fn search_finds_fish_types_by_anishinaabe_name_and_typos() {
+    let app = spawn_app().await;
+
+    for query in ["anishaabe", "Test Fsh"] {
+        let response = app.get_search(query).await;
+        let body: SearchResult = response.json().await.expect("Failed to parse response.");
+
+        assert!(
+            body.results.iter().any(|hit| hit.id == app.fish_type.id),
+            "No hit for {query}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn search_finds_recipes_by_ingredient_with_a_highlight() {
+    let app = spawn_app().await;
+    let recipe_id = Uuid::new_v4();
+    sqlx::query!(
+        "INSERT INTO recipe (id, name, ingredients, steps) VALUES ($1, $2, $3, $4)",
+        recipe_id,
+        "Pan Fried Dinner",
+        &["2 walleye fillets".to_string(), "1 cup flour".to_string()],
+        &["Dredge the fillets in flour and fry.".to_string()]
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the recipe.");
+
+    let response = app.get_search("walleye").await;
 
     let body: SearchResult = response.json().await.expect("Failed to parse response.");
+    assert_eq!(body.results.len(), 1);
+    assert_eq!(body.results[0].result_type, "recipe");
+    assert_eq!(body.results[0].id, recipe_id);
+    assert!(body.results[0]
+        .highlight
+        .as_deref()
+        .unwrap()
+        .contains("<mark>walleye</mark>"));
+}
 
-    assert!(body.fishs.len() >= 1);
-    // This could be false unless app is created with a recipe pre-defined.
-    // assert!(body.recipes.len() >= 1);
+#[tokio::test]
+async fn search_returns_nothing_for_unrelated_queries() {
+    let app = spawn_app().await;
 
-    assert!(body
-        .fishs
-        .iter()
-        .map(|fish| fish.id)
-        .collect::<Vec<uuid::Uuid>>()
-        .contains(&app.fish_type.id))
+    let response = app.get_search("xylophone").await;
+
+    let body: SearchResult = response.json().await.expect("Failed to parse response.");
+    assert!(body.results.is_empty());
+}
+
+#[tokio::test]
+async fn search_highlights_escape_the_matched_text() {
+    let app = spawn_app().await;
+    sqlx::query!(
+        "INSERT INTO recipe (id, name, ingredients, steps) VALUES ($1, $2, $3, $4)",
+        Uuid::new_v4(),
+        "Walleye Bake",
+        &["2 walleye <img src=x onerror=alert(1)> & butter".to_string()],
+        &["Bake.".to_string()]
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to store the recipe.");
+
+    let response = app.get_search("walleye").await;
+
+    let body: SearchResult = response.json().await.expect("Failed to parse response.");
+    let highlight = body.results[0].highlight.as_deref().unwrap();
+    assert_eq!(
+        highlight,
+        "<mark>walleye</mark> &lt;img src=x onerror=alert(1)&gt; &amp; butter Bake"
+    );
 }
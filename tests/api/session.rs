@@ -0,0 +1,280 @@
+use crate::helpers::{spawn_app, spawn_app_with};
+use fishy_edge::authentication::{create_session, SessionDevice};
+
+#[derive(serde::Deserialize)]
+struct LoginResponseBody {
+    session_token: String,
+    refresh_token: String,
+}
+
+#[derive(serde::Deserialize)]
+struct SessionBody {
+    user_agent: Option<String>,
+    current: bool,
+}
+
+#[tokio::test]
+async fn login_returns_a_session_token_that_identifies_the_user() {
+    let app = spawn_app().await;
+
+    let body = format!(
+        "email={}&password={}",
+        &app.test_user.email, &app.test_user.password_hash
+    );
+    let response = app.login(body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let cookie = response
+        .headers()
+        .get("Set-Cookie")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(cookie.starts_with("session="));
+
+    let response_body: LoginResponseBody = response.json().await.unwrap();
+
+    let response = app
+        .api_client
+        .get(format!("{}/v1/favorite/", &app.address))
+        .header("X-Session-Token", &response_body.session_token)
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .send()
+        .await
+        .expect("Failed to get favorites.");
+
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn a_tampered_session_token_is_rejected() {
+    let app = spawn_app().await;
+
+    // Swap the user in the token for the admin, keeping the original signature.
+    let token = app.user_session.replace(
+        &app.test_user.id.to_string(),
+        &app.admin_user.user_id.to_string(),
+    );
+
+    let response = app
+        .api_client
+        .get(format!("{}/v1/admin/fish_type/", &app.address))
+        .header("Cookie", &format!("session={}", token))
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .send()
+        .await
+        .expect("Failed to get all fish types.");
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn an_expired_session_is_rejected() {
+    let mut app = spawn_app().await;
+
+    let mut settings = app.session_settings.clone();
+    settings.ttl_minutes = -1;
+    app.user_session = create_session(
+        &app.db_pool,
+        app.test_user.id,
+        &SessionDevice::default(),
+        &settings,
+    )
+    .await
+    .expect("Failed to create session.")
+    .token;
+
+    let response = app.favorite_fish(&app.fish_type.id).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn changing_the_password_revokes_every_session() {
+    let app = spawn_app().await;
+
+    let response = app.favorite_fish(&app.fish_type.id).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let new_password = uuid::Uuid::new_v4().to_string();
+    let body = format!(
+        "current_password={}&new_password={}&new_password_check={}",
+        &app.test_user.password_hash, new_password, new_password
+    );
+    let response = app.change_password(body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.favorite_fish(&app.fish_type.id).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn the_user_id_cookie_is_rejected_when_disabled() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .get(format!("{}/v1/favorite/", &app.address))
+        .header("Cookie", &format!("user_id={}", &app.test_user.id))
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .send()
+        .await
+        .expect("Failed to get favorites.");
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn the_user_id_cookie_of_a_disabled_user_is_rejected() {
+    let app = spawn_app_with(|config| config.session.allow_user_id_cookie = true).await;
+    let favorites = |user_id| {
+        app.api_client
+            .get(format!("{}/v1/favorite/", &app.address))
+            .header("Cookie", &format!("user_id={}", user_id))
+            .header("Authorization", &format!("Bearer {}", &app.api_key))
+            .send()
+    };
+
+    let response = favorites(app.test_user.id).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    sqlx::query!(
+        "UPDATE users SET disabled_at = now() WHERE id = $1;",
+        app.test_user.id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let response = favorites(app.test_user.id).await.unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = favorites(uuid::Uuid::new_v4()).await.unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn a_refresh_token_can_be_exchanged_once_for_a_new_session_token() {
+    let mut app = spawn_app().await;
+
+    let body = format!(
+        "email={}&password={}",
+        &app.test_user.email, &app.test_user.password_hash
+    );
+    let login: LoginResponseBody = app.login(body).await.json().await.unwrap();
+
+    let response = app
+        .refresh(format!("refresh_token={}", &login.refresh_token))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let refreshed: LoginResponseBody = response.json().await.unwrap();
+    assert_ne!(refreshed.refresh_token, login.refresh_token);
+
+    app.user_session = refreshed.session_token;
+    let response = app.favorite_fish(&app.fish_type.id).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // The old refresh token has been rotated out.
+    let response = app
+        .refresh(format!("refresh_token={}", &login.refresh_token))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn logging_out_ends_the_session() {
+    let app = spawn_app().await;
+
+    let response = app.logout().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.favorite_fish(&app.fish_type.id).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn revoking_all_sessions_ends_every_session() {
+    let mut app = spawn_app().await;
+    let other_session = app.create_session(app.test_user.id).await;
+
+    let response = app.revoke_all_sessions().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.favorite_fish(&app.fish_type.id).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.user_session = other_session;
+    let response = app.favorite_fish(&app.fish_type.id).await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn a_user_can_list_their_sessions() {
+    let mut app = spawn_app().await;
+
+    let body = format!(
+        "email={}&password={}",
+        &app.test_user.email, &app.test_user.password_hash
+    );
+    let response = app
+        .api_client
+        .post(format!("{}/v1/login", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("User-Agent", "fishy-edge-tests")
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .body(body)
+        .send()
+        .await
+        .expect("Failed to login.");
+    let login: LoginResponseBody = response.json().await.unwrap();
+    app.user_session = login.session_token;
+
+    let response = app.get_sessions().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let sessions: Vec<SessionBody> = response.json().await.unwrap();
+    assert_eq!(sessions.len(), 2);
+    let current = sessions.iter().find(|session| session.current).unwrap();
+    assert_eq!(current.user_agent.as_deref(), Some("fishy-edge-tests"));
+}
+
+#[tokio::test]
+async fn last_seen_at_is_only_updated_when_stale() {
+    let app = spawn_app().await;
+    let set_last_seen_at = |minutes_ago: i32| {
+        sqlx::query!(
+            r#"
+            UPDATE session
+            SET last_seen_at = now() - make_interval(mins => $2)
+            WHERE user_id = $1;
+            "#,
+            app.test_user.id,
+            minutes_ago
+        )
+        .execute(&app.db_pool)
+    };
+    let minutes_since_seen = || async {
+        sqlx::query!(
+            r#"
+            SELECT EXTRACT(EPOCH FROM now() - MAX(last_seen_at))::FLOAT8 / 60 as "minutes!"
+            FROM session
+            WHERE user_id = $1;
+            "#,
+            app.test_user.id
+        )
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .minutes
+    };
+
+    set_last_seen_at(1).await.unwrap();
+    let response = app.get_sessions().await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(minutes_since_seen().await > 0.5);
+
+    set_last_seen_at(10).await.unwrap();
+    let response = app.get_sessions().await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(minutes_since_seen().await < 0.5);
+}
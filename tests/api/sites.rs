@@ -0,0 +1,103 @@
+use crate::helpers::{spawn_app, TestApp};
+use uuid::Uuid;
+
+async fn store_site(app: &TestApp, name: &str, latitude: f64, longitude: f64) -> Uuid {
+    let response = app
+        .post_new_sampling_site(&serde_json::json!({
+            "name": name,
+            "water_body": name,
+            "lake": "Superior",
+            "agency": "GLIFWC",
+            "latitude": latitude,
+            "longitude": longitude
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    body["id"].as_str().unwrap().parse().unwrap()
+}
+
+fn fish_body(fish_type_id: &Uuid, sampling_site_id: &Uuid, mercury: f32) -> serde_json::Value {
+    serde_json::json!({
+        "fish_type_id": fish_type_id,
+        "lake": "Superior",
+        "mercury": mercury,
+        "omega_3": 1.1,
+        "omega_3_ratio": 1.1,
+        "pcb": 1.1,
+        "protein": 1.1,
+        "sampling_site_id": sampling_site_id
+    })
+}
+
+#[tokio::test]
+async fn sites_near_returns_sites_in_the_radius_closest_first() {
+    let app = spawn_app().await;
+    let ashland = store_site(&app, "Chequamegon Bay", 46.59, -90.88).await;
+    let duluth = store_site(&app, "Duluth Harbor", 46.78, -92.10).await;
+    store_site(&app, "Marquette", 46.55, -87.40).await;
+
+    let response = app.get_sites_near("lat=46.6&lon=-90.9&radius_km=120").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let sites: Vec<serde_json::Value> = response.json().await.unwrap();
+    let ids: Vec<&str> = sites.iter().map(|s| s["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, [ashland.to_string(), duluth.to_string()]);
+    let duluth_km = sites[1]["distance_km"].as_f64().unwrap();
+    assert!((90.0..100.0).contains(&duluth_km), "{}", duluth_km);
+}
+
+#[tokio::test]
+async fn sites_near_includes_the_averaged_species_of_each_site() {
+    let app = spawn_app().await;
+    let site = store_site(&app, "Chequamegon Bay", 46.59, -90.88).await;
+    for mercury in [0.2, 0.4] {
+        let response = app
+            .post_new_fish(&fish_body(&app.fish_type.id, &site, mercury))
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app.get_sites_near("lat=46.59&lon=-90.88").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let sites: Vec<serde_json::Value> = response.json().await.unwrap();
+    let species = sites[0]["species"].as_array().unwrap();
+    assert_eq!(species.len(), 1);
+    assert_eq!(species[0]["fish_type_id"], app.fish_type.id.to_string());
+    assert_eq!(species[0]["samples"], 2);
+    assert!((species[0]["mercury"].as_f64().unwrap() - 0.3).abs() < 1e-3);
+}
+
+#[tokio::test]
+async fn sites_near_rejects_invalid_queries() {
+    let app = spawn_app().await;
+    let test_cases = [
+        ("lat=91&lon=-90", "an out of range latitude"),
+        ("lat=46&lon=-190", "an out of range longitude"),
+        ("lat=46&lon=-90&radius_km=0", "a zero radius"),
+        ("lat=46&lon=-90&radius_km=1000", "a too large radius"),
+        ("lat=46", "a missing longitude"),
+    ];
+
+    for (query, description) in test_cases {
+        let response = app.get_sites_near(query).await;
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "The API did not reject a query with {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn new_fish_rejects_an_unknown_sampling_site() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_new_fish(&fish_body(&app.fish_type.id, &Uuid::new_v4(), 0.2))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
@@ -0,0 +1,151 @@
+use crate::helpers::{spawn_app, TestApp};
+
+async fn store_sample(app: &TestApp, lake: &str, mercury: f32) {
+    let response = app
+        .post_new_fish(&serde_json::json!({
+            "fish_type_id": app.fish_type.id,
+            "lake": lake,
+            "mercury": mercury,
+            "omega_3": 1.0,
+            "omega_3_ratio": 1.0,
+            "pcb": 1.0,
+            "protein": 1.0
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn stats_summarise_an_attribute_in_a_lake() {
+    let app = spawn_app().await;
+    for mercury in [0.0, 1.0, 2.0, 3.0, 4.0] {
+        store_sample(&app, "Huron", mercury).await;
+    }
+    store_sample(&app, "Superior", 100.0).await;
+
+    let response = app.get_stats("lake=Huron&attr=mercury").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let stats = body["stats"].as_array().unwrap();
+    assert_eq!(stats.len(), 1);
+    let mercury = &stats[0];
+    assert_eq!(mercury["attr"], "mercury");
+    assert_eq!(mercury["count"], 5);
+    assert_eq!(mercury["below_detection"], 1);
+    assert_eq!(mercury["mean"], 2.0);
+    assert_eq!(mercury["median"], 2.0);
+    assert_eq!(mercury["min"], 0.0);
+    assert_eq!(mercury["max"], 4.0);
+    assert!((mercury["std_dev"].as_f64().unwrap() - 2.5_f64.sqrt()).abs() < 1e-9);
+    assert!((mercury["percentiles"]["p10"].as_f64().unwrap() - 0.4).abs() < 1e-9);
+    assert!((mercury["percentiles"]["p90"].as_f64().unwrap() - 3.6).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn stats_cover_every_attribute_by_default() {
+    let app = spawn_app().await;
+    store_sample(&app, "Huron", 1.0).await;
+
+    let response = app
+        .get_stats(&format!("fish_type={}", app.fish_type.id))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let attrs: Vec<&str> = body["stats"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["attr"].as_str().unwrap())
+        .collect();
+    let analytes: Vec<serde_json::Value> = app.get_analytes().await.json().await.unwrap();
+    let names: Vec<&str> = analytes
+        .iter()
+        .map(|analyte| analyte["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(attrs, names);
+    assert!(attrs.contains(&"selenium"));
+}
+
+#[tokio::test]
+async fn stats_summarise_any_analyte_in_the_catalog() {
+    let app = spawn_app().await;
+    for selenium in [0.2, 0.4] {
+        let response = app
+            .post_new_fish(&serde_json::json!({
+                "fish_type_id": app.fish_type.id,
+                "lake": "Huron",
+                "mercury": 1.0,
+                "omega_3": 1.0,
+                "omega_3_ratio": 1.0,
+                "pcb": 1.0,
+                "protein": 1.0,
+                "measurements": { "selenium": selenium }
+            }))
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app.get_stats("lake=Huron&attr=selenium").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let selenium = &body["stats"][0];
+    assert_eq!(selenium["attr"], "selenium");
+    assert_eq!(selenium["count"], 2);
+    assert!((selenium["mean"].as_f64().unwrap() - 0.3).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn stats_without_samples_are_empty() {
+    let app = spawn_app().await;
+
+    let response = app.get_stats("lake=Inland&attr=omega_3").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["stats"][0]["count"], 0);
+    assert!(body["stats"][0]["mean"].is_null());
+}
+
+#[tokio::test]
+async fn stats_reject_an_invalid_attr_or_lake() {
+    let app = spawn_app().await;
+
+    for query in ["attr=weight", "lake=Atlantis"] {
+        let response = app.get_stats(query).await;
+        assert_eq!(response.status().as_u16(), 400, "{}", query);
+    }
+}
+
+#[tokio::test]
+async fn stats_only_count_measurements_reported_as_non_detects() {
+    let app = spawn_app().await;
+    for mercury in [0.0, 1.0] {
+        store_sample(&app, "Huron", mercury).await;
+    }
+    // A measured 0 was detected, it's just too small to tell apart from 0.
+    sqlx::query!(
+        r#"
+        UPDATE measurement
+        SET value = 0
+        FROM fish
+        WHERE fish.id = measurement.fish_id
+            AND fish.lake = 'Huron'
+            AND measurement.analyte = 'mercury'
+            AND measurement.detected
+        "#
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let response = app.get_stats("lake=Huron&attr=mercury").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["stats"][0]["count"], 2);
+    assert_eq!(body["stats"][0]["below_detection"], 1);
+    assert_eq!(body["stats"][0]["max"], 0.0);
+}
@@ -0,0 +1,151 @@
+use crate::helpers::{spawn_app, TestApp};
+
+async fn store_sample(app: &TestApp, lake: &str, sample_date: &str, mercury: f32) {
+    let response = app
+        .post_new_fish(&serde_json::json!({
+            "fish_type_id": app.fish_type.id,
+            "lake": lake,
+            "mercury": mercury,
+            "omega_3": 1.1,
+            "omega_3_ratio": 1.1,
+            "pcb": 1.1,
+            "protein": 1.1,
+            "sample_date": sample_date
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn trend_groups_samples_by_year_and_fits_a_slope() {
+    let app = spawn_app().await;
+    store_sample(&app, "Superior", "2018-06-01", 0.5).await;
+    store_sample(&app, "Superior", "2018-09-01", 0.3).await;
+    store_sample(&app, "Superior", "2020-06-01", 0.2).await;
+    store_sample(&app, "Superior", "2022-06-01", 0.1).await;
+    store_sample(&app, "Huron", "2019-06-01", 5.0).await;
+
+    let response = app
+        .get_trend(app.fish_type.id, "attr=mercury&lake=Superior")
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let trend: serde_json::Value = response.json().await.unwrap();
+    let periods = trend["periods"].as_array().unwrap();
+    let years: Vec<&str> = periods
+        .iter()
+        .map(|p| p["period"].as_str().unwrap())
+        .collect();
+    assert_eq!(years, ["2018-01-01", "2020-01-01", "2022-01-01"]);
+    assert_eq!(periods[0]["count"], 2);
+    assert!((periods[0]["mean"].as_f64().unwrap() - 0.4).abs() < 1e-3);
+    assert!((periods[0]["median"].as_f64().unwrap() - 0.4).abs() < 1e-3);
+    assert_eq!(trend["trend"]["samples"], 4);
+    assert!(trend["trend"]["slope_per_year"].as_f64().unwrap() < 0.0);
+    assert!(trend["trend"]["percent_per_year"].as_f64().unwrap() < 0.0);
+}
+
+#[tokio::test]
+async fn trend_without_a_lake_includes_every_lake() {
+    let app = spawn_app().await;
+    store_sample(&app, "Superior", "2018-06-01", 0.5).await;
+    store_sample(&app, "Huron", "2018-07-01", 0.7).await;
+
+    let response = app
+        .get_trend(app.fish_type.id, "attr=mercury&bucket=month")
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let trend: serde_json::Value = response.json().await.unwrap();
+    let periods = trend["periods"].as_array().unwrap();
+    assert_eq!(periods.len(), 2);
+    assert_eq!(periods[1]["period"], "2018-07-01");
+    assert_eq!(trend["trend"]["samples"], 2);
+}
+
+#[tokio::test]
+async fn trend_without_dated_samples_has_no_slope() {
+    let app = spawn_app().await;
+
+    let response = app.get_trend(app.fish_type.id, "attr=mercury").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let trend: serde_json::Value = response.json().await.unwrap();
+    assert!(trend["periods"].as_array().unwrap().is_empty());
+    assert!(trend["trend"]["slope_per_year"].is_null());
+    assert_eq!(trend["trend"]["samples"], 0);
+}
+
+#[tokio::test]
+async fn trend_dates_samples_without_a_sample_date_by_date_sampled() {
+    let app = spawn_app().await;
+    store_sample(&app, "Superior", "2018-06-01", 0.5).await;
+    let csv = format!(
+        "fish_type_id,lake,date_sampled,mercury\n{},Superior,2020-06-01 10:30:00,0.3\n",
+        app.fish_type.id
+    );
+    let response = app.import("fish", csv, false).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .get_trend(app.fish_type.id, "attr=mercury&lake=Superior")
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let trend: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(trend["periods"][1]["period"], "2020-01-01");
+    assert_eq!(trend["trend"]["samples"], 2);
+}
+
+#[tokio::test]
+async fn trend_covers_any_analyte_in_the_catalog() {
+    let app = spawn_app().await;
+    for (sample_date, selenium) in [("2018-06-01", 0.4), ("2020-06-01", 0.2)] {
+        let response = app
+            .post_new_fish(&serde_json::json!({
+                "fish_type_id": app.fish_type.id,
+                "lake": "Superior",
+                "mercury": 0.1,
+                "omega_3": 1.1,
+                "omega_3_ratio": 1.1,
+                "pcb": 1.1,
+                "protein": 1.1,
+                "sample_date": sample_date,
+                "measurements": { "selenium": selenium }
+            }))
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app.get_trend(app.fish_type.id, "attr=selenium").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let trend: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(trend["periods"].as_array().unwrap().len(), 2);
+    assert_eq!(trend["trend"]["samples"], 2);
+    assert!(trend["trend"]["slope_per_year"].as_f64().unwrap() < 0.0);
+}
+
+#[tokio::test]
+async fn trend_rejects_invalid_queries() {
+    let app = spawn_app().await;
+    let test_cases = [
+        ("attr=weight", "an unknown attr"),
+        ("attr=mercury&bucket=week", "an unknown bucket"),
+        ("attr=mercury&lake=Atlantis", "an unknown lake"),
+        ("lake=Superior", "a missing attr"),
+    ];
+
+    for (query, description) in test_cases {
+        let response = app.get_trend(app.fish_type.id, query).await;
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "The API did not reject a query with {}.",
+            description
+        );
+    }
+
+    let response = app.get_trend(uuid::Uuid::new_v4(), "attr=mercury").await;
+    assert_eq!(response.status().as_u16(), 400);
+}
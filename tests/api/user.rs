@@ -8,10 +8,7 @@ async fn a_user_should_be_able_to_update_their_profile() {
     let age = 23;
     let portion_size = 8;
 
-    let body = format!(
-        "user_id={}&weight={weight}&age={age}&portion_size={portion_size}",
-        &app.test_user.id,
-    );
+    let body = format!("weight={weight}&age={age}&portion_size={portion_size}");
 
     let response = app.update_profile(body).await;
 
@@ -28,15 +25,33 @@ async fn a_user_should_be_able_to_update_their_profile() {
     assert_eq!(user.portion_size, Some(portion_size));
 }
 
+#[tokio::test]
+async fn updating_the_profile_without_a_sex_keeps_the_previous_one() {
+    let app = spawn_app().await;
+
+    let response = app
+        .update_profile("weight=130&age=28&sex=female&portion_size=4".to_string())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .update_profile("weight=140&age=29&portion_size=4".to_string())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let user = app
+        .get_test_user_from_db()
+        .await
+        .expect("Failed to get user from the db.");
+    assert_eq!(user.weight, Some(140));
+    assert_eq!(user.sex.as_deref(), Some("female"));
+}
+
 #[tokio::test]
 async fn a_user_should_be_able_to_update_their_account() {
     let app = spawn_app().await;
-    let email = uuid::Uuid::new_v4();
+    let email = format!("{}@example.com", uuid::Uuid::new_v4());
 
-    let body = format!(
-        "user_id={}&email={}&first_name=&last_name=",
-        &app.test_user.id, email
-    );
+    let body = format!("email={}&first_name=&last_name=", email);
 
     let response = app.update_account(body).await;
 
@@ -47,7 +62,7 @@ async fn a_user_should_be_able_to_update_their_account() {
         .await
         .expect("Failed to get user from the db.");
 
-    assert_eq!(user.email, email.to_string());
+    assert_eq!(user.email, email);
     assert_eq!(user.first_name, Some("".to_string()));
     assert_eq!(user.last_name, Some("".to_string()));
 }
@@ -55,9 +70,9 @@ async fn a_user_should_be_able_to_update_their_account() {
 #[tokio::test]
 async fn a_user_should_be_able_to_update_their_account_with_incomplete_data() {
     let app = spawn_app().await;
-    let email = uuid::Uuid::new_v4();
+    let email = format!("{}@example.com", uuid::Uuid::new_v4());
 
-    let body = format!("user_id={}&email={}&last_name=", &app.test_user.id, email);
+    let body = format!("email={}&last_name=", email);
 
     let response = app.update_account(body).await;
 
@@ -68,19 +83,68 @@ async fn a_user_should_be_able_to_update_their_account_with_incomplete_data() {
         .await
         .expect("Failed to get user from the db.");
 
-    assert_eq!(user.email, email.to_string());
+    assert_eq!(user.email, email);
     assert_eq!(user.first_name, None);
     assert_eq!(user.last_name, Some("".to_string()));
 }
 
+#[tokio::test]
+async fn changing_the_email_has_it_verified_again() {
+    let app = spawn_app().await;
+    let verified = format!("{}@example.com", uuid::Uuid::new_v4());
+    sqlx::query!(
+        "UPDATE users SET email = $1, email_verified_at = now() WHERE id = $2",
+        verified,
+        app.test_user.id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let body = format!("email={verified}&first_name=Ann");
+    let response = app.update_account(body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let user = app.get_test_user_from_db().await.unwrap();
+    assert!(user.email_verified_at.is_some());
+    assert!(app.email_client.sent_emails().is_empty());
+
+    let email = format!("{}@example.com", uuid::Uuid::new_v4());
+    let response = app.update_account(format!("email={email}")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let user = app.get_test_user_from_db().await.unwrap();
+    assert!(user.email_verified_at.is_none());
+
+    let response = reqwest::get(&app.link_from_email(&email)).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let user = app.get_test_user_from_db().await.unwrap();
+    assert!(user.email_verified_at.is_some());
+}
+
+#[tokio::test]
+async fn changing_the_email_to_another_users_returns_a_409() {
+    let app = spawn_app().await;
+    let email = format!("{}@example.com", uuid::Uuid::new_v4());
+    sqlx::query!(
+        "UPDATE users SET email = $1 WHERE id = $2",
+        email,
+        app.admin_user.user_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let response = app.update_account(format!("email={email}")).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let response = app.update_account("email=not-an-email".to_string()).await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
 #[tokio::test]
 async fn a_user_should_be_able_to_update_their_profile_image() {
     let app = spawn_app().await;
 
-    let body = format!(
-        "user_id={}&image_url=http://test.url/test/path",
-        &app.test_user.id
-    );
+    let body = "image_url=http://test.url/test/path".to_string();
 
     let response = app.update_image(body).await;
 
@@ -101,7 +165,7 @@ async fn a_user_should_be_able_to_update_their_profile_image() {
 async fn a_user_should_not_be_able_to_update_their_profile_image_with_incomplete_data() {
     let app = spawn_app().await;
 
-    let body = format!("user_id={}", &app.test_user.id);
+    let body = String::new();
 
     let response = app.update_image(body).await;
 
@@ -120,3 +184,77 @@ async fn a_user_should_be_able_to_delete_their_own_account() {
 
     assert!(user.is_err())
 }
+
+#[tokio::test]
+async fn the_deprecated_delete_route_only_deletes_the_signed_in_user() {
+    let app = spawn_app().await;
+    let delete_by_id = |user_id: uuid::Uuid| {
+        app.api_client
+            .delete(format!("{}/v1/user/{}", &app.address, user_id))
+            .header("Cookie", &format!("session={}", &app.user_session))
+            .header("Authorization", &format!("Bearer {}", &app.api_key))
+            .send()
+    };
+
+    let response = delete_by_id(app.admin_user.user_id)
+        .await
+        .expect("Failed to delete account.");
+    assert_eq!(response.status().as_u16(), 403);
+    assert!(app.get_test_user_from_db().await.is_ok());
+
+    let response = delete_by_id(app.test_user.id)
+        .await
+        .expect("Failed to delete account.");
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(app.get_test_user_from_db().await.is_err());
+}
+
+#[tokio::test]
+async fn a_user_id_in_the_body_cant_change_another_users_account() {
+    let app = spawn_app().await;
+    let email = format!("{}@example.com", uuid::Uuid::new_v4());
+
+    let body = format!("user_id={}&email={}", &app.admin_user.user_id, email);
+    let response = app.update_account(body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let user = app
+        .get_test_user_from_db()
+        .await
+        .expect("Failed to get user from the db.");
+    assert_eq!(user.email, email);
+    let admin_email = sqlx::query_scalar!(
+        "SELECT email FROM users WHERE id = $1",
+        app.admin_user.user_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_ne!(admin_email, email);
+}
+
+#[tokio::test]
+async fn updating_or_deleting_an_account_requires_a_session() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .post(format!("{}/v1/user/image", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .body("image_url=http://test.url/test/path")
+        .send()
+        .await
+        .expect("Failed to update image.");
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .api_client
+        .delete(format!("{}/v1/user", &app.address))
+        .header("Authorization", &format!("Bearer {}", &app.api_key))
+        .send()
+        .await
+        .expect("Failed to delete account.");
+    assert_eq!(response.status().as_u16(), 401);
+    assert!(app.get_test_user_from_db().await.is_ok());
+}